@@ -0,0 +1,88 @@
+// A minimal stand-in for a governance program, used by the tests to call vote_cpi
+// It signs with its delegate PDA for whichever voter it is asked to vote for, so it
+// must never be named as the cpi_caller of a real election
+use anchor_lang::prelude::*;
+
+// The voting program's CPI client, generated by its "cpi" feature
+use voting_system::cpi::accounts::VoteCpi;
+use voting_system::program::VotingSystem;
+use voting_system::CPI_VOTER_SEED;
+
+// Declares the program ID (public key) of this Solana program
+declare_id!("GoxGqsFF9H9JrHHHooc6BFyQCFo7mYBmf1E8zoTg6wFw");
+
+#[program]
+pub mod mock_caller {
+    use super::*;
+
+    // INSTRUCTION #1: Cast a vote for a voter through voting-system's vote_cpi
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_id: The stable ID of the candidate to vote for
+    // - expected_round: The election round the vote is meant for
+    pub fn cast_vote(ctx: Context<CastVote>, candidate_id: u64, expected_round: u64) -> Result<()> {
+        // Sign as the delegate PDA for this election and voter
+        let voting_key = ctx.accounts.voting_account.key();
+        let voter_key = ctx.accounts.voter.key();
+        let bump = ctx.bumps.delegate;
+        let seeds: &[&[u8]] = &[CPI_VOTER_SEED, voting_key.as_ref(), voter_key.as_ref(), &[bump]];
+        let signer_seeds = &[seeds];
+
+        // Forward the accounts to the voting program
+        let cpi_accounts = VoteCpi {
+            voting_account: ctx.accounts.voting_account.to_account_info(),
+            user_account: ctx.accounts.user_account.to_account_info(),
+            voter: ctx.accounts.voter.to_account_info(),
+            delegate: ctx.accounts.delegate.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            roster: None,
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.voting_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        // Return the result of the vote
+        voting_system::cpi::vote_cpi(cpi_ctx, candidate_id, expected_round, bump)
+    }
+}
+
+// Define the account context for the cast_vote instruction
+// The voting program validates everything it is passed
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    /// CHECK: Validated by voting-system
+    #[account(mut)]
+    pub voting_account: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by voting-system
+    #[account(mut)]
+    pub user_account: UncheckedAccount<'info>,
+
+    /// CHECK: The voter this program votes for
+    pub voter: UncheckedAccount<'info>,
+
+    /// CHECK: This program's delegate PDA for the election and voter, signed for through CPI
+    #[account(
+        seeds = [CPI_VOTER_SEED, voting_account.key().as_ref(), voter.key().as_ref()],
+        bump,
+    )]
+    pub delegate: UncheckedAccount<'info>,
+
+    // Pays any vote fee
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Validated by voting-system as the fee recipient
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+
+    // The voting program being called
+    pub voting_program: Program<'info, VotingSystem>,
+
+    // The system program is required to transfer the vote fee
+    pub system_program: Program<'info, System>,
+}
@@ -2,10 +2,99 @@
 // for Solana program development using the Anchor framework
 use anchor_lang::prelude::*;
 
+// SPL Token account types, used to read balances for token-weighted voting
+use anchor_spl::token::{Mint, TokenAccount};
+
+// System program CPI helpers, used to collect vote fees
+use anchor_lang::system_program;
+
+// SHA-256, used to match voters against the hashed whitelist and to fingerprint results
+use anchor_lang::solana_program::hash::hash;
+
+// Instruction introspection, used to find the ed25519 signature check authorizing a
+// sponsored registration
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions};
+
 // Declares the program ID (public key) of this Solana program
 // This ID must match the deployed program ID on the Solana blockchain
 declare_id!("DKrPYCwiCPfCy2JHCeghPZj9BXZjWB2FA762D36eSLCd");
 
+// Sentinel stored in winner_index while there is no single winner
+// (voting still running, or the top candidates are tied)
+pub const NO_WINNER: u32 = u32::MAX;
+
+// Limits on the discovery tags an election can carry
+pub const MAX_TAGS: usize = 5;
+pub const MAX_TAG_LEN: usize = 16;
+
+// Prefix of the message a voter signs to let a sponsor register them
+// (followed by the election and voter pubkeys, see registration_message)
+pub const REGISTRATION_MESSAGE_PREFIX: &[u8] = b"solana-voting:register:";
+
+// Most candidates a ballot can hold, enforced by initialize and add_candidates
+// The SPACE formula reserves each per-candidate vector for exactly this many
+pub const MAX_CANDIDATES: usize = 100;
+
+// Longest candidate (or display) name accepted anywhere a name enters the ballot, in bytes
+// The space formulas reserve exactly this much per name, so a valid ballot always fits
+pub const MAX_CANDIDATE_NAME_LEN: usize = 32;
+
+// Bytes one candidate slot reserved by expand_capacity holds: a maximum-length name,
+// stored as candidate and display name. VotingAccount::SPACE already reserves every
+// other per-candidate entry for MAX_CANDIDATES, as space_for relies on
+pub const CANDIDATE_SLOT_SPACE: usize = 2 * (4 + MAX_CANDIDATE_NAME_LEN);
+
+// Limits on the extra questions (see add_ballot) an election can pose
+// MAX_BALLOTS must fit the bits of UserAccount::voted_ballots
+pub const MAX_BALLOTS: usize = 8;
+pub const MAX_BALLOT_OPTIONS: usize = 8;
+pub const MAX_QUESTION_LEN: usize = 64;
+
+// Longest reason a voter can give with vote_with_reason, in bytes
+pub const MAX_REASON_LEN: usize = 200;
+
+// Longest locale tag (e.g. "pt-BR") an election can carry, in bytes
+pub const MAX_LOCALE_LEN: usize = 16;
+
+// Longest title and description an election can carry, in bytes
+pub const MAX_TITLE_LEN: usize = 128;
+pub const MAX_DESCRIPTION_LEN: usize = 512;
+
+// Most operational admins an election can have
+pub const MAX_ADMINS: usize = 5;
+
+// Most candidate indices a voter's eligible_candidates allow-list can hold
+pub const MAX_ELIGIBLE_CANDIDATES: usize = 16;
+
+// Most candidates get_leaderboard reports, bounding its compute and log output
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Seed prefix of the delegate PDA an election's cpi_caller signs vote_cpi with
+// (derived as [CPI_VOTER_SEED, election, voter] under the caller program)
+pub const CPI_VOTER_SEED: &[u8] = b"cpi_voter";
+
+// STRUCTURED LOGS: alongside the Anchor events, initialization, every counted vote and the
+// end of voting log one pipe-delimited line each, so log parsers can ingest them without
+// decoding events:
+// - INIT|<election>|<authority>|<candidate count>
+// - VOTE|<voter>|<candidate index>|<slot>
+// - END|<election>|<winner index>|<total votes>
+
+// Length of a day in seconds, the unit of vote decay
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// How far in the past (in seconds) an election's start time may lie when it is created
+pub const MAX_START_TIME_DRIFT: i64 = SECONDS_PER_DAY;
+
+// Layout version of VotingAccount written by this program, stored in its version field
+// Bump it whenever the layout changes; migrate_voting_account upgrades accounts in the
+// original layout and refuses any version other than this one with UnsupportedLayout
+pub const VOTING_ACCOUNT_VERSION: u8 = 22;
+
+// Size of a voting account in the original deployed layout: discriminator, 40 bytes of
+// candidates, 100 u32 vote counts and has_ended. No later layout has this size
+pub const BASELINE_VOTING_ACCOUNT_SPACE: usize = 8 + 40 + (4 * 100) + 1;
+
 // The #[program] macro defines the program module containing all the program's instructions
 #[program]
 pub mod voting_system {
@@ -17,50 +106,66 @@ pub mod voting_system {
     // Parameters:
     // - ctx: The context containing all accounts needed for this instruction
     // - candidates: A vector of strings representing candidate names
-    pub fn initialize(ctx: Context<Initialize>, candidates: Vec<String>) -> Result<()> {
-        // Get a mutable reference to the voting account from the context
-        let voting_account = &mut ctx.accounts.voting_account;
-        
-        // Store the candidates in the voting account
-        voting_account.candidates = candidates;
-        
-        // Initialize the votes vector with zeros, one zero for each candidate
-        // This creates a vector with the same length as candidates, filled with zeros
-        voting_account.votes = vec![0; voting_account.candidates.len()];
-        
-        // Set the voting state to not ended
-        voting_account.has_ended = false;
-
-        // Return success
-        Ok(())
+    // - config: Optional settings for the election (weighted voting, caps, ...)
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        candidates: Vec<String>,
+        config: ElectionConfig,
+    ) -> Result<()> {
+        // The initializer becomes the election authority
+        let authority = ctx.accounts.user.key();
+        init_voting_account(&mut ctx.accounts.voting_account, authority, candidates, config)
     }
 
-    // INSTRUCTION #2: Initialize a user account (standalone method)
-    // This creates a user account which tracks whether a user has voted
+    // INSTRUCTION #2: Initialize a user account (standalone method) - DEPRECATED
+    // Keypair user accounts are not bound to an election or voter when created, so anyone
+    // could pass someone else's account. Voting now only accepts the PDA created by
+    // initialize_user_account; this instruction is kept so old clients get a clear error
     // Parameters:
     // - ctx: The context containing all accounts needed for this instruction
-    pub fn initialize_user(ctx: Context<InitializeUser>) -> Result<()> {
-        // Get a mutable reference to the user account from the context
-        let user_account = &mut ctx.accounts.user_account;
-        
-        // Set the initial voting state to false (user has not voted)
-        user_account.has_voted = false;
-        
-        // Return success
-        Ok(())
+    pub fn initialize_user(_ctx: Context<InitializeUser>) -> Result<()> {
+        err!(ErrorCode::UseAccountPda)
     }
 
     // INSTRUCTION #3: Initialize a user account with PDA (Program Derived Address)
     // This is an alternative method to create a user account using a PDA
+    // The account registers the user for one election and is bound to it right away;
+    // in AuthorityOnly elections the authority must co-sign the registration.
+    // In weighted elections the voter's token balance is snapshotted here, and
+    // vote_weighted counts that snapshot instead of the balance at voting time
     // Parameters:
     // - ctx: The context containing all accounts needed for this instruction
     pub fn initialize_user_account(ctx: Context<InitializeUserAccount>) -> Result<()> {
-        // Get a mutable reference to the user account from the context
-        let user_account = &mut ctx.accounts.user_account;
-        
-        // Set the initial voting state to false (user has not voted)
-        user_account.has_voted = false;
-        
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION #1: Closed elections only accept voters registered by the authority
+        if voting_account.registration_mode == RegistrationMode::AuthorityOnly {
+            let authority = ctx
+                .accounts
+                .authority
+                .as_ref()
+                .ok_or(ErrorCode::RegistrationClosed)?;
+            require_authority(voting_account, authority, ctx.remaining_accounts)?;
+        }
+
+        // VALIDATION #2: In weighted elections the current balance of the voter's token
+        // account for the vote mint becomes their weight; voters registering without one
+        // can only cast plain votes
+        let snapshot_weight = registration_weight(
+            voting_account,
+            ctx.accounts.token_account.as_ref(),
+            &ctx.accounts.user.key(),
+        )?;
+
+        // EXECUTION: Bind the user account to the election and voter
+        register_voter(
+            &mut ctx.accounts.voting_account,
+            &mut ctx.accounts.user_account,
+            ctx.accounts.user.key(),
+            snapshot_weight,
+            ctx.bumps.user_account,
+        )?;
+
         // Return success
         Ok(())
     }
@@ -69,168 +174,7677 @@ pub mod voting_system {
     // This instruction processes a vote for a specific candidate
     // Parameters:
     // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_id: The stable ID (see candidate_ids) of the candidate the user wants to
+    //   vote for; unlike a ballot position it does not change when candidates are removed
+    // - expected_round: The election round the vote is meant for, so a transaction replayed
+    //   from an earlier round is rejected with StaleRound instead of counting again
+    // - blinded_increment: Optional client-blinded value XOR-ed into the candidate's
+    //   encrypted_tally slot (see below)
+    //
+    // ENCRYPTED TALLY (experimental): encrypted_tally is a stepping stone towards private
+    // voting, not a privacy feature. XOR-ing client-provided values is not cryptographically
+    // secure on its own: the plaintext votes are still updated and public, every increment is
+    // visible in the transaction data, and XOR lets identical increments cancel out. It only
+    // gives clients a place to experiment with their own blinding schemes.
+    pub fn vote(
+        ctx: Context<Vote>,
+        candidate_id: u64,
+        expected_round: u64,
+        blinded_increment: Option<[u8; 32]>,
+    ) -> Result<()> {
+        // Get mutable references to the voting and user accounts from the context
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // A vote arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The vote must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: Find the candidate's current position on the ballot
+        let candidate_index = candidate_position(voting_account, candidate_id)?;
+
+        // VALIDATION #3: Run the checks shared by every way of casting a vote
+        // (consistent candidate lists, election binding, already voted, reason required,
+        // candidate index, paused, disabled candidate, whitelist, SOL balance)
+        let election = voting_account.key();
+        validate_vote(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            candidate_index,
+            false,
+        )?;
+
+        // VALIDATION #4: Count the vote against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #5: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION #1: Fold the blinded increment into the candidate's encrypted slot
+        if let Some(blinded_increment) = blinded_increment {
+            let slot = &mut voting_account.encrypted_tally[candidate_index as usize];
+            for (byte, blind) in slot.iter_mut().zip(blinded_increment.iter()) {
+                *byte ^= blind;
+            }
+        }
+
+        // EXECUTION #2: Cast the vote by incrementing the vote count for the selected candidate
+        // (by less once the vote has decayed) and mark the user as having voted
+        let weight = apply_decay(voting_account, 1)?;
+        record_vote(
+            &election,
+            voting_account,
+            user_account,
+            ctx.accounts.user.key,
+            candidate_index,
+            weight,
+        )?;
+
+        // Remember the blinded increment so undo_vote can take it back out
+        user_account.last_vote_blinded_increment = blinded_increment;
+
+        // EXECUTION #3: List the voter on the public roster, if the election keeps one
+        add_to_roster(voting_account, ctx.accounts.roster.as_mut(), ctx.accounts.user.key)
+    }
+
+    // INSTRUCTION #5: Cast a token-weighted vote
+    // The vote counts as many times as the user's balance of the election's vote mint
+    // when they registered (so tokens acquired afterwards, e.g. by a flash loan, don't
+    // count), subject to the optional per-voter cap and any decay since the election started
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
     // - candidate_index: The index of the candidate the user wants to vote for
-    pub fn vote(ctx: Context<Vote>, candidate_index: u32) -> Result<()> {
+    // - expected_round: The election round the vote is meant for, as for vote
+    pub fn vote_weighted(
+        ctx: Context<VoteWeighted>,
+        candidate_index: u32,
+        expected_round: u64,
+    ) -> Result<()> {
         // Get mutable references to the voting and user accounts from the context
         let voting_account = &mut ctx.accounts.voting_account;
         let user_account = &mut ctx.accounts.user_account;
 
-        // VALIDATION #1: Check if the user has already voted 
-        // If they have, return an error
-        if user_account.has_voted {
-            return Err(ErrorCode::AlreadyVoted.into());
+        // A vote arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The vote must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
         }
 
-        // VALIDATION #2: Check if the candidate index is valid
-        // If the index is out of bounds, return an error
-        if candidate_index >= voting_account.candidates.len() as u32 {
-            return Err(ErrorCode::InvalidCandidate.into());
+        // VALIDATION #2: Weighted voting must be enabled for this election
+        if voting_account.vote_mint.is_none() {
+            return Err(ErrorCode::WeightedVotingDisabled.into());
         }
 
-        // EXECUTION: Cast the vote by incrementing the vote count for the selected candidate
-        voting_account.votes[candidate_index as usize] += 1;
-        
-        // Mark the user as having voted
-        user_account.has_voted = true;
+        // VALIDATION #3: Run the checks shared by every way of casting a vote
+        let election = voting_account.key();
+        validate_vote(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            candidate_index,
+            false,
+        )?;
 
-        // Return success
-        Ok(())
+        // VALIDATION #4: The balance snapshotted at registration is the weight. When a
+        // snapshot slot is configured the token account's balance must also have been
+        // recorded before it, and only the smaller of the two balances counts
+        let mut balance = user_account.snapshot_weight;
+        if voting_account.snapshot_slot != 0 {
+            let snapshot = ctx
+                .accounts
+                .token_snapshot
+                .as_ref()
+                .ok_or(ErrorCode::SnapshotRequired)?;
+            balance = balance.min(snapshot.amount);
+        }
+
+        // VALIDATION #5: Apply the per-voter cap to the token balance
+        let weight = apply_weight_cap(
+            balance,
+            voting_account.max_weight_per_voter,
+            voting_account.weight_cap_mode,
+        )?;
+        if weight == 0 {
+            return Err(ErrorCode::NoVotingWeight.into());
+        }
+
+        // VALIDATION #6: Count the vote against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #7: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION #1: Add the weight, decayed by the time since the start, to the selected
+        // candidate's count and mark the user as having voted
+        let weight = apply_decay(voting_account, weight)?;
+        record_vote(
+            &election,
+            voting_account,
+            user_account,
+            ctx.accounts.user.key,
+            candidate_index,
+            weight,
+        )?;
+
+        // EXECUTION #2: List the voter on the public roster, if the election keeps one
+        add_to_roster(voting_account, ctx.accounts.roster.as_mut(), ctx.accounts.user.key)
     }
 
-    // INSTRUCTION #5: End the voting process
+    // INSTRUCTION #6: End the voting process
     // This instruction marks the voting as complete
+    // Only the authority (or enough co-signers of a governance authority) can end it
     // Parameters:
     // - ctx: The context containing all accounts needed for this instruction
     pub fn end_voting(ctx: Context<EndVoting>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
         // Get a mutable reference to the voting account from the context
         let voting_account = &mut ctx.accounts.voting_account;
-        
-        // Mark the voting as ended
-        voting_account.has_ended = true;
+
+        // VALIDATION #2: An election can only be ended once (Ended -> Ended is not a valid move)
+        // Mark the voting as ended, record the outcome and announce it
+        finalize_voting(voting_account)?;
+
+        // Record when voting actually ended (this replaces any earlier deadline)
+        voting_account.end_time = Clock::get()?.unix_timestamp;
 
         // Return success
         Ok(())
     }
-}
 
-// ACCOUNT CONTEXTS
+    // INSTRUCTION #7: Close the voting account and reclaim its rent
+    // Only the authority can close, and only once the election has ended and
+    // the close delay has passed, giving observers time to archive the results
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn close_voting(ctx: Context<CloseVoting>) -> Result<()> {
+        // VALIDATION: Voting must have ended and the decommission window elapsed
+        require_closable(&ctx.accounts.voting_account)?;
 
-// Define the account context for the initialize instruction
-// This struct specifies which accounts are required and how they should be validated
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    // The voting_account is initialized in this instruction
-    // init: This account will be created in this transaction
-    // payer = user: The 'user' account will pay for the account creation
-    // space = 8 + 40 + (4 * 100) + 1: Allocate space for:
-    //   - 8 bytes for account discriminator (added by Anchor)
-    //   - 40 bytes for candidates data (estimated space for Vec<String>)
-    //   - 400 bytes for votes data (4 bytes per u32 * 100 potential candidates)
-    //   - 1 byte for the boolean has_ended flag
-    #[account(init, payer = user, space = 8 + 40 + (4 * 100) + 1)]
-    pub voting_account: Account<'info, VotingAccount>,
-    
-    // The user account must be mutable as it will pay for the transaction
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    // The system program is required for creating new accounts
-    pub system_program: Program<'info, System>,
-}
+        // EXECUTION: Commit the final results to the log before the account is gone
+        archive_results(&ctx.accounts.voting_account)?;
 
-// Define the account context for initializing a user
-#[derive(Accounts)]
-pub struct InitializeUser<'info> {
-    // The user_account is initialized in this instruction
-    // space = 8 + 1: Allocate space for:
-    //   - 8 bytes for account discriminator
-    //   - 1 byte for the boolean has_voted flag
-    #[account(init, payer = user, space = 8 + 1)]
-    pub user_account: Account<'info, UserAccount>,
-    
-    // The user account must be mutable as it will pay for the transaction
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    // The system program is required for creating new accounts
-    pub system_program: Program<'info, System>,
-}
+        // The close constraint on the context transfers the rent to the authority
+        Ok(())
+    }
 
-// Define the account context for initializing a user account as a PDA
-#[derive(Accounts)]
-pub struct InitializeUserAccount<'info> {
-    // The user_account is initialized as a PDA (Program Derived Address)
-    // seeds = [b"user", user.key().as_ref()]: The PDA is derived from:
-    //   - The string "user"
-    //   - The user's public key
-    // bump: Automatically adds the bump seed for the PDA
-    #[account(init, payer = user, space = 8 + 1, seeds = [b"user", user.key().as_ref()], bump)]
-    pub user_account: Account<'info, UserAccount>,
-    
-    // The user account must be mutable as it will pay for the transaction
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    // The system program is required for creating new accounts
-    pub system_program: Program<'info, System>,
-}
+    // INSTRUCTION #8: Record a token account's balance for snapshot-based weighted voting
+    // Elections with a snapshot_slot only accept weighted votes from token accounts
+    // whose balance was recorded at or before that slot, so tokens borrowed or
+    // freshly minted into a new account after the snapshot carry no weight.
+    //
+    // LIMITATIONS: SPL token accounts do not keep a balance history, so this records
+    // the balance at the moment of the call rather than at the snapshot slot itself.
+    // Tokens can still be borrowed within the same transaction as the recording, and
+    // tokens moved between accounts before the snapshot may be recorded more than once
+    // (vote_weighted only counts min(recorded, current) balance, which limits but does
+    // not remove this). Full protection requires a token with on-chain checkpoints.
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn record_snapshot(ctx: Context<RecordSnapshot>) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
 
-// Define the account context for the vote instruction
-#[derive(Accounts)]
-pub struct Vote<'info> {
-    // The voting account must be mutable as we'll update vote counts
-    #[account(mut)]
-    pub voting_account: Account<'info, VotingAccount>,
-    
-    // The user account must be mutable as we'll mark it as having voted
-    #[account(mut)]
-    pub user_account: Account<'info, UserAccount>,
-    
-    // The user must sign the transaction to vote
-    #[account(mut)]
-    pub user: Signer<'info>,
-}
+        // VALIDATION #1: The election must use a snapshot
+        if voting_account.snapshot_slot == 0 {
+            return Err(ErrorCode::SnapshotNotConfigured.into());
+        }
 
-// Define the account context for ending the voting
-#[derive(Accounts)]
-pub struct EndVoting<'info> {
-    // The voting account must be mutable as we'll update its state
-    #[account(mut)]
-    pub voting_account: Account<'info, VotingAccount>,
-}
+        // VALIDATION #2: Balances can only be recorded up to the snapshot slot
+        let slot = Clock::get()?.slot;
+        if slot > voting_account.snapshot_slot {
+            return Err(ErrorCode::SnapshotSlotPassed.into());
+        }
 
-// ACCOUNT DATA STRUCTURES
+        // EXECUTION: Store the current balance
+        let token_snapshot = &mut ctx.accounts.token_snapshot;
+        token_snapshot.token_account = ctx.accounts.token_account.key();
+        token_snapshot.amount = ctx.accounts.token_account.amount;
+        token_snapshot.slot = slot;
 
-// Define the structure of the voting account's data
-#[account]
-pub struct VotingAccount {
-    // List of candidate names
-    pub candidates: Vec<String>,
-    
-    // Vote counts for each candidate (parallel array to candidates)
-    pub votes: Vec<u32>,
-    
-    // Flag indicating if the voting has ended
-    pub has_ended: bool,
-}
+        // Return success
+        Ok(())
+    }
 
-// Define the structure of the user account's data
-#[account]
-pub struct UserAccount {
-    // Flag indicating if the user has voted
-    pub has_voted: bool,
-}
+    // INSTRUCTION #9: Query whether a user has voted
+    // A read-only convenience for integrators (and CPI callers) that returns the
+    // user's voting state without them decoding the UserAccount layout
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn has_user_voted(ctx: Context<HasUserVoted>) -> Result<UserVoteStatus> {
+        let user_account = &ctx.accounts.user_account;
 
-// CUSTOM ERROR CODES
+        // Return the user's voting state as the instruction return value
+        Ok(UserVoteStatus {
+            has_voted: user_account.has_voted,
+            voted_for: user_account.voted_for,
+        })
+    }
 
-// Define custom error codes for the program
-#[error_code]
-pub enum ErrorCode {
-    // Error when a user tries to vote more than once
-    #[msg("User has already voted")]
-    AlreadyVoted,
-    
-    // Error when a user tries to vote for a non-existent candidate
-    #[msg("Invalid candidate index")]
-    InvalidCandidate,
-}
\ No newline at end of file
+    // INSTRUCTION #10: Create a governance account for multisig administration
+    // A governance account lists the signers allowed to administer an election jointly
+    // and how many of them must co-sign; make it an election's authority with
+    // transfer_authority
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - signers: The public keys allowed to co-sign
+    // - threshold: How many distinct listed signers must co-sign an action
+    pub fn create_governance(
+        ctx: Context<CreateGovernance>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        // VALIDATION #1: The threshold must be reachable and non-zero
+        if threshold == 0 || threshold as usize > signers.len() {
+            return Err(ErrorCode::InvalidGovernance.into());
+        }
+
+        // VALIDATION #2: Each signer may only be listed once
+        for (i, signer) in signers.iter().enumerate() {
+            if signers[..i].contains(signer) {
+                return Err(ErrorCode::InvalidGovernance.into());
+            }
+        }
+
+        // EXECUTION: Store the signer set
+        let governance = &mut ctx.accounts.governance;
+        governance.signers = signers;
+        governance.threshold = threshold;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #11: Hand the election over to a new authority
+    // The new authority may be a plain key or a governance account
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - new_authority: The key (or governance account) that will administer the election
+    pub fn transfer_authority(ctx: Context<AuthorityAction>, new_authority: Pubkey) -> Result<()> {
+        // VALIDATION: The caller must act for the current authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // EXECUTION: Replace the authority
+        ctx.accounts.voting_account.authority = new_authority;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #12: Pause voting
+    // While paused, votes are rejected; the authority or an admin can resume later
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn pause_voting(ctx: Context<AuthorityAction>) -> Result<()> {
+        // VALIDATION #1: The caller must be an admin or act for the election authority
+        require_admin_or_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2 / EXECUTION: Stop accepting votes (only an active election can be paused)
+        transition(&mut ctx.accounts.voting_account, VotingStatus::Paused)?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #13: Resume a paused election
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn resume_voting(ctx: Context<AuthorityAction>) -> Result<()> {
+        // VALIDATION #1: The caller must be an admin or act for the election authority
+        require_admin_or_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2 / EXECUTION: Accept votes again (only a paused election can be
+        // resumed; Ended -> Active is reopen_voting's, within its window)
+        if ctx.accounts.voting_account.status != VotingStatus::Paused {
+            return Err(ErrorCode::InvalidTransition.into());
+        }
+        transition(&mut ctx.accounts.voting_account, VotingStatus::Active)?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #14: Disable a candidate without removing them
+    // Used when a candidate withdraws mid-election: indices stay stable, further votes
+    // for the candidate are rejected, and the votes they already received stay on record
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate to disable
+    pub fn disable_candidate(ctx: Context<AuthorityAction>, candidate_index: u32) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The ballot is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: Check if the candidate index is valid
+        require_valid_candidate(voting_account, candidate_index)?;
+
+        // EXECUTION: Flag the candidate as disabled
+        voting_account.disabled[candidate_index as usize] = true;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #15: Read a single candidate's data
+    // Lets paginated frontends fetch one candidate instead of the whole ballot;
+    // the result is logged and emitted as a CandidateInfo event. In results_hidden
+    // elections the vote count reads 0 until voting has ended
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate to read
+    pub fn get_candidate(ctx: Context<ReadVoting>, candidate_index: u32) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Check if the candidate index is valid
+        require_valid_candidate(voting_account, candidate_index)?;
+
+        // Report the candidate's name and current vote count, unless the count is hidden
+        let name = voting_account.candidates[candidate_index as usize].clone();
+        let votes = if require_results_visible(voting_account).is_ok() {
+            voting_account.votes[candidate_index as usize]
+        } else {
+            0
+        };
+        msg!("Candidate {}: {} with {} votes", candidate_index, name, votes);
+        emit!(CandidateInfo {
+            index: candidate_index,
+            name,
+            votes,
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #16: Create the registry that tracks an authority's elections
+    // Elections created through create_election are counted here so one authority
+    // cannot spam election accounts beyond the configured cap
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - max_elections: How many open elections the authority may have at once
+    pub fn initialize_registry(ctx: Context<InitializeRegistry>, max_elections: u64) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.authority = ctx.accounts.authority.key();
+        registry.election_count = 0;
+        registry.max_elections = max_elections;
+        registry.next_election_id = 0;
+        registry.active_elections = 0;
+        registry.max_active_elections = 0;
+        registry.bump = ctx.bumps.registry;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #17: Create an election as a PDA tracked by the authority's registry
+    // The election address is derived from the authority and a per-registry id, and
+    // creation fails with ElectionLimitReached once the registry's cap is reached
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidates: A vector of strings representing candidate names
+    // - config: Optional settings for the election (weighted voting, caps, ...)
+    pub fn create_election(
+        ctx: Context<CreateElection>,
+        candidates: Vec<String>,
+        config: ElectionConfig,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+
+        // VALIDATION #1: The authority must be below its election cap
+        if registry.election_count >= registry.max_elections {
+            return Err(ErrorCode::ElectionLimitReached.into());
+        }
+
+        // VALIDATION #2: ...and below its cap on elections still running, if it has one
+        if registry.max_active_elections != 0
+            && registry.active_elections >= registry.max_active_elections
+        {
+            return Err(ErrorCode::TooManyActiveElections.into());
+        }
+
+        // Count the new election and advance the id used for the next address
+        let election_id = registry.next_election_id;
+        registry.election_count += 1;
+        registry.active_elections += 1;
+        registry.next_election_id += 1;
+
+        // Fill in the election itself
+        let authority = ctx.accounts.authority.key();
+        let voting_account = &mut ctx.accounts.voting_account;
+        init_voting_account(voting_account, authority, candidates, config)?;
+
+        // Remember the address seeds so later instructions can re-derive the PDA
+        voting_account.election_id = election_id;
+        voting_account.bump = ctx.bumps.voting_account;
+
+        // The election counts as active in the registry until it is released
+        voting_account.registry_active = true;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #18: Close a registry-tracked election and free its slot in the registry
+    // Applies the same rules as close_voting, then decrements the registry's count (and
+    // its active count, unless release_election already did)
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn close_election(ctx: Context<CloseElection>) -> Result<()> {
+        // VALIDATION: Voting must have ended and the decommission window elapsed
+        require_closable(&ctx.accounts.voting_account)?;
+
+        // EXECUTION #1: Commit the final results to the log before the account is gone
+        archive_results(&ctx.accounts.voting_account)?;
+
+        // EXECUTION #2: Free the slot in the registry
+        let registry = &mut ctx.accounts.registry;
+        registry.election_count = registry.election_count.saturating_sub(1);
+        if ctx.accounts.voting_account.registry_active {
+            registry.active_elections = registry.active_elections.saturating_sub(1);
+        }
+
+        // The close constraint on the context transfers the rent to the authority
+        Ok(())
+    }
+
+    // INSTRUCTION #19: Reorder the ballot for display
+    // Moves candidates and everything attached to them (votes, flags, tallies) together,
+    // so counts stay with their candidates
+    // Note: UserAccount::voted_for stores the index at the time of voting and is not rewritten
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - new_order: new_order[i] is the current index of the candidate to place at position i
+    pub fn reorder_candidates(ctx: Context<AuthorityAction>, new_order: Vec<u32>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The result indices are fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: Ranked ballots store indices, so the ballot is fixed once one is cast
+        if voting_account.ranked_ballot_count > 0 {
+            return Err(ErrorCode::RankedBallotsCast.into());
+        }
+
+        // VALIDATION #4: new_order must be a permutation of 0..candidates.len()
+        if !is_permutation(&new_order, voting_account.candidates.len()) {
+            return Err(ErrorCode::InvalidPermutation.into());
+        }
+
+        // EXECUTION: Apply the same permutation to every parallel vector
+        voting_account.candidates = permute(&voting_account.candidates, &new_order);
+        voting_account.candidate_ids = permute(&voting_account.candidate_ids, &new_order);
+        voting_account.display_names = permute(&voting_account.display_names, &new_order);
+        voting_account.votes = permute(&voting_account.votes, &new_order);
+        voting_account.vetoes = permute(&voting_account.vetoes, &new_order);
+        voting_account.reached_max_at = permute(&voting_account.reached_max_at, &new_order);
+        voting_account.disabled = permute(&voting_account.disabled, &new_order);
+        voting_account.disqualified = permute(&voting_account.disqualified, &new_order);
+        voting_account.encrypted_tally = permute(&voting_account.encrypted_tally, &new_order);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #20: Add a hashed voter key to the election's whitelist
+    // Eligibility is stored as sha256(voter pubkey) rather than raw keys to reduce
+    // on-chain linkability; once the list is non-empty only listed voters can vote
+    // The voting account grows by 32 bytes, paid for by the signing authority
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - voter_hash: sha256 of the eligible voter's public key
+    pub fn add_eligible_hash(ctx: Context<AddEligibleHash>, voter_hash: [u8; 32]) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Each hash is only stored once
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.eligible_voter_hashes.contains(&voter_hash) {
+            return Err(ErrorCode::HashAlreadyEligible.into());
+        }
+
+        // EXECUTION: Append the hash into the space added by the realloc constraint
+        voting_account.eligible_voter_hashes.push(voter_hash);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #21: Reset a finished election for a new voting round
+    // Tallies and the outcome are cleared and the round counter advances; candidates,
+    // configuration and the whitelist are kept. The election goes back to Draft until
+    // open_voting is called, and voters re-enable their accounts with reset_user
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn reset_voting(ctx: Context<AuthorityAction>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Only a finished election can be reset
+        let voting_account = &mut ctx.accounts.voting_account;
+        if !voting_account.has_ended() {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // EXECUTION #1: Advance to the next round
+        voting_account.round = voting_account
+            .round
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // EXECUTION #2: Clear the tallies and the previous outcome
+        let candidate_count = voting_account.candidates.len();
+        voting_account.votes = vec![0; candidate_count];
+        voting_account.vetoes = vec![0; candidate_count];
+        voting_account.reached_max_at = vec![0; candidate_count];
+        voting_account.encrypted_tally = vec![[0u8; 32]; candidate_count];
+        for ballot in voting_account.ballots.iter_mut() {
+            ballot.votes = vec![0; ballot.options.len()];
+        }
+        voting_account.total_votes = 0;
+        voting_account.voter_count = 0;
+        voting_account.batched_votes = 0;
+        voting_account.abstain_count = 0;
+        voting_account.withdrawn_votes = 0;
+        voting_account.winner_index = NO_WINNER;
+        voting_account.is_tie = false;
+        voting_account.tie_break_seed = [0u8; 32];
+        voting_account.turnout_bps = 0;
+        voting_account.ended_at = 0;
+
+        // The stored ranked ballots are kept, but their tally starts over
+        voting_account.eliminated = Vec::new();
+        voting_account.ranked_winner = None;
+
+        // EXECUTION #3: Return to Draft; the authority reopens voting with open_voting
+        transition(voting_account, VotingStatus::Draft)?;
+        voting_account.end_time = 0;
+        voting_account.configured_end_time = 0;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #22: Re-enable a user account for the election's current round
+    // Either the election authority or the voter who used the account can call this,
+    // and only once the election has been reset past the round the account voted in
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn reset_user(ctx: Context<ResetUser>) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // VALIDATION #1: The caller must be the voter or act for the election authority
+        if ctx.accounts.signer.key() != user_account.voter {
+            require_authority(voting_account, &ctx.accounts.signer, ctx.remaining_accounts)?;
+        }
+
+        // VALIDATION #2: The user account must belong to this election
+        if user_account.election != voting_account.key() {
+            return Err(ErrorCode::UserAccountMismatch.into());
+        }
+
+        // VALIDATION #3: The election must have been reset (Draft) or be open for voting
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #4: Accounts can only be reset once per round, otherwise a voter
+        // could reset and vote again within the same round
+        if user_account.round >= voting_account.round {
+            return Err(ErrorCode::ResetNotAllowed.into());
+        }
+
+        // EXECUTION: Let the user vote in the current round
+        user_account.has_voted = false;
+        user_account.voted_for = None;
+        user_account.voted_ballots = 0;
+        user_account.round = voting_account.round;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #23: Open a draft election for voting
+    // Elections created with start_as_draft, and elections reset for a new round,
+    // accept no votes until the authority opens them
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn open_voting(ctx: Context<AuthorityAction>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2 / EXECUTION: Start accepting votes (only a draft can be opened;
+        // Ended -> Active is reopen_voting's, within its window)
+        if ctx.accounts.voting_account.status != VotingStatus::Draft {
+            return Err(ErrorCode::InvalidTransition.into());
+        }
+        transition(&mut ctx.accounts.voting_account, VotingStatus::Active)?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #24: Abstain from voting
+    // Records the user's participation without endorsing any candidate: the user is
+    // marked as having voted and counts towards turnout (voter_count) and abstain_count,
+    // while no candidate's votes or total_votes change. The vote fee still applies
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - expected_round: The election round the vote is meant for, as for vote
+    pub fn vote_abstain(ctx: Context<Vote>, expected_round: u64) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // An abstention arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The abstention must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: Run the voter checks shared with candidate votes
+        let election = voting_account.key();
+        validate_voter(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            false,
+        )?;
+
+        // VALIDATION #3: Count the abstention against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #4: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION: Count the abstention and mark the user as having voted
+        voting_account.abstain_count = voting_account
+            .abstain_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        mark_voted(&election, voting_account, user_account, ctx.accounts.user.key, None)
+    }
+
+    // INSTRUCTION #25: Get the winner of an ended election
+    // Fails with MarginNotMet when the leader's lead is below min_victory_margin, so
+    // clients can tell an inconclusive result from a tie (which returns NO_WINNER, unless
+    // the election breaks ties with its tie-break rule)
+    // The result is only final once the election's dispute period after end_time has
+    // passed; until then this fails with DisputePeriodActive, and the authority can still
+    // void a contested outcome with emergency_cancel
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_winner(ctx: Context<ReadVoting>) -> Result<u32> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION #1: Cancelled elections have no result
+        if voting_account.status == VotingStatus::Cancelled {
+            return Err(ErrorCode::ElectionCancelled.into());
+        }
+
+        // VALIDATION #2: The outcome is only known once voting has ended
+        if !voting_account.has_ended() {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // VALIDATION #3: The dispute window after the end must have elapsed
+        if dispute_period_active(
+            voting_account.end_time,
+            voting_account.dispute_period_secs,
+            Clock::get()?.unix_timestamp,
+        ) {
+            return Err(ErrorCode::DisputePeriodActive.into());
+        }
+
+        // VALIDATION #4: Enough of the token supply must have voted
+        if voting_account.quorum_bps > 0
+            && !quorum_met(
+                voting_account.total_votes,
+                voting_account.total_supply,
+                voting_account.quorum_bps,
+            )
+        {
+            return Err(ErrorCode::QuorumNotReached.into());
+        }
+
+        // VALIDATION #5: The winner must lead by the required margin
+        check_margin(
+            &standing_votes(
+                &voting_account.votes,
+                &voting_account.vetoes,
+                &voting_account.disqualified,
+            ),
+            voting_account.min_victory_margin,
+        )?;
+
+        // Return the winner recorded by end_voting
+        Ok(voting_account.winner_index)
+    }
+
+    // INSTRUCTION #26: Create a runoff between the top two candidates of an ended election
+    // The runoff is a PDA derived from the parent election, so each election has at most
+    // one runoff. It inherits the parent's authority and voting settings, except that it
+    // opens immediately, has no deadline, snapshot, whitelist, margin, vote threshold or quorum, and
+    // links back to the parent through parent_election
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn create_runoff(ctx: Context<CreateRunoff>) -> Result<()> {
+        let parent = &ctx.accounts.parent_election;
+
+        // VALIDATION #1: The caller must act for the parent election's authority
+        require_authority(parent, &ctx.accounts.authority, ctx.remaining_accounts)?;
+
+        // VALIDATION #2: The parent's results must be final
+        if !parent.has_ended() {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // VALIDATION #3: A runoff needs two candidates to choose from
+        // (disqualified and vetoed-out candidates count as having no votes)
+        let standing = standing_votes(&parent.votes, &parent.vetoes, &parent.disqualified);
+        let finalists = top_two(&standing).ok_or(ErrorCode::NotEnoughCandidates)?;
+
+        // EXECUTION #1: Carry the parent's voting settings over to the runoff
+        let candidates = finalists
+            .iter()
+            .map(|index| parent.candidates[*index].clone())
+            .collect();
+        let config = ElectionConfig {
+            vote_mint: parent.vote_mint,
+            max_weight_per_voter: parent.max_weight_per_voter,
+            weight_cap_mode: parent.weight_cap_mode,
+            close_delay: parent.close_delay,
+            dispute_period_secs: parent.dispute_period_secs,
+            reopen_window: parent.reopen_window,
+            points_budget: parent.points_budget,
+            quorum_bps: 0,
+            undo_window: parent.undo_window,
+            require_reason: parent.require_reason,
+            allow_revote: parent.allow_revote,
+            cpi_caller: parent.cpi_caller,
+            snapshot_slot: 0,
+            vote_fee: parent.vote_fee,
+            min_sol_balance: parent.min_sol_balance,
+            tags: parent.tags.clone(),
+            start_as_draft: false,
+            start_time: 0,
+            end_time: 0,
+            min_victory_margin: 0,
+            min_winning_votes: 0,
+            rate_limit_votes: parent.rate_limit_votes,
+            registration_mode: parent.registration_mode,
+            results_hidden: parent.results_hidden,
+            display_names: finalists
+                .iter()
+                .map(|index| parent.display_names[*index].clone())
+                .collect(),
+            locale: parent.locale.clone(),
+            title: parent.title.clone(),
+            description: parent.description.clone(),
+            rollup_parent: None,
+            decay_rate_bps_per_day: parent.decay_rate_bps_per_day,
+            min_decay_weight_bps: parent.min_decay_weight_bps,
+            registration_period: 0,
+            max_votes_per_slot: parent.max_votes_per_slot,
+            tie_break: parent.tie_break,
+            public_roster: parent.public_roster,
+        };
+        let authority = parent.authority;
+        let parent_key = parent.key();
+
+        // EXECUTION #2: Fill in the runoff and link it to its parent
+        let runoff = &mut ctx.accounts.runoff;
+        init_voting_account(runoff, authority, candidates, config)?;
+        runoff.parent_election = Some(parent_key);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #27: Cast a ranked-choice ballot
+    // Ranked ballots are stored in their own PDA per voter and election and are counted
+    // by tally_ranked rather than in the plain votes. The voter needs their registered
+    // user account, and the checks every other ballot passes apply (registration binding,
+    // deadline, round, voting open, whitelist and balance gates, per-slot cap), with each
+    // ranked candidate on the voter's allow-list; the vote fee only applies to plain votes
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - rankings: Candidate indices in order of preference (distinct, at least one)
+    // - expected_round: The election round the ballot is meant for, as for vote
+    pub fn cast_ranked_ballot(
+        ctx: Context<CastRankedBallot>,
+        rankings: Vec<u32>,
+        expected_round: u64,
+    ) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &ctx.accounts.user_account;
+
+        // A ballot arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The ballot must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: Run the voter checks shared by every kind of vote. The ballot PDA
+        // can only be created once, so the voter can't have cast it already
+        let election = voting_account.key();
+        validate_participant(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            false,
+            false,
+        )?;
+
+        // VALIDATION #3: The ranking must only list valid candidates, once each, that the
+        // voter may choose
+        if !is_valid_ranking(&rankings, voting_account.candidates.len()) {
+            return Err(ErrorCode::InvalidRanking.into());
+        }
+        if !rankings
+            .iter()
+            .all(|index| may_choose(&user_account.eligible_candidates, *index))
+        {
+            return Err(ErrorCode::CandidateNotEligible.into());
+        }
+
+        // VALIDATION #4: Count the ballot against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // EXECUTION #1: Store the ballot
+        let ranked_ballot = &mut ctx.accounts.ranked_ballot;
+        ranked_ballot.voting_account = voting_account.key();
+        ranked_ballot.voter = ctx.accounts.user.key();
+        ranked_ballot.rankings = rankings;
+
+        // EXECUTION #2: Count it so tally_ranked can require every ballot
+        voting_account.ranked_ballot_count = voting_account
+            .ranked_ballot_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #28: Run one instant-runoff round over the election's ranked ballots
+    // Every RankedBallot of the election must be passed in remaining_accounts, sorted by
+    // address so none can be counted twice. Each call either finds a majority winner
+    // (stored in ranked_winner) or eliminates the weakest candidate (appended to
+    // eliminated), so it is called repeatedly until a winner emerges. Anyone may call it,
+    // since every round is determined by the ballots
+    //
+    // COMPUTE LIMITS: all ballots must fit in one transaction, so this only suits small
+    // electorates. A legacy transaction can reference roughly 30 accounts, and every ballot
+    // is deserialized and scanned once per round (up to candidates * rankings comparisons)
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn tally_ranked(ctx: Context<TallyRanked>) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+
+        // VALIDATION #1: Ballots can only be tallied once voting has ended
+        if !voting_account.has_ended() {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // VALIDATION #2: The tally stops once a winner is known
+        if voting_account.ranked_winner.is_some() {
+            return Err(ErrorCode::TallyComplete.into());
+        }
+
+        // VALIDATION #3: Every ballot must be passed exactly once
+        if ctx.remaining_accounts.len() as u64 != voting_account.ranked_ballot_count {
+            return Err(ErrorCode::InvalidRankedBallots.into());
+        }
+        let election = voting_account.key();
+        let mut ballots = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut previous: Option<Pubkey> = None;
+        for info in ctx.remaining_accounts {
+            // Strictly increasing addresses rule out duplicates
+            if previous.is_some_and(|key| key >= *info.key) {
+                return Err(ErrorCode::InvalidRankedBallots.into());
+            }
+            previous = Some(*info.key);
+
+            // The account must be a ranked ballot of this election
+            if info.owner != &crate::ID {
+                return Err(ErrorCode::InvalidRankedBallots.into());
+            }
+            let data = info.try_borrow_data()?;
+            let ballot = RankedBallot::try_deserialize(&mut &data[..])?;
+            if ballot.voting_account != election {
+                return Err(ErrorCode::InvalidRankedBallots.into());
+            }
+            ballots.push(ballot.rankings);
+        }
+
+        // EXECUTION: Record this round's result
+        match irv_round(
+            &ballots,
+            voting_account.candidates.len(),
+            &voting_account.eliminated,
+        )? {
+            IrvOutcome::Winner(winner) => {
+                voting_account.ranked_winner = Some(winner);
+                msg!("Ranked-choice winner: candidate {}", winner);
+            }
+            IrvOutcome::Eliminate(candidate) => {
+                voting_account.eliminated.push(candidate);
+                msg!("Eliminated candidate {}", candidate);
+            }
+        }
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #29: Append several candidates in one call
+    // Every name is validated and checked against the existing ballot and the rest of
+    // the batch before anything changes; the account is reallocated once for all of them
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - names: The new candidates' names
+    pub fn add_candidates(ctx: Context<AddCandidates>, names: Vec<String>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The ballot is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: The grown ballot must still fit MAX_CANDIDATES
+        require_candidate_capacity(voting_account.candidates.len() + names.len())?;
+
+        // VALIDATION #4: Each name must be valid and not already on the ballot
+        for (position, name) in names.iter().enumerate() {
+            validate_candidate_name(name)?;
+            if voting_account.candidates.contains(name) || names[..position].contains(name) {
+                msg!("Duplicate candidate: {}", name);
+                return Err(ErrorCode::DuplicateCandidate.into());
+            }
+        }
+
+        // EXECUTION #1: Use up reserved slots before the space the account grew by
+        let reserved = voting_account.reserved_capacity.min(names.len() as u32);
+        voting_account.reserved_capacity -= reserved;
+
+        // EXECUTION #2: Extend every parallel vector for the new candidates
+        // New candidates are displayed under their canonical name
+        for name in names {
+            let candidate_id = voting_account.next_candidate_id;
+            voting_account.candidate_ids.push(candidate_id);
+            voting_account.next_candidate_id = candidate_id
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+            voting_account.display_names.push(name.clone());
+            voting_account.candidates.push(name);
+            voting_account.votes.push(0);
+            voting_account.vetoes.push(0);
+            voting_account.reached_max_at.push(0);
+            voting_account.disabled.push(false);
+            voting_account.disqualified.push(false);
+            voting_account.encrypted_tally.push([0; 32]);
+        }
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #30: Submit a batch of votes aggregated off-chain
+    // Intended for L2-style aggregation, where votes are collected and verified off-chain
+    // and the authority posts the result. TRUST ASSUMPTION: the program cannot check who
+    // cast these votes; it trusts the authority that each entry is one vote from a distinct,
+    // eligible voter who has not also voted on-chain. Only use this where voters accept that
+    // Each entry adds one vote to the candidate at that index; every index is validated
+    // before any vote is counted, so a bad entry rejects the whole batch
+    // Batched votes go into the counts and total_votes but are kept apart from the
+    // on-chain voters in batched_votes: voter_count, and so turnout_bps, only counts
+    // voters the program has seen. Each entry uses up one of the slot's max_votes_per_slot
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - votes: One candidate index per aggregated vote
+    // - expected_round: The round the votes were collected for, to reject a stale batch
+    pub fn submit_batch(
+        ctx: Context<AuthorityAction>,
+        votes: Vec<u32>,
+        expected_round: u64,
+    ) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The election must be accepting votes
+        // A batch arriving after the deadline is refused (see require_before_deadline)
+        let voting_account = &mut ctx.accounts.voting_account;
+        require_before_deadline(voting_account)?;
+        require_voting_open(voting_account)?;
+
+        // VALIDATION #3: The votes must have been collected for the current round
+        if voting_account.round != expected_round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #4: Every entry must name a valid, enabled and qualified candidate
+        for candidate_index in &votes {
+            require_valid_candidate(voting_account, *candidate_index)?;
+            if voting_account.disabled[*candidate_index as usize] {
+                return Err(ErrorCode::CandidateDisabled.into());
+            }
+            if voting_account.disqualified[*candidate_index as usize] {
+                return Err(ErrorCode::CandidateDisqualified.into());
+            }
+        }
+
+        // VALIDATION #5: The batch must fit in what is left of the slot's vote cap
+        let clock = Clock::get()?;
+        for _ in &votes {
+            count_slot_vote(voting_account, clock.slot)?;
+        }
+
+        // EXECUTION: Count each entry as one vote from one voter
+        let now = clock.unix_timestamp;
+        for candidate_index in &votes {
+            let count = &mut voting_account.votes[*candidate_index as usize];
+            *count = add_votes(*count, 1)?;
+            voting_account.reached_max_at[*candidate_index as usize] = now;
+        }
+        let batch_size = votes.len() as u64;
+        voting_account.total_votes = voting_account
+            .total_votes
+            .checked_add(batch_size)
+            .ok_or(ErrorCode::Overflow)?;
+        voting_account.batched_votes = voting_account
+            .batched_votes
+            .checked_add(batch_size)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #31: Declare an election null and void
+    // Unlike end_voting no outcome is computed: the election moves to Cancelled for good,
+    // get_winner refuses to report a result, and the account can be closed after the
+    // close delay. Works from any status except Cancelled, including after voting ended
+    // (e.g. when fraud is discovered later)
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - reason: Why the election was cancelled, published in the ElectionCancelled event
+    pub fn emergency_cancel(ctx: Context<AuthorityAction>, reason: String) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2 / EXECUTION #1: Void the election and discard any recorded outcome
+        let voting_account = &mut ctx.accounts.voting_account;
+        transition(voting_account, VotingStatus::Cancelled)?;
+        voting_account.winner_index = NO_WINNER;
+        voting_account.is_tie = false;
+        voting_account.ranked_winner = None;
+
+        // EXECUTION #2: The close delay counts from the cancellation
+        voting_account.end_time = Clock::get()?.unix_timestamp;
+
+        // Announce the cancellation to off-chain listeners
+        emit!(ElectionCancelled {
+            voting_account: voting_account.key(),
+            reason,
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #32: Get the vote counts of every candidate
+    // Elections with results_hidden refuse to report counts until voting has ended.
+    // This gate is advisory: the votes are still stored in the account and anyone can
+    // read them on-chain; it only gives clients an official API that respects the setting
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_results(ctx: Context<ReadVoting>) -> Result<Vec<u64>> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Hidden results are only published once voting has ended
+        require_results_visible(voting_account)?;
+
+        // Return the vote counts in ballot order
+        Ok(voting_account.votes.clone())
+    }
+
+    // INSTRUCTION #33: Get each candidate's share of the votes
+    // Shares are in basis points (10000 = 100%), rounded down, so they may sum to
+    // slightly less than 10000. Subject to the same advisory gate as get_results
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_percentages(ctx: Context<ReadVoting>) -> Result<Vec<u16>> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Hidden results are only published once voting has ended
+        require_results_visible(voting_account)?;
+
+        // Return the shares in ballot order
+        Ok(vote_shares(&voting_account.votes))
+    }
+
+    // INSTRUCTION #34: Reserve space for more candidates ahead of time
+    // Grows the voting account by CANDIDATE_SLOT_SPACE per slot, paid for by the signing
+    // authority, so that later add_candidates calls fit without reallocating. Solana
+    // limits growth to 10 KiB per instruction, so one call reserves at most about 140 slots
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - additional: How many candidate slots to reserve
+    pub fn expand_capacity(ctx: Context<ExpandCapacity>, additional: u16) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The ballot is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // EXECUTION: Record the reserved slots for add_candidates
+        voting_account.reserved_capacity = voting_account
+            .reserved_capacity
+            .checked_add(u32::from(additional))
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #35: Check a vote without casting it
+    // Runs the same checks as vote (deadline, election binding, already voted, candidate
+    // index, status and start time, disabled candidate, whitelist, SOL balance, vote fee)
+    // and logs the outcome, but never writes to an account, so UIs can show a pre-check
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate the user would vote for
+    pub fn simulate_vote(ctx: Context<SimulateVote>, candidate_index: u32) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION #1: Past the deadline vote would be refused
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #2: The checks shared by every way of casting a vote
+        validate_vote(
+            &voting_account.key(),
+            voting_account,
+            &ctx.accounts.user_account,
+            &ctx.accounts.user.to_account_info(),
+            candidate_index,
+            false,
+        )?;
+
+        // VALIDATION #3: The voter must be able to pay the vote fee
+        if ctx.accounts.user.lamports() < voting_account.vote_fee {
+            return Err(ErrorCode::InsufficientFundsForFee.into());
+        }
+
+        // Report what vote would do
+        msg!(
+            "Vote is valid: it would add 1 vote for candidate {} ({}) and charge a fee of {} lamports",
+            candidate_index,
+            voting_account.candidates[candidate_index as usize],
+            voting_account.vote_fee
+        );
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #36: Get the candidates ranked by votes
+    // Returns every candidate as (original index, name, votes), most votes first with ties
+    // in ballot order, so leaderboards don't need to sort. Subject to the same advisory
+    // gate as get_results. Return data is capped at 1 KiB, which limits this to ballots of
+    // a few dozen candidates
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_rankings(ctx: Context<ReadVoting>) -> Result<Vec<CandidateRanking>> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Hidden results are only published once voting has ended
+        require_results_visible(voting_account)?;
+
+        // Return the candidates in ranking order
+        Ok(ranking_order(&voting_account.votes)
+            .into_iter()
+            .map(|index| CandidateRanking {
+                index: index as u32,
+                name: voting_account.candidates[index].clone(),
+                votes: voting_account.votes[index],
+            })
+            .collect())
+    }
+
+    // INSTRUCTION #37: Veto a candidate
+    // For consent-style elections: instead of supporting a candidate the user objects to
+    // one. A veto uses up the user's ballot like a vote, passes the same checks and pays the
+    // same fee. When voting ends a candidate with more vetoes than votes is disqualified
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate the user vetoes
+    // - expected_round: The election round the vote is meant for, as for vote
+    pub fn veto(ctx: Context<Vote>, candidate_index: u32, expected_round: u64) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // A veto arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The veto must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: Run the checks shared by every way of casting a vote
+        let election = voting_account.key();
+        validate_vote(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            candidate_index,
+            false,
+        )?;
+
+        // VALIDATION #3: Count the veto against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #4: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION: Count the veto and mark the user as having voted, without a choice
+        let vetoes = &mut voting_account.vetoes[candidate_index as usize];
+        *vetoes = vetoes.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        mark_voted(&election, voting_account, user_account, ctx.accounts.user.key, None)
+    }
+
+    // INSTRUCTION #38: Add an operational admin
+    // Admins may pause and resume voting, but everything else (including transferring
+    // ownership and closing the election) stays with the owner authority
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - admin: The key to grant admin rights to
+    pub fn add_admin(ctx: Context<AuthorityAction>, admin: Pubkey) -> Result<()> {
+        // VALIDATION #1: Only the owner authority manages admins
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Each admin is listed once
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.admins.contains(&admin) {
+            return Err(ErrorCode::AdminAlreadyAdded.into());
+        }
+
+        // VALIDATION #3: The list must fit the allocated space
+        if voting_account.admins.len() >= MAX_ADMINS {
+            return Err(ErrorCode::TooManyAdmins.into());
+        }
+
+        // EXECUTION: Grant the admin rights
+        voting_account.admins.push(admin);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #39: Remove an operational admin
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - admin: The key to revoke admin rights from
+    pub fn remove_admin(ctx: Context<AuthorityAction>, admin: Pubkey) -> Result<()> {
+        // VALIDATION #1: Only the owner authority manages admins
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The key must currently be an admin
+        let voting_account = &mut ctx.accounts.voting_account;
+        let position = voting_account
+            .admins
+            .iter()
+            .position(|key| *key == admin)
+            .ok_or(ErrorCode::AdminNotFound)?;
+
+        // EXECUTION: Revoke the admin rights
+        voting_account.admins.remove(position);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #40: Remove a candidate from the ballot
+    // Later candidates move up one position, but their IDs stay the same, so votes cast by
+    // ID keep landing on the right candidate. Only candidates without votes or vetoes can be
+    // removed, and not once ranked ballots (which store positions) have been cast. The
+    // account keeps its size; the freed bytes are simply unused
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_id: The ID of the candidate to remove
+    pub fn remove_candidate(ctx: Context<AuthorityAction>, candidate_id: u64) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The ballot is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: Ranked ballots store positions, so the ballot is fixed once one is cast
+        if voting_account.ranked_ballot_count > 0 {
+            return Err(ErrorCode::RankedBallotsCast.into());
+        }
+
+        // VALIDATION #4: The candidate must exist and have nothing counted for them
+        let position = candidate_position(voting_account, candidate_id)? as usize;
+        if voting_account.votes[position] > 0 || voting_account.vetoes[position] > 0 {
+            return Err(ErrorCode::CandidateHasVotes.into());
+        }
+
+        // EXECUTION: Remove the candidate from every parallel vector
+        voting_account.candidates.remove(position);
+        voting_account.display_names.remove(position);
+        voting_account.candidate_ids.remove(position);
+        voting_account.votes.remove(position);
+        voting_account.vetoes.remove(position);
+        voting_account.reached_max_at.remove(position);
+        voting_account.disabled.remove(position);
+        voting_account.disqualified.remove(position);
+        voting_account.encrypted_tally.remove(position);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #41: Replace an election's discovery tags
+    // Tags are metadata for indexers only and never affect voting, so they can be changed
+    // at any point of the election's life. The space for MAX_TAGS tags is reserved up
+    // front, so no realloc is needed
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - tags: The new tags (at most MAX_TAGS of MAX_TAG_LEN bytes)
+    pub fn update_tags(ctx: Context<AuthorityAction>, tags: Vec<String>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Tags must fit the allocated space
+        validate_tags(&tags)?;
+
+        // EXECUTION: Replace the tags
+        ctx.accounts.voting_account.tags = tags;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #42: Get the election's status as of the current time
+    // The stored status only becomes Ended when someone ends the election, so an election
+    // past its deadline can still read as Active. This reports it as Ended instead,
+    // without changing the stored status
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_status(ctx: Context<ReadVoting>) -> Result<VotingStatus> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // Derive the status from the stored one and the clock
+        Ok(effective_status(
+            voting_account.status,
+            voting_account.end_time,
+            Clock::get()?.unix_timestamp,
+        ))
+    }
+
+    // INSTRUCTION #43: Freeze an election for good
+    // For security incidents: once frozen, every instruction that changes the election or
+    // its voters' accounts (and simulate_vote) fails with Frozen, so the state stays as it
+    // was for investigation. Unlike pause_voting there is no way back, and unlike
+    // emergency_cancel the election keeps its status and results. Read-only getters
+    // keep working
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn emergency_halt(ctx: Context<AuthorityAction>) -> Result<()> {
+        // VALIDATION: The caller must act for the election authority
+        // (AuthorityAction itself refuses elections that are already frozen)
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // EXECUTION: Freeze the election
+        let voting_account = &mut ctx.accounts.voting_account;
+        voting_account.frozen = true;
+
+        // Announce the halt to off-chain listeners
+        emit!(ElectionHalted {
+            voting_account: voting_account.key(),
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #44: Count the candidates that received at least one vote
+    // Helps spot "dead" candidates; the count is logged and emitted as a
+    // NonzeroCandidateCount event. Read-only, and subject to the same advisory gate
+    // as get_results since it reveals part of the tally
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn nonzero_candidate_count(ctx: Context<ReadVoting>) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Hidden results are only published once voting has ended
+        require_results_visible(voting_account)?;
+
+        // Report how many vote counts are above zero
+        let count = voting_account.votes.iter().filter(|votes| **votes > 0).count() as u32;
+        msg!("{} of {} candidates have votes", count, voting_account.votes.len());
+        emit!(NonzeroCandidateCount {
+            voting_account: voting_account.key(),
+            count,
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #45: Withdraw a candidate and discard their votes
+    // Unlike disable_candidate, which keeps the votes a withdrawn candidate received, this
+    // zeroes them and takes them out of total_votes; the discarded votes are added to
+    // withdrawn_votes and published in a CandidateWithdrawn event. The votes are not
+    // redistributed, and the voters stay marked as having voted
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate who withdraws
+    pub fn withdraw_candidate(ctx: Context<AuthorityAction>, candidate_index: u32) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The tally is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: Check if the candidate index is valid
+        require_valid_candidate(voting_account, candidate_index)?;
+
+        // EXECUTION #1: Disable the candidate so they receive no further votes
+        let index = candidate_index as usize;
+        voting_account.disabled[index] = true;
+
+        // EXECUTION #2: Move their votes out of the tally and into withdrawn_votes
+        let votes = std::mem::take(&mut voting_account.votes[index]);
+        voting_account.total_votes = voting_account
+            .total_votes
+            .checked_sub(votes)
+            .ok_or(ErrorCode::Overflow)?;
+        voting_account.withdrawn_votes = voting_account
+            .withdrawn_votes
+            .checked_add(votes)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Record the adjustment for off-chain listeners
+        emit!(CandidateWithdrawn {
+            voting_account: voting_account.key(),
+            index: candidate_index,
+            votes,
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #46: Upgrade a voting account written with the original layout
+    // The original deployed layout (recognised by its size) isn't a prefix of the current
+    // one: votes were u32 rather than u64 and has_ended stood in for status. This decodes
+    // it explicitly (see decode_baseline_voting_account), gives every later field its
+    // initial value, grows the account to the current size (the signer pays the extra
+    // rent) and writes it back with the current version. Accounts already in the current
+    // layout are refused with AlreadyMigrated; the layouts in between were never deployed
+    // and are refused with UnsupportedLayout rather than guessed at
+    // The original layout had no authority, so only the program's upgrade authority
+    // (shown by passing the program's ProgramData account) may migrate one, and it becomes
+    // the election's authority; it can hand the election on with transfer_authority
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn migrate_voting_account(ctx: Context<MigrateVotingAccount>) -> Result<()> {
+        let account_info = ctx.accounts.voting_account.to_account_info();
+        let is_baseline = account_info.data_len() == BASELINE_VOTING_ACCOUNT_SPACE;
+
+        // VALIDATION #1: The account must hold a voting account of some layout
+        let mut voting_account = decode_legacy_voting_account(&account_info.try_borrow_data()?)?;
+
+        // VALIDATION #2: Accounts already in the current layout are left alone
+        if voting_account.version >= VOTING_ACCOUNT_VERSION {
+            return Err(ErrorCode::AlreadyMigrated.into());
+        }
+
+        // VALIDATION #3: The caller must act for the election authority, or for a baseline
+        // account, which has none yet, be the program's upgrade authority
+        if is_baseline {
+            require_upgrade_authority(
+                ctx.accounts.program_data.as_ref(),
+                ctx.accounts.authority.key,
+            )?;
+            voting_account.authority = ctx.accounts.authority.key();
+        } else {
+            require_authority(
+                &voting_account,
+                &ctx.accounts.authority,
+                ctx.remaining_accounts,
+            )?;
+        }
+
+        // EXECUTION #1: Give the fields the old layout lacked their initial values
+        fill_migrated_defaults(&mut voting_account);
+
+        // Baseline elections that had ended never recorded an outcome
+        if is_baseline && voting_account.status == VotingStatus::Ended {
+            let (winner_index, is_tie) = decide_outcome(&voting_account);
+            voting_account.winner_index = winner_index;
+            voting_account.is_tie = is_tie;
+        }
+
+        // EXECUTION #2: Grow the account to the current layout's size, topping up its rent
+        let serialized_len = 8 + voting_account.try_to_vec()?.len();
+        let new_len = account_info
+            .data_len()
+            .max(VotingAccount::space_for(voting_account.candidates.len()))
+            .max(serialized_len);
+        grow_account(
+            &account_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        // EXECUTION #3: Write the account back in the current layout
+        voting_account.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #47: Get the time left until the election's deadline
+    // A convenience for countdowns: seconds until end_time by the cluster clock (zero or
+    // negative once it has passed, i64::MAX without a deadline) and whether voting is
+    // currently paused
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn time_remaining(ctx: Context<ReadVoting>) -> Result<TimeRemaining> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // Count down from the current cluster time
+        Ok(TimeRemaining {
+            seconds: seconds_until(voting_account.end_time, Clock::get()?.unix_timestamp),
+            paused: voting_account.status == VotingStatus::Paused,
+        })
+    }
+
+    // INSTRUCTION #48: Create the public roster of an election that keeps one
+    // The roster is a separate PDA listing who voted (but not how); it starts empty and
+    // grows by one pubkey, paid for by the voter, each time someone new votes
+    // Only the authority (or enough co-signers of a governance authority) can create it
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn initialize_roster(ctx: Context<InitializeRoster>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Only elections with a public roster keep one
+        if !ctx.accounts.voting_account.public_roster {
+            return Err(ErrorCode::RosterDisabled.into());
+        }
+
+        // EXECUTION: Bind the empty roster to the election
+        let roster = &mut ctx.accounts.roster;
+        roster.election = ctx.accounts.voting_account.key();
+        roster.voters = Vec::new();
+        roster.bump = ctx.bumps.roster;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #49: Close a voter's user account and refund its rent to them
+    // Only possible once the election has ended (or was cancelled), so a voter can't
+    // close their account and register again to vote twice. The close constraint on the
+    // context transfers the rent, and the PDA seeds make sure voters only close their own
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn close_user_account(ctx: Context<CloseUserAccount>) -> Result<()> {
+        // VALIDATION: Voting must be over
+        let voting_account = &ctx.accounts.voting_account;
+        if !voting_account.has_ended() && voting_account.status != VotingStatus::Cancelled {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #50: Cast a vote on behalf of a user through CPI
+    // Lets another program (e.g. a governance program doing meta-governance) vote for a
+    // voter who registered their user account as usual, without the voter signing
+    //
+    // SECURITY MODEL: The election's authority names one trusted caller program in
+    // cpi_caller. Instead of the voter's signature, vote_cpi requires a signature from the
+    // delegate PDA [CPI_VOTER_SEED, election, voter] of that program, which only the
+    // program itself can produce (with invoke_signed). The caller program is therefore
+    // fully trusted to decide when a voter's vote is cast: it must only sign for a voter
+    // once that voter has authorized it (e.g. by signing into the caller program or
+    // depositing governance tokens with it). All other vote checks still apply, and
+    // each user account still votes once per round, whether directly or through CPI.
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_id: The stable ID of the candidate to vote for, as for vote
+    // - expected_round: The election round the vote is meant for, as for vote
+    // - delegate_bump: The bump of the caller program's delegate PDA
+    pub fn vote_cpi(
+        ctx: Context<VoteCpi>,
+        candidate_id: u64,
+        expected_round: u64,
+        delegate_bump: u8,
+    ) -> Result<()> {
+        // Get mutable references to the voting and user accounts from the context
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // A vote arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The signing delegate must belong to the election's caller program
+        let election = voting_account.key();
+        require_cpi_delegate(
+            voting_account,
+            &election,
+            ctx.accounts.voter.key,
+            ctx.accounts.delegate.key,
+            delegate_bump,
+        )?;
+
+        // VALIDATION #2: The vote must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #3: Find the candidate's current position on the ballot
+        let candidate_index = candidate_position(voting_account, candidate_id)?;
+
+        // VALIDATION #4: Run the checks shared by every way of casting a vote
+        validate_vote(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.voter.to_account_info(),
+            candidate_index,
+            false,
+        )?;
+
+        // VALIDATION #5: Count the vote against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #6: Collect the vote fee, if any, from the caller's payer
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.payer,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION #1: Count the vote for the voter and mark them as having voted
+        let weight = apply_decay(voting_account, 1)?;
+        record_vote(
+            &election,
+            voting_account,
+            user_account,
+            ctx.accounts.voter.key,
+            candidate_index,
+            weight,
+        )?;
+
+        // EXECUTION #2: List the voter on the public roster, if the election keeps one
+        add_to_roster(voting_account, ctx.accounts.roster.as_mut(), ctx.accounts.voter.key)
+    }
+
+    // INSTRUCTION #51: Get the top of the standings
+    // Returns up to LEADERBOARD_SIZE (candidate_index, votes) entries, most votes first with
+    // ties in ballot order, and logs the ranking; cheaper than get_rankings for UIs showing
+    // live standings on long ballots. Read-only, and subject to the same advisory gate as
+    // get_results
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_leaderboard(ctx: Context<ReadVoting>) -> Result<Vec<LeaderboardEntry>> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Hidden results are only published once voting has ended
+        require_results_visible(voting_account)?;
+
+        // Rank the candidates and keep the leaders
+        let leaderboard: Vec<LeaderboardEntry> = ranking_order(&voting_account.votes)
+            .into_iter()
+            .take(LEADERBOARD_SIZE)
+            .map(|index| LeaderboardEntry {
+                candidate_index: index as u32,
+                votes: voting_account.votes[index],
+            })
+            .collect();
+
+        // Log the ranking for explorers
+        for (rank, entry) in leaderboard.iter().enumerate() {
+            msg!(
+                "#{} {} (index {}): {} votes",
+                rank + 1,
+                voting_account.candidates[entry.candidate_index as usize],
+                entry.candidate_index,
+                entry.votes
+            );
+        }
+
+        // Return the leaders
+        Ok(leaderboard)
+    }
+
+    // INSTRUCTION #52: Undo an end_voting issued by mistake
+    // Within reopen_window seconds of ended_at the election goes back to Active, the
+    // recorded outcome is discarded and the votes cast so far stay counted. The election
+    // runs until its original deadline (kept in configured_end_time, as end_voting replaces
+    // end_time) or, without one, until the authority ends it again; an election that
+    // finalize_expired ended at its deadline is therefore past it again straight away.
+    // Elections released from their registry's active count can't be reopened, as they
+    // would run outside max_active_elections. Only the authority (or enough co-signers of a
+    // governance authority) can reopen; after the window this fails with ReopenWindowClosed
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn reopen_voting(ctx: Context<AuthorityAction>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Only an ended election can be reopened
+        let voting_account = &mut ctx.accounts.voting_account;
+        if !voting_account.has_ended() {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // VALIDATION #3: The reopen window after the end must still be open
+        if !reopen_window_open(
+            voting_account.ended_at,
+            voting_account.reopen_window,
+            Clock::get()?.unix_timestamp,
+        ) {
+            return Err(ErrorCode::ReopenWindowClosed.into());
+        }
+
+        // VALIDATION #4: A released election no longer counts towards the active cap
+        if voting_account.released {
+            return Err(ErrorCode::ElectionAlreadyReleased.into());
+        }
+
+        // EXECUTION #1: Accept votes again, until the original deadline if there is one
+        transition(voting_account, VotingStatus::Active)?;
+        voting_account.end_time = voting_account.configured_end_time;
+        voting_account.ended_at = 0;
+
+        // EXECUTION #2: Discard the outcome recorded when voting ended
+        voting_account.winner_index = NO_WINNER;
+        voting_account.is_tie = false;
+        voting_account.tie_break_seed = [0u8; 32];
+        voting_account.turnout_bps = 0;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #53: Restrict the candidates a voter may choose
+    // For role-restricted ballots, e.g. where voters can only vote within their district.
+    // The indices are ballot positions, so they should be set once the ballot is final
+    // (reorder_candidates and remove_candidate don't update them). An empty list lifts
+    // the restriction. Only the authority (or enough co-signers of a governance authority)
+    // can set it
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - indices: The candidate indices the voter may choose (at most MAX_ELIGIBLE_CANDIDATES)
+    pub fn set_eligible_candidates(
+        ctx: Context<SetEligibleCandidates>,
+        indices: Vec<u32>,
+    ) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        let voting_account = &ctx.accounts.voting_account;
+        require_authority(voting_account, &ctx.accounts.authority, ctx.remaining_accounts)?;
+
+        // VALIDATION #2: The user account must belong to this election
+        let user_account = &mut ctx.accounts.user_account;
+        if user_account.election != voting_account.key() {
+            return Err(ErrorCode::UserAccountMismatch.into());
+        }
+
+        // VALIDATION #3: The voter's choice can't be restricted after the fact
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #4: The list must fit the user account, and name real candidates
+        if indices.len() > MAX_ELIGIBLE_CANDIDATES {
+            return Err(ErrorCode::TooManyEligibleCandidates.into());
+        }
+        for index in &indices {
+            require_valid_candidate(voting_account, *index)?;
+        }
+
+        // EXECUTION: Replace the voter's allow-list
+        user_account.eligible_candidates = indices;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #54: Cast a cumulative ballot
+    // In elections with a points_budget each voter distributes exactly that many points
+    // across candidates (several to one candidate if they like); the points are added to
+    // the candidates' vote counts. The ballot uses up the user's vote like any other and is
+    // recorded without a single choice (voted_for stays None)
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - allocations: (candidate_index, points) pairs whose points add up to points_budget
+    // - expected_round: The election round the vote is meant for, as for vote
+    pub fn cast_cumulative(
+        ctx: Context<Vote>,
+        allocations: Vec<PointAllocation>,
+        expected_round: u64,
+    ) -> Result<()> {
+        // Get mutable references to the voting and user accounts from the context
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // A ballot arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The ballot must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: The election must use cumulative voting
+        if voting_account.points_budget == 0 {
+            return Err(ErrorCode::CumulativeVotingDisabled.into());
+        }
+
+        // VALIDATION #3: Run the checks shared by every way of casting a vote for each
+        // candidate that receives points
+        let election = voting_account.key();
+        for allocation in &allocations {
+            validate_vote(
+                &election,
+                voting_account,
+                user_account,
+                &ctx.accounts.user.to_account_info(),
+                allocation.candidate_index,
+                false,
+            )?;
+        }
+
+        // VALIDATION #4: The points must add up to the budget exactly
+        check_budget(&allocations, voting_account.points_budget)?;
+
+        // VALIDATION #5: Count the ballot against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #6: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION #1: Add each allocation's points to the candidate's count and mark
+        // the user as having voted
+        for allocation in allocations.iter().filter(|allocation| allocation.points > 0) {
+            record_vote(
+                &election,
+                voting_account,
+                user_account,
+                ctx.accounts.user.key,
+                allocation.candidate_index,
+                u64::from(allocation.points),
+            )?;
+        }
+
+        // A cumulative ballot has no single choice
+        user_account.voted_for = None;
+
+        // EXECUTION #2: List the voter on the public roster, if the election keeps one
+        add_to_roster(voting_account, ctx.accounts.roster.as_mut(), ctx.accounts.user.key)
+    }
+
+    // INSTRUCTION #55: Check whether a voter could vote right now
+    // Runs the same gating as vote (frozen elections, the deadline, registration, the
+    // whitelist and balance gates, already having voted, rate limits) without casting
+    // anything, so frontends can simulate it before the voter spends a transaction.
+    // Returns false, logging the reason, instead of failing when the voter is ineligible.
+    // Candidate-specific checks (disabled or not on the voter's allow-list) aren't covered
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn check_eligibility(ctx: Context<CheckEligibility>) -> Result<bool> {
+        let voting_account = &ctx.accounts.voting_account;
+        let now = Clock::get()?.unix_timestamp;
+
+        // Run the gating checks vote would, stopping at the first failure
+        let gate = if voting_account.frozen {
+            Err(ErrorCode::Frozen.into())
+        } else if effective_status(voting_account.status, voting_account.end_time, now)
+            == VotingStatus::Ended
+            && !voting_account.has_ended()
+        {
+            // Past its deadline a vote would be refused
+            Err(ErrorCode::VotingExpired.into())
+        } else {
+            validate_voter(
+                &voting_account.key(),
+                voting_account,
+                &ctx.accounts.user_account,
+                &ctx.accounts.user.to_account_info(),
+                true,
+            )
+        };
+
+        // Report the outcome instead of failing
+        match gate {
+            Ok(()) => Ok(true),
+            Err(error) => {
+                msg!("Not eligible: {}", error);
+                Ok(false)
+            }
+        }
+    }
+
+    // INSTRUCTION #56: Check the election's internal consistency
+    // Verifies that every per-candidate list matches the ballot, that total_votes is the
+    // sum of the candidates' votes and that an ended election's winner is on the ballot,
+    // and emits a HealthReport with the outcome. Meant for monitoring: it never fails on
+    // an inconsistent account, it reports it. Read-only
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn health_check(ctx: Context<ReadVoting>) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // Collect every broken invariant
+        let details = health_issues(voting_account);
+        for detail in &details {
+            msg!("Health check failed: {}", detail);
+        }
+
+        // Publish the report
+        emit!(HealthReport {
+            voting_account: voting_account.key(),
+            ok: details.is_empty(),
+            details,
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #57: Record the vote mint's supply for the quorum
+    // Quorum elections measure their weighted votes against the supply recorded here.
+    // Only the authority (or enough co-signers of a governance authority) can record it,
+    // any time before voting ends; recording again replaces the earlier value. Until it is
+    // recorded the quorum can't be met
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn snapshot_supply(ctx: Context<SnapshotSupply>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The supply is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // EXECUTION: Store the current supply
+        voting_account.total_supply = ctx.accounts.vote_mint.supply;
+        msg!("Recorded a vote mint supply of {}", voting_account.total_supply);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #58: Register a voter on their behalf
+    // A sponsor pays for and creates the voter's user account PDA, so the voter needs no
+    // SOL to join. The voter authorizes it by signing registration_message (the election
+    // and their pubkey) off-chain; the transaction must verify that signature with an
+    // ed25519 program instruction placed right before this one. Registration rules are
+    // those of initialize_user_account (authority co-signature in AuthorityOnly elections,
+    // token balance snapshot in weighted elections)
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - voter: The voter being registered
+    // - signature: The voter's ed25519 signature of registration_message
+    pub fn register_with_authorization(
+        ctx: Context<RegisterWithAuthorization>,
+        voter: Pubkey,
+        signature: [u8; 64],
+    ) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION #1: The voter must have signed the registration message, as checked by
+        // the ed25519 program in the preceding instruction
+        let message = registration_message(&voting_account.key(), &voter);
+        let sysvar = ctx.accounts.instructions.to_account_info();
+        let current = instructions::load_current_index_checked(&sysvar)?;
+        let verified = match current.checked_sub(1) {
+            Some(index) => {
+                let check = instructions::load_instruction_at_checked(index as usize, &sysvar)?;
+                check.program_id == ed25519_program::ID
+                    && ed25519_verifies(&check.data, &voter, &signature, &message)
+            }
+            None => false,
+        };
+        if !verified {
+            return Err(ErrorCode::InvalidSignature.into());
+        }
+
+        // VALIDATION #2: Closed elections only accept voters registered by the authority
+        if voting_account.registration_mode == RegistrationMode::AuthorityOnly {
+            let authority = ctx
+                .accounts
+                .authority
+                .as_ref()
+                .ok_or(ErrorCode::RegistrationClosed)?;
+            require_authority(voting_account, authority, ctx.remaining_accounts)?;
+        }
+
+        // VALIDATION #3: Weighted elections snapshot the voter's token balance
+        let snapshot_weight =
+            registration_weight(voting_account, ctx.accounts.token_account.as_ref(), &voter)?;
+
+        // EXECUTION: Bind the user account to the election and voter
+        register_voter(
+            &mut ctx.accounts.voting_account,
+            &mut ctx.accounts.user_account,
+            voter,
+            snapshot_weight,
+            ctx.bumps.user_account,
+        )?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #59: Pre-seed the vote counts of a migrated election
+    // Carries an off-chain tally over by setting each candidate's starting count (in
+    // ballot order) and total_votes to their sum. Only possible before anyone has voted
+    // and before voting ends, so seeded counts can't be mixed into a running tally; they
+    // don't count as voters for turnout. Only the authority (or enough co-signers of a
+    // governance authority) can seed
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - counts: The starting vote count of each candidate, in ballot order
+    pub fn seed_votes(ctx: Context<AuthorityAction>, counts: Vec<u64>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Nobody may have voted yet
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.total_votes != 0
+            || voting_account.voter_count != 0
+            || voting_account.has_ended()
+        {
+            return Err(ErrorCode::CannotSeedAfterStart.into());
+        }
+
+        // VALIDATION #3: There must be exactly one count per candidate
+        if counts.len() != voting_account.candidates.len() {
+            return Err(ErrorCode::SeedCountsMismatch.into());
+        }
+
+        // VALIDATION #4: The counts must fit in total_votes
+        let total = counts
+            .iter()
+            .try_fold(0u64, |total, count| total.checked_add(*count))
+            .ok_or(ErrorCode::Overflow)?;
+
+        // EXECUTION: Store the starting counts
+        let now = Clock::get()?.unix_timestamp;
+        for (index, count) in counts.iter().enumerate() {
+            if *count > 0 {
+                voting_account.reached_max_at[index] = now;
+            }
+        }
+        voting_account.votes = counts;
+        voting_account.total_votes = total;
+        msg!("Seeded {} votes", total);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #60: Take back a vote shortly after casting it
+    // Within undo_window seconds of their last vote a voter can undo it: the weight it
+    // added is taken off the candidate (found by ID, so a reordered ballot doesn't matter)
+    // and the total, and the user account may vote again. Its blinded increment is XOR-ed
+    // back out of encrypted_tally, and the candidate's reached_max_at is restored unless a
+    // later vote has moved it since. Only single-choice votes cast in the current round of
+    // a running election can be undone; abstentions and cumulative ballots can't. Any vote
+    // fee paid is not refunded. A vote for a candidate disqualified or disabled since
+    // (withdraw_candidate disables the candidate and discards the votes) stands
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn undo_vote(ctx: Context<UndoVote>) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // VALIDATION #1: The election must still be running
+        // Past the deadline the vote stands, like any other (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+        require_voting_open(voting_account)?;
+
+        // VALIDATION #2: The user must have a single-choice vote in this round
+        if !user_account.has_voted
+            || user_account.voted_for.is_none()
+            || user_account.round != voting_account.round
+        {
+            return Err(ErrorCode::NoVoteToUndo.into());
+        }
+
+        // VALIDATION #3: The undo window after the vote must still be open
+        if !undo_window_open(
+            user_account.last_vote_ts,
+            voting_account.undo_window,
+            Clock::get()?.unix_timestamp,
+        ) {
+            return Err(ErrorCode::UndoWindowClosed.into());
+        }
+
+        // VALIDATION #4: The candidate must still be on the ballot
+        let index = voting_account
+            .candidate_ids
+            .iter()
+            .position(|id| *id == user_account.last_vote_candidate_id)
+            .ok_or(ErrorCode::CandidateNotFound)?;
+
+        // VALIDATION #5: The candidate must not have been disqualified, disabled or withdrawn
+        if voting_account.disqualified[index] {
+            return Err(ErrorCode::CandidateDisqualified.into());
+        }
+        if voting_account.disabled[index] {
+            return Err(ErrorCode::CandidateDisabled.into());
+        }
+
+        // EXECUTION #1: Take the vote's weight back off the candidate and the total
+        let weight = user_account.last_vote_weight;
+        voting_account.votes[index] = voting_account.votes[index]
+            .checked_sub(weight)
+            .ok_or(ErrorCode::Overflow)?;
+        voting_account.total_votes = voting_account
+            .total_votes
+            .checked_sub(weight)
+            .ok_or(ErrorCode::Overflow)?;
+        voting_account.voter_count = voting_account
+            .voter_count
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // EXECUTION #2: Take the blinded increment back out of the encrypted slot
+        if let Some(blinded_increment) = user_account.last_vote_blinded_increment {
+            let slot = &mut voting_account.encrypted_tally[index];
+            for (byte, blind) in slot.iter_mut().zip(blinded_increment.iter()) {
+                *byte ^= blind;
+            }
+        }
+
+        // EXECUTION #3: Give the candidate back the time it reached its count before the vote
+        if voting_account.reached_max_at[index] == user_account.last_vote_ts {
+            voting_account.reached_max_at[index] = user_account.last_vote_prev_reached_max_at;
+        }
+
+        // EXECUTION #4: Let the user vote again
+        user_account.has_voted = false;
+        user_account.voted_for = None;
+        user_account.last_vote_weight = 0;
+        user_account.last_vote_blinded_increment = None;
+        msg!("Vote for candidate {} undone", index);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #61: Disqualify a candidate without removing them
+    // Indices stay stable and the candidate's votes stay on record, but further votes for
+    // them are rejected with CandidateDisqualified and end_voting (and create_runoff)
+    // treat them as having no votes, so the best qualified candidate wins. Only the
+    // authority (or enough co-signers of a governance authority) can disqualify, and only
+    // before voting ends
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate to disqualify
+    pub fn disqualify_candidate(ctx: Context<AuthorityAction>, candidate_index: u32) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The outcome is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: Check if the candidate index is valid
+        require_valid_candidate(voting_account, candidate_index)?;
+
+        // EXECUTION: Flag the candidate as disqualified
+        voting_account.disqualified[candidate_index as usize] = true;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #62: Add an extra question to the election
+    // Multi-question elections keep their first question on the main ballot (candidates
+    // and vote) and add the others here; each ballot has its own options and tally and is
+    // answered through vote_ballot. The voting account grows to fit the ballot, paid for
+    // by the signing authority. At most MAX_BALLOTS ballots of 2 to MAX_BALLOT_OPTIONS
+    // distinct options can be added, and only before voting ends
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - question: The question put to the voters
+    // - options: The answers to choose from
+    pub fn add_ballot(
+        ctx: Context<AddBallot>,
+        question: String,
+        options: Vec<String>,
+    ) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The ballots are fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: The election must have room for another ballot
+        if voting_account.ballots.len() >= MAX_BALLOTS {
+            return Err(ErrorCode::TooManyBallots.into());
+        }
+
+        // VALIDATION #4: The question must fit the space the ballot limits assume
+        if question.is_empty() || question.len() > MAX_QUESTION_LEN {
+            return Err(ErrorCode::InvalidQuestion.into());
+        }
+
+        // VALIDATION #5: There must be a real choice between valid, distinct options
+        if options.len() < 2 || options.len() > MAX_BALLOT_OPTIONS {
+            return Err(ErrorCode::InvalidBallotOptions.into());
+        }
+        for (position, option) in options.iter().enumerate() {
+            validate_candidate_name(option)?;
+            if options[..position].contains(option) {
+                msg!("Duplicate option: {}", option);
+                return Err(ErrorCode::DuplicateCandidate.into());
+            }
+        }
+
+        // EXECUTION: Append the ballot with an empty tally
+        msg!("Ballot {}: {}", voting_account.ballots.len(), question);
+        voting_account.ballots.push(Ballot {
+            question,
+            votes: vec![0; options.len()],
+            options,
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #63: Answer one of the election's extra questions
+    // Each ballot takes one vote per voter and round, independently of the main ballot
+    // and the other ballots, with the same gating as vote (registration, whitelist and
+    // balance gates, status, rate limits and vote fee). Extra ballots are plain
+    // one-vote-per-voter questions and don't count towards turnout
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - ballot_index: The index of the question in ballots
+    // - option_index: The index of the chosen answer in that ballot's options
+    // - expected_round: The election round the vote is meant for, as for vote
+    pub fn vote_ballot(
+        ctx: Context<Vote>,
+        ballot_index: u32,
+        option_index: u32,
+        expected_round: u64,
+    ) -> Result<()> {
+        // Get mutable references to the voting and user accounts from the context
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // A vote arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The vote must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: The ballot and option must exist
+        let ballot = voting_account
+            .ballots
+            .get(ballot_index as usize)
+            .ok_or(ErrorCode::InvalidBallot)?;
+        if option_index as usize >= ballot.options.len() {
+            return Err(ErrorCode::InvalidCandidate.into());
+        }
+
+        // VALIDATION #3: Run the voter checks shared by every kind of vote, with this
+        // ballot's voted flag in place of the main ballot's
+        let ballot_bit = 1u32 << ballot_index;
+        let election = voting_account.key();
+        validate_participant(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            user_account.voted_ballots & ballot_bit != 0,
+            false,
+        )?;
+
+        // VALIDATION #4: Count the vote against the election-wide per-slot cap
+        let slot = Clock::get()?.slot;
+        count_slot_vote(voting_account, slot)?;
+
+        // VALIDATION #5: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION #1: Count the answer and mark the ballot as answered
+        let count = &mut voting_account.ballots[ballot_index as usize].votes[option_index as usize];
+        *count = add_votes(*count, 1)?;
+        user_account.voted_ballots |= ballot_bit;
+        user_account.last_vote_slot = slot;
+        msg!("BALLOT|{}|{}|{}|{}", ctx.accounts.user.key, ballot_index, option_index, slot);
+
+        // EXECUTION #2: List the voter on the public roster, if the election keeps one
+        add_to_roster(voting_account, ctx.accounts.roster.as_mut(), ctx.accounts.user.key)
+    }
+
+    // INSTRUCTION #64: Cap how many of an authority's elections can run at once
+    // Unlike max_elections, which counts elections until they are closed, this counts
+    // elections from create_election until they end and are released (release_election
+    // or close_election). It only covers registry-tracked elections; 0 removes the cap
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - max_active_elections: How many elections may run at once (0 for no cap)
+    pub fn set_max_active_elections(
+        ctx: Context<ConfigureRegistry>,
+        max_active_elections: u64,
+    ) -> Result<()> {
+        // EXECUTION: Store the cap (the context checked the authority)
+        ctx.accounts.registry.max_active_elections = max_active_elections;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #65: Stop counting an ended election towards the active cap
+    // Anyone can release a registry-tracked election once it has ended or been cancelled,
+    // freeing its slot under max_active_elections while the account stays readable
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn release_election(ctx: Context<ReleaseElection>) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+
+        // VALIDATION #1: The election must be over
+        if !matches!(voting_account.status, VotingStatus::Ended | VotingStatus::Cancelled) {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // VALIDATION #2: It must still be counted
+        if !voting_account.registry_active {
+            return Err(ErrorCode::ElectionAlreadyReleased.into());
+        }
+
+        // EXECUTION: Take it out of the active count
+        voting_account.registry_active = false;
+        voting_account.released = true;
+        let registry = &mut ctx.accounts.registry;
+        registry.active_elections = registry.active_elections.saturating_sub(1);
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #66: Write a checkpoint of the tally for light clients
+    // The checkpoint PDA records the slot, the vote total and a hash of the votes vector,
+    // so a client that can't replay the chain can check candidate counts it was given
+    // against it. Anyone can checkpoint; writing again overwrites the previous checkpoint
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn checkpoint(ctx: Context<WriteCheckpoint>) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+        let checkpoint = &mut ctx.accounts.checkpoint;
+
+        // EXECUTION: Record the current tally
+        checkpoint.voting_account = voting_account.key();
+        checkpoint.slot = Clock::get()?.slot;
+        checkpoint.total_votes = voting_account.total_votes;
+        checkpoint.votes_hash = votes_hash(&voting_account.votes)?;
+        checkpoint.bump = ctx.bumps.checkpoint;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #67: Correct a candidate's name
+    // Only the name changes: the candidate keeps its index, id and votes. A display name
+    // that was defaulted to the old name follows it. The account grows if the new name
+    // is longer, paid for by the signing authority; names are fixed once voting has ended
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The candidate being renamed
+    // - new_name: The corrected name
+    pub fn rename_candidate(
+        ctx: Context<RenameCandidate>,
+        candidate_index: u32,
+        new_name: String,
+    ) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The ballot is fixed once voting has ended
+        let voting_account = &mut ctx.accounts.voting_account;
+        if voting_account.has_ended() {
+            return Err(ErrorCode::VotingAlreadyEnded.into());
+        }
+
+        // VALIDATION #3: Check if the candidate index is valid
+        require_valid_candidate(voting_account, candidate_index)?;
+
+        // VALIDATION #4: The new name must be valid and not another candidate's
+        validate_candidate_name(&new_name)?;
+        let index = candidate_index as usize;
+        if voting_account
+            .candidates
+            .iter()
+            .enumerate()
+            .any(|(other, name)| other != index && *name == new_name)
+        {
+            msg!("Duplicate candidate: {}", new_name);
+            return Err(ErrorCode::DuplicateCandidate.into());
+        }
+
+        // EXECUTION: Replace the name (and a defaulted display name)
+        if voting_account.display_names[index] == voting_account.candidates[index] {
+            voting_account.display_names[index] = new_name.clone();
+        }
+        voting_account.candidates[index] = new_name;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #68: Cast a vote together with the voter's reason for it
+    // Counts like vote, and also records the choice and the reason (up to MAX_REASON_LEN
+    // bytes) in the voter's vote receipt PDA. Elections with require_reason only take votes
+    // this way, and refuse an empty reason
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - candidate_index: The index of the candidate the user wants to vote for
+    // - expected_round: The election round the vote is meant for, as for vote
+    // - reason: Why the voter chose them
+    pub fn vote_with_reason(
+        ctx: Context<VoteWithReason>,
+        candidate_index: u32,
+        expected_round: u64,
+        reason: String,
+    ) -> Result<()> {
+        // Get mutable references to the voting and user accounts from the context
+        let voting_account = &mut ctx.accounts.voting_account;
+        let user_account = &mut ctx.accounts.user_account;
+
+        // A vote arriving after the deadline is refused (see require_before_deadline)
+        require_before_deadline(voting_account)?;
+
+        // VALIDATION #1: The vote must be meant for the current round
+        if expected_round != voting_account.round {
+            return Err(ErrorCode::StaleRound.into());
+        }
+
+        // VALIDATION #2: The reason must fit the receipt, and be given if it is required
+        if reason.len() > MAX_REASON_LEN {
+            return Err(ErrorCode::ReasonTooLong.into());
+        }
+        if voting_account.require_reason && reason.trim().is_empty() {
+            return Err(ErrorCode::ReasonRequired.into());
+        }
+
+        // VALIDATION #3: Run the checks shared by every way of casting a vote
+        let election = voting_account.key();
+        validate_vote(
+            &election,
+            voting_account,
+            user_account,
+            &ctx.accounts.user.to_account_info(),
+            candidate_index,
+            true,
+        )?;
+
+        // VALIDATION #4: Count the vote against the election-wide per-slot cap
+        count_slot_vote(voting_account, Clock::get()?.slot)?;
+
+        // VALIDATION #5: Collect the vote fee, if any, from the voter
+        collect_vote_fee(
+            voting_account.vote_fee,
+            &ctx.accounts.user,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        // EXECUTION #1: Count the vote and mark the user as having voted
+        let weight = apply_decay(voting_account, 1)?;
+        record_vote(
+            &election,
+            voting_account,
+            user_account,
+            ctx.accounts.user.key,
+            candidate_index,
+            weight,
+        )?;
+
+        // EXECUTION #2: Record the choice and the reason on the receipt
+        let vote_receipt = &mut ctx.accounts.vote_receipt;
+        vote_receipt.voting_account = election;
+        vote_receipt.voter = ctx.accounts.user.key();
+        vote_receipt.candidate_id = voting_account.candidate_ids[candidate_index as usize];
+        vote_receipt.reason = reason;
+        vote_receipt.bump = ctx.bumps.vote_receipt;
+
+        // EXECUTION #3: List the voter on the public roster, if the election keeps one
+        add_to_roster(voting_account, ctx.accounts.roster.as_mut(), ctx.accounts.user.key)
+    }
+
+    // INSTRUCTION #69: Replace an election's title and description
+    // Like tags these are display metadata and can be changed at any point of the
+    // election's life; their maximum sizes are reserved up front, so no realloc is needed
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    // - title: The new title (at most MAX_TITLE_LEN bytes)
+    // - description: The new description (at most MAX_DESCRIPTION_LEN bytes)
+    pub fn update_metadata(
+        ctx: Context<AuthorityAction>,
+        title: String,
+        description: String,
+    ) -> Result<()> {
+        // VALIDATION #1: The caller must act for the election authority
+        require_authority(
+            &ctx.accounts.voting_account,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: Both must fit the allocated space
+        validate_metadata(&title, &description)?;
+
+        // EXECUTION: Replace the title and description
+        let voting_account = &mut ctx.accounts.voting_account;
+        voting_account.title = title;
+        voting_account.description = description;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #70: Get an overview of the election in one call
+    // Gives dashboards the headline fields without parsing the raw account. Every field is
+    // always present, whichever optional features the election uses; in results_hidden
+    // elections total_votes reads 0 until voting has ended
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn get_summary(ctx: Context<ReadVoting>) -> Result<ElectionSummary> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // Only report the total if the results are visible, and a winner once ended
+        let total_votes = if require_results_visible(voting_account).is_ok() {
+            voting_account.total_votes
+        } else {
+            0
+        };
+        let winner_index = if voting_account.has_ended() {
+            Some(voting_account.winner_index)
+        } else {
+            None
+        };
+
+        Ok(ElectionSummary {
+            title: voting_account.title.clone(),
+            candidate_count: voting_account.candidates.len() as u32,
+            total_votes,
+            has_ended: voting_account.has_ended(),
+            is_paused: voting_account.status == VotingStatus::Paused,
+            start_ts: voting_account.start_time,
+            end_ts: voting_account.end_time,
+            winner_index,
+        })
+    }
+
+    // INSTRUCTION #71: Add an ended sub-election's votes to the election it rolls up into
+    // For hierarchical elections, e.g. districts rolling up to a national total. The
+    // child must name the parent as its rollup_parent and list the same candidates in
+    // the same order; both must have ended. The parent's authority signs, since the votes
+    // change its result, which is decided afresh from the new tally. Each child can only
+    // be aggregated once
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn aggregate_to_parent(ctx: Context<AggregateToParent>) -> Result<()> {
+        // VALIDATION #1: The caller must act for the parent election's authority
+        require_authority(
+            &ctx.accounts.parent,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+        )?;
+
+        // VALIDATION #2: The child must roll up into this parent, once
+        let child = &mut ctx.accounts.child;
+        let parent = &mut ctx.accounts.parent;
+        if child.rollup_parent != Some(parent.key()) {
+            return Err(ErrorCode::NotSubElection.into());
+        }
+        if child.aggregated {
+            return Err(ErrorCode::AlreadyAggregated.into());
+        }
+
+        // VALIDATION #3: Both tallies must be final
+        if !child.has_ended() || !parent.has_ended() {
+            return Err(ErrorCode::VotingNotEnded.into());
+        }
+
+        // VALIDATION #4: The ballots must line up index for index
+        if child.candidates != parent.candidates || child.votes.len() != parent.votes.len() {
+            return Err(ErrorCode::CandidateMismatch.into());
+        }
+
+        // EXECUTION #1: Add the child's votes and voters to the parent's
+        for (total, votes) in parent.votes.iter_mut().zip(child.votes.iter()) {
+            *total = add_votes(*total, *votes)?;
+        }
+        parent.total_votes = add_votes(parent.total_votes, child.total_votes)?;
+        parent.voter_count = parent
+            .voter_count
+            .checked_add(child.voter_count)
+            .ok_or(ErrorCode::Overflow)?;
+        parent.batched_votes = parent
+            .batched_votes
+            .checked_add(child.batched_votes)
+            .ok_or(ErrorCode::Overflow)?;
+        child.aggregated = true;
+
+        // EXECUTION #2: Decide the parent's result from the combined tally
+        let (winner_index, is_tie) = decide_outcome(parent);
+        parent.winner_index = winner_index;
+        parent.is_tie = is_tie;
+        archive_results(parent)
+    }
+
+    // INSTRUCTION #72: Emit the vote counts as a packed byte blob
+    // For clients polling on minimal bandwidth: the PackedResults event carries only the
+    // counts, no names or other strings, laid out as a little-endian u32 candidate count
+    // followed by one little-endian u64 per candidate in ballot order. Subject to the same
+    // advisory gate as get_results
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn export_packed(ctx: Context<ReadVoting>) -> Result<()> {
+        let voting_account = &ctx.accounts.voting_account;
+
+        // VALIDATION: Hidden results are only published once voting has ended
+        require_results_visible(voting_account)?;
+
+        // Publish the packed counts
+        emit!(PackedResults {
+            voting_account: voting_account.key(),
+            data: pack_votes(&voting_account.votes),
+        });
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #73: Upgrade a user account written with an older layout
+    // Fields are only ever appended to UserAccount, so an account created before some of
+    // them (down to the original 8 + 1 bytes holding has_voted) is too short to
+    // deserialize. This decodes it with the missing fields at their initial values (see
+    // decode_legacy_user_account), grows it to the current size, paid for by the voter,
+    // and writes it back with voter set to them. Accounts that already have the current
+    // size are refused
+    // Only the voter can migrate their account: it must be the original [b"user", voter]
+    // PDA and they must sign. Accounts created at a keypair address by the original
+    // initialize_user can't be tied to a voter and stay as they are
+    // A migrated account is READ-ONLY: it keeps the voter's has_voted history, but it
+    // isn't bound to an election and isn't any election's [b"user", election, voter] PDA,
+    // so no vote instruction accepts it. The voter gets a new user account per election
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>) -> Result<()> {
+        let account_info = ctx.accounts.user_account.to_account_info();
+        let new_len = 8 + UserAccount::INIT_SPACE;
+
+        // VALIDATION #1: Only accounts in an older, shorter layout need migrating
+        if account_info.data_len() >= new_len {
+            return Err(ErrorCode::AlreadyMigrated.into());
+        }
+
+        // VALIDATION #2: The account must hold a user account of some layout
+        let mut user_account = decode_legacy_user_account(&account_info.try_borrow_data()?)?;
+
+        // EXECUTION #1: Grow the account to the current layout's size, topping up its rent
+        grow_account(
+            &account_info,
+            &ctx.accounts.voter.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_len,
+        )?;
+
+        // EXECUTION #2: Write the account back in the current layout, owned by the voter
+        // The election stays unset, so the account can't be used to vote
+        user_account.voter = ctx.accounts.voter.key();
+        user_account.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+
+        // Return success
+        Ok(())
+    }
+
+    // INSTRUCTION #74: End an election whose deadline has passed
+    // Votes after end_time are refused rather than ending the election themselves, so
+    // this crank records the outcome. Anyone may call it once the deadline has passed;
+    // it finalizes exactly as end_voting does but leaves end_time at the deadline
+    // Parameters:
+    // - ctx: The context containing all accounts needed for this instruction
+    pub fn finalize_expired(ctx: Context<FinalizeExpired>) -> Result<()> {
+        let voting_account = &mut ctx.accounts.voting_account;
+
+        // VALIDATION: The deadline must have passed without the election being ended
+        if !deadline_passed(voting_account, Clock::get()?.unix_timestamp) {
+            return Err(ErrorCode::DeadlineNotReached.into());
+        }
+
+        // EXECUTION: Mark the voting as ended, record the outcome and announce it
+        finalize_voting(voting_account)
+    }
+}
+
+// HELPER FUNCTIONS
+
+// Determine the outcome of an election from its vote counts
+// Returns (winner_index, is_tie):
+// - a single leader yields its index and is_tie = false
+// - a shared top count yields NO_WINNER and is_tie = true
+// - an empty ballot yields NO_WINNER and is_tie = false
+fn compute_outcome(votes: &[u64]) -> (u32, bool) {
+    // Find the highest vote count, if there are any candidates at all
+    let top = match votes.iter().max() {
+        Some(top) => *top,
+        None => return (NO_WINNER, false),
+    };
+
+    // Collect every candidate that reached the top count
+    let mut leaders = votes.iter().enumerate().filter(|(_, count)| **count == top);
+    let first = leaders.next().map(|(index, _)| index as u32).unwrap_or(NO_WINNER);
+
+    // More than one leader means the election is tied
+    if leaders.next().is_some() {
+        (NO_WINNER, true)
+    } else {
+        (first, false)
+    }
+}
+
+// Check that the leader is ahead of the runner-up by at least min_victory_margin votes
+// A lone candidate is compared against zero; a margin of 0 accepts any outcome
+fn check_margin(votes: &[u64], min_victory_margin: u32) -> Result<()> {
+    let mut sorted = votes.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    let top = sorted.first().copied().unwrap_or(0);
+    let second = sorted.get(1).copied().unwrap_or(0);
+
+    if top - second < u64::from(min_victory_margin) {
+        return Err(ErrorCode::MarginNotMet.into());
+    }
+    Ok(())
+}
+
+// Check that a voting account doesn't hold an election yet
+// Anchor's init constraint already refuses existing accounts; this is defense in depth
+// for the PDA path, should an account ever reach init_voting_account another way
+fn require_uninitialized(voting_account: &VotingAccount) -> Result<()> {
+    if voting_account.is_initialized {
+        return Err(ErrorCode::AlreadyInitialized.into());
+    }
+    Ok(())
+}
+
+// Bytes the voting account grows by when the given candidates are appended:
+// the first reserved_capacity names fit in slots reserved by expand_capacity; each other
+// name needs 4 + len bytes twice (candidate and display name). The other per-candidate
+// entries are already reserved for MAX_CANDIDATES by VotingAccount::SPACE
+fn candidates_growth(reserved_capacity: u32, names: &[String]) -> usize {
+    let reserved = names.len().min(reserved_capacity as usize);
+    names[reserved..]
+        .iter()
+        .map(|name| 2 * (4 + name.len()))
+        .sum()
+}
+
+// Bytes a voting account grows by when candidate_index is renamed to new_name: what the
+// new name adds to the candidate's name and, if it was defaulted to the old name, its
+// display name (0 for an unknown index, which rename_candidate rejects)
+fn rename_growth(voting_account: &VotingAccount, candidate_index: u32, new_name: &str) -> usize {
+    let index = candidate_index as usize;
+    let Some(old_name) = voting_account.candidates.get(index) else {
+        return 0;
+    };
+    let names_changed = if voting_account.display_names.get(index) == Some(old_name) {
+        2
+    } else {
+        1
+    };
+    names_changed * new_name.len().saturating_sub(old_name.len())
+}
+
+// Check a candidate or display name entering the ballot
+// Names must not be empty and must fit the MAX_CANDIDATE_NAME_LEN bytes that
+// VotingAccount::space_for and candidates_growth can rely on
+fn validate_candidate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        msg!("Invalid candidate name: {:?}", name);
+        return Err(ErrorCode::InvalidCandidateName.into());
+    }
+    if name.len() > MAX_CANDIDATE_NAME_LEN {
+        msg!(
+            "Candidate name {:?} is {} bytes, the limit is {}",
+            name,
+            name.len(),
+            MAX_CANDIDATE_NAME_LEN
+        );
+        return Err(ErrorCode::CandidateNameTooLong.into());
+    }
+    Ok(())
+}
+
+// Resolve an election's start time and check its schedule against the current time
+// A start_time of 0 starts the election now; an explicit start may lie at most
+// MAX_START_TIME_DRIFT seconds in the past. Voters get the registration period to
+// register before voting opens, so with a period the start moves back to its end if
+// need be. A deadline (if any) must come after the resolved start
+fn check_schedule(
+    now: i64,
+    start_time: i64,
+    end_time: i64,
+    registration_period: i64,
+) -> Result<i64> {
+    if registration_period < 0 {
+        return Err(ErrorCode::InvalidRegistrationPeriod.into());
+    }
+    let mut start_time = if start_time == 0 { now } else { start_time };
+    if start_time < now.saturating_sub(MAX_START_TIME_DRIFT) {
+        return Err(ErrorCode::InvalidStartTime.into());
+    }
+    if registration_period > 0 {
+        start_time = start_time.max(now.saturating_add(registration_period));
+    }
+    if end_time != 0 && end_time <= start_time {
+        return Err(ErrorCode::InvalidEndTime.into());
+    }
+    Ok(start_time)
+}
+
+// Each candidate's share of the votes in basis points, rounded down
+// An election without votes gives every candidate 0
+fn vote_shares(votes: &[u64]) -> Vec<u16> {
+    let total: u128 = votes.iter().map(|count| u128::from(*count)).sum();
+    votes
+        .iter()
+        .map(|count| {
+            // A share never exceeds 10000, so the narrowing is lossless
+            (u128::from(*count) * 10_000)
+                .checked_div(total)
+                .map_or(0, |share| share as u16)
+        })
+        .collect()
+}
+
+// Derive the seed that settles ties from the election and the moment it ended
+// This is PSEUDO-RANDOM, NOT SECURE RANDOMNESS: the slot and time are public and known in
+// advance, so whoever ends the election can predict the pick and, by timing the call,
+// influence it. It only makes the tie-break deterministic and checkable after the fact
+fn tie_break_seed(election: &Pubkey, slot: u64, unix_timestamp: i64) -> [u8; 32] {
+    hash(&[election.as_ref(), &slot.to_le_bytes(), &unix_timestamp.to_le_bytes()].concat())
+        .to_bytes()
+}
+
+// Pick the winner among the candidates sharing the top count under a tie-break rule
+// - None: nobody, the election stays without a winner
+// - Seeded: the first 8 bytes of the seed (little-endian) select among the leaders in
+//   ballot order, so the same votes and seed always pick the same candidate
+// - LowestIndex: the leader listed first on the ballot
+// - EarliestToReach: the leader whose count reached the top count first according to
+//   reached_max_at; leaders who reached it in the same second fall back to ballot order
+fn break_tie(votes: &[u64], rule: TieBreak, seed: &[u8; 32], reached_max_at: &[i64]) -> u32 {
+    let top = votes.iter().max().copied().unwrap_or(0);
+    let leaders: Vec<u32> = (0..votes.len() as u32)
+        .filter(|index| votes[*index as usize] == top)
+        .collect();
+    if leaders.is_empty() {
+        return NO_WINNER;
+    }
+
+    match rule {
+        TieBreak::None => NO_WINNER,
+        TieBreak::Seeded => {
+            let mut pick = [0u8; 8];
+            pick.copy_from_slice(&seed[..8]);
+            leaders[(u64::from_le_bytes(pick) % leaders.len() as u64) as usize]
+        }
+        TieBreak::LowestIndex => leaders[0],
+        TieBreak::EarliestToReach => leaders
+            .iter()
+            .copied()
+            .min_by_key(|index| reached_max_at.get(*index as usize).copied().unwrap_or(0))
+            .unwrap_or(NO_WINNER),
+    }
+}
+
+// Vote counts used to decide the outcome: a candidate disqualified by the authority, or
+// with more vetoes than votes, counts as having no votes
+fn standing_votes(votes: &[u64], vetoes: &[u32], disqualified: &[bool]) -> Vec<u64> {
+    votes
+        .iter()
+        .zip(vetoes)
+        .enumerate()
+        .map(|(index, (count, vetoes))| {
+            if disqualified.get(index) == Some(&true) || u64::from(*vetoes) > *count {
+                0
+            } else {
+                *count
+            }
+        })
+        .collect()
+}
+
+// Reduce a vote's weight by decay_rate_bps_per_day for every full day since the start,
+// but never below min_weight_bps of it:
+//   effective = weight * max(10_000 - decay_rate_bps_per_day * days_elapsed, min_weight_bps)
+//               / 10_000
+// Integer approximation: elapsed time is rounded down to whole days and the result is
+// rounded down, so without a floor a plain vote of weight 1 is worth nothing once any
+// decay applies
+fn decay_weight(
+    weight: u64,
+    decay_rate_bps_per_day: u16,
+    days_elapsed: u64,
+    min_weight_bps: u16,
+) -> u64 {
+    let decay_bps = u128::from(decay_rate_bps_per_day) * u128::from(days_elapsed);
+    let remaining_bps = 10_000u128
+        .saturating_sub(decay_bps)
+        .max(u128::from(min_weight_bps));
+    // The result never exceeds weight, so the narrowing is lossless
+    (u128::from(weight) * remaining_bps / 10_000) as u64
+}
+
+// The weight a vote cast now contributes in the given election
+fn apply_decay(voting_account: &VotingAccount, weight: u64) -> Result<u64> {
+    if voting_account.decay_rate_bps_per_day == 0 {
+        return Ok(weight);
+    }
+    let elapsed = Clock::get()?
+        .unix_timestamp
+        .saturating_sub(voting_account.start_time)
+        .max(0);
+    let days_elapsed = (elapsed / SECONDS_PER_DAY) as u64;
+    Ok(decay_weight(
+        weight,
+        voting_account.decay_rate_bps_per_day,
+        days_elapsed,
+        voting_account.min_decay_weight_bps,
+    ))
+}
+
+// Order the candidate indices by votes, most first
+// Equal counts are broken by the lower index, so every client sees the same order
+fn ranking_order(votes: &[u64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..votes.len()).collect();
+    order.sort_by(|a, b| votes[*b].cmp(&votes[*a]).then(a.cmp(b)));
+    order
+}
+
+// Pick the indices of the two candidates with the most votes, best first
+// Equal counts are broken by the lower index; None if there are fewer than two candidates
+fn top_two(votes: &[u64]) -> Option<[usize; 2]> {
+    match ranking_order(votes).as_slice() {
+        [first, second, ..] => Some([*first, *second]),
+        _ => None,
+    }
+}
+
+// Fill in a newly created voting account (shared by initialize and create_election)
+// and announce it with a VotingInitialized event
+fn init_voting_account(
+    voting_account: &mut Account<VotingAccount>,
+    authority: Pubkey,
+    candidates: Vec<String>,
+    config: ElectionConfig,
+) -> Result<()> {
+    // VALIDATION #1: The account must not hold an election already
+    require_uninitialized(voting_account)?;
+
+    // VALIDATION #2: The close delay, dispute period, reopen and undo windows must not be negative
+    if config.close_delay < 0 {
+        return Err(ErrorCode::InvalidCloseDelay.into());
+    }
+    if config.dispute_period_secs < 0 {
+        return Err(ErrorCode::InvalidDisputePeriod.into());
+    }
+    if config.reopen_window < 0 {
+        return Err(ErrorCode::InvalidReopenWindow.into());
+    }
+    if config.undo_window < 0 {
+        return Err(ErrorCode::InvalidUndoWindow.into());
+    }
+
+    // VALIDATION #3: Tags must fit the allocated space
+    validate_tags(&config.tags)?;
+
+    // VALIDATION #4: The ballot must fit MAX_CANDIDATES
+    // (in practice the 10 KiB an account can be created with caps it lower, see space_for)
+    require_candidate_capacity(candidates.len())?;
+
+    // VALIDATION #5: Display names, if given, must match the candidates one to one
+    if !config.display_names.is_empty() && config.display_names.len() != candidates.len() {
+        return Err(ErrorCode::DisplayNamesMismatch.into());
+    }
+
+    // VALIDATION #6: Every name must fit the space reserved for it
+    for name in candidates.iter().chain(config.display_names.iter()) {
+        validate_candidate_name(name)?;
+    }
+
+    // VALIDATION #7: The locale tag must fit the allocated space
+    if config.locale.len() > MAX_LOCALE_LEN {
+        return Err(ErrorCode::LocaleTooLong.into());
+    }
+
+    // VALIDATION #8: A quorum is a share of the vote mint's supply, so it needs a vote
+    // mint and can't exceed 100%
+    if config.quorum_bps > 10_000 || (config.quorum_bps > 0 && config.vote_mint.is_none()) {
+        return Err(ErrorCode::InvalidQuorum.into());
+    }
+
+    // VALIDATION #9: The start may not be long past, waits for the end of any registration
+    // period, and any deadline must follow it
+    let init_time = Clock::get()?.unix_timestamp;
+    let start_time = check_schedule(
+        init_time,
+        config.start_time,
+        config.end_time,
+        config.registration_period,
+    )?;
+
+    // VALIDATION #10: The title and description must fit the allocated space
+    validate_metadata(&config.title, &config.description)?;
+
+    // Store the ballot's human-readable context
+    voting_account.title = config.title;
+    voting_account.description = config.description;
+
+    // VALIDATION #11: A decayed vote can keep at most its full weight
+    if config.min_decay_weight_bps > 10_000 {
+        return Err(ErrorCode::InvalidDecayFloor.into());
+    }
+    voting_account.min_decay_weight_bps = config.min_decay_weight_bps;
+
+    // Remember when the registration period started
+    voting_account.init_time = init_time;
+    voting_account.registration_period = config.registration_period;
+
+    // Link a sub-election to the election it rolls up into
+    voting_account.rollup_parent = config.rollup_parent;
+    voting_account.aggregated = false;
+
+    // Store the display names, defaulting to the canonical names, and their locale
+    voting_account.display_names = if config.display_names.is_empty() {
+        candidates.clone()
+    } else {
+        config.display_names
+    };
+    voting_account.locale = config.locale;
+
+    // Store the candidates in the voting account
+    voting_account.candidates = candidates;
+
+    // Give every candidate a stable ID, counting up from 0
+    let candidate_count = voting_account.candidates.len() as u64;
+    voting_account.candidate_ids = (0..candidate_count).collect();
+    voting_account.next_candidate_id = candidate_count;
+
+    // Every candidate starts out enabled and qualified
+    voting_account.disabled = vec![false; voting_account.candidates.len()];
+    voting_account.disqualified = vec![false; voting_account.candidates.len()];
+
+    // Extra questions are added afterwards by add_ballot
+    voting_account.ballots = Vec::new();
+
+    // Only create_election counts an election as active in a registry
+    voting_account.registry_active = false;
+    voting_account.released = false;
+
+    // Mark the account as holding an election so it can't be initialized again
+    voting_account.is_initialized = true;
+
+    // New accounts are written with the current layout
+    voting_account.version = VOTING_ACCOUNT_VERSION;
+    
+    // Initialize the votes vector with zeros, one zero for each candidate
+    // This creates a vector with the same length as candidates, filled with zeros
+    voting_account.votes = vec![0; voting_account.candidates.len()];
+
+    // No candidate has been vetoed yet
+    voting_account.vetoes = vec![0; voting_account.candidates.len()];
+
+    // No candidate has reached a count yet
+    voting_account.reached_max_at = vec![0; voting_account.candidates.len()];
+
+    // Every encrypted tally slot starts zeroed
+    voting_account.encrypted_tally = vec![[0; 32]; voting_account.candidates.len()];
+    
+    // Elections accept votes immediately unless created as a draft
+    voting_account.status = if config.start_as_draft {
+        VotingStatus::Draft
+    } else {
+        VotingStatus::Active
+    };
+
+    // No outcome exists until end_voting runs
+    voting_account.is_tie = false;
+    voting_account.winner_index = NO_WINNER;
+
+    // The creator administers the election
+    voting_account.authority = authority;
+    voting_account.vote_fee = config.vote_fee;
+
+    // The optional deadline; end_voting overwrites it with the actual end time
+    voting_account.start_time = start_time;
+    voting_account.end_time = config.end_time;
+    voting_account.configured_end_time = config.end_time;
+    voting_account.close_delay = config.close_delay;
+    voting_account.dispute_period_secs = config.dispute_period_secs;
+    voting_account.reopen_window = config.reopen_window;
+    voting_account.undo_window = config.undo_window;
+    voting_account.require_reason = config.require_reason;
+    voting_account.allow_revote = config.allow_revote;
+    voting_account.points_budget = config.points_budget;
+
+    // The quorum is measured against a supply recorded later by snapshot_supply
+    voting_account.quorum_bps = config.quorum_bps;
+    voting_account.total_supply = 0;
+    voting_account.ended_at = 0;
+
+    // Votes through CPI are only accepted from the configured caller program
+    voting_account.cpi_caller = config.cpi_caller;
+    voting_account.min_victory_margin = config.min_victory_margin;
+    voting_account.min_winning_votes = config.min_winning_votes;
+    voting_account.results_hidden = config.results_hidden;
+    voting_account.decay_rate_bps_per_day = config.decay_rate_bps_per_day;
+
+    // Only create_runoff links an election to a parent
+    voting_account.parent_election = None;
+
+    // Store the spam protection setting
+    voting_account.rate_limit_votes = config.rate_limit_votes;
+
+    // No ranked ballots have been cast or tallied yet
+    voting_account.ranked_ballot_count = 0;
+    voting_account.eliminated = Vec::new();
+    voting_account.ranked_winner = None;
+
+    // Store who may create user accounts for the election
+    voting_account.registration_mode = config.registration_mode;
+
+    // Store the token-weighted voting settings
+    voting_account.vote_mint = config.vote_mint;
+    voting_account.max_weight_per_voter = config.max_weight_per_voter;
+    voting_account.weight_cap_mode = config.weight_cap_mode;
+    voting_account.snapshot_slot = config.snapshot_slot;
+
+    // Store the eligibility settings
+    voting_account.min_sol_balance = config.min_sol_balance;
+
+    // Only the authority administers a new election
+    voting_account.admins = Vec::new();
+
+    // No candidate slots are reserved beyond the initial allocation
+    voting_account.reserved_capacity = 0;
+
+    // Cap the votes accepted per slot; no slot has been counted yet
+    voting_account.max_votes_per_slot = config.max_votes_per_slot;
+    voting_account.votes_this_slot = 0;
+    voting_account.current_slot = 0;
+
+    // A new election is not frozen
+    voting_account.frozen = false;
+
+    // No candidate has withdrawn yet
+    voting_account.withdrawn_votes = 0;
+
+    // The tie-break seed is only fixed when voting ends
+    voting_account.tie_break = config.tie_break;
+    voting_account.tie_break_seed = [0u8; 32];
+
+    // Voters are only listed if the organizer opts in
+    voting_account.public_roster = config.public_roster;
+
+    // Nothing has been counted yet
+    voting_account.total_votes = 0;
+    voting_account.voter_count = 0;
+    voting_account.batched_votes = 0;
+    voting_account.abstain_count = 0;
+    voting_account.registered_voters = 0;
+    voting_account.turnout_bps = 0;
+
+    // Store the discovery metadata
+    voting_account.tags = config.tags;
+
+    // The whitelist starts empty, meaning everyone may vote
+    voting_account.eligible_voter_hashes = Vec::new();
+
+    // Elections start in their first round
+    voting_account.round = 0;
+
+    // Keypair elections have no PDA seeds; create_election fills these in
+    voting_account.election_id = 0;
+    voting_account.bump = 0;
+
+    // Let indexers discover and categorize the new election
+    msg!(
+        "INIT|{}|{}|{}",
+        voting_account.key(),
+        authority,
+        voting_account.candidates.len()
+    );
+    emit!(VotingInitialized {
+        voting_account: voting_account.key(),
+        authority,
+        candidates: voting_account.candidates.clone(),
+        tags: voting_account.tags.clone(),
+    });
+
+    Ok(())
+}
+
+// Check that an election's title and description fit MAX_TITLE_LEN and
+// MAX_DESCRIPTION_LEN bytes
+fn validate_metadata(title: &str, description: &str) -> Result<()> {
+    if title.len() > MAX_TITLE_LEN || description.len() > MAX_DESCRIPTION_LEN {
+        msg!(
+            "Title is {} of {} bytes, description {} of {}",
+            title.len(),
+            MAX_TITLE_LEN,
+            description.len(),
+            MAX_DESCRIPTION_LEN
+        );
+        return Err(ErrorCode::MetadataTooLong.into());
+    }
+    Ok(())
+}
+
+// Check that an election's tags fit within MAX_TAGS tags of MAX_TAG_LEN bytes each
+fn validate_tags(tags: &[String]) -> Result<()> {
+    if tags.len() > MAX_TAGS {
+        return Err(ErrorCode::TooManyTags.into());
+    }
+    if tags.iter().any(|tag| tag.len() > MAX_TAG_LEN) {
+        return Err(ErrorCode::TagTooLong.into());
+    }
+    Ok(())
+}
+
+// Decide an election's (winner_index, is_tie) from its current tally and rules
+// - disqualified candidates, and those with more vetoes than votes, can't win
+// - an election nobody voted in has no winner, rather than an all-zero tie for the
+//   tie-break rule to settle (or a lone candidate winning with no votes)
+// - a shared top count is settled by the election's tie-break rule, if any
+// - a lead below min_victory_margin or a leader below min_winning_votes means no winner
+fn decide_outcome(voting_account: &VotingAccount) -> (u32, bool) {
+    let votes = standing_votes(
+        &voting_account.votes,
+        &voting_account.vetoes,
+        &voting_account.disqualified,
+    );
+    if voting_account.total_votes == 0 {
+        return (NO_WINNER, false);
+    }
+    let (mut winner_index, is_tie) = compute_outcome(&votes);
+    if is_tie {
+        winner_index = break_tie(
+            &votes,
+            voting_account.tie_break,
+            &voting_account.tie_break_seed,
+            &voting_account.reached_max_at,
+        );
+    }
+    if check_margin(&votes, voting_account.min_victory_margin).is_err() {
+        winner_index = NO_WINNER;
+    }
+    let top_votes = votes.iter().max().copied().unwrap_or(0);
+    if top_votes < u64::from(voting_account.min_winning_votes) {
+        winner_index = NO_WINNER;
+    }
+    (winner_index, is_tie)
+}
+
+// Mark an election as ended and record its outcome
+// Computing the outcome once means clients don't have to re-scan the votes
+fn finalize_voting(voting_account: &mut Account<VotingAccount>) -> Result<()> {
+    transition(voting_account, VotingStatus::Ended)?;
+
+    // Remember when voting ended, which starts the reopen window
+    voting_account.ended_at = Clock::get()?.unix_timestamp;
+
+    // Seeded elections fix their tie-break seed now
+    if voting_account.tie_break == TieBreak::Seeded {
+        let clock = Clock::get()?;
+        voting_account.tie_break_seed =
+            tie_break_seed(&voting_account.key(), clock.slot, clock.unix_timestamp);
+    }
+
+    // Decide the outcome, telling listeners why an election has no winner
+    let (winner_index, is_tie) = decide_outcome(voting_account);
+    if voting_account.total_votes == 0 {
+        emit!(NoVotesCast {
+            voting_account: voting_account.key(),
+        });
+    }
+    let top_votes = standing_votes(
+        &voting_account.votes,
+        &voting_account.vetoes,
+        &voting_account.disqualified,
+    )
+    .into_iter()
+    .max()
+    .unwrap_or(0);
+    if top_votes < u64::from(voting_account.min_winning_votes) {
+        emit!(NoWinner {
+            voting_account: voting_account.key(),
+            top_votes,
+            min_winning_votes: voting_account.min_winning_votes,
+        });
+    }
+    voting_account.winner_index = winner_index;
+    voting_account.is_tie = is_tie;
+
+    // Record the share of registered voters who took part
+    voting_account.turnout_bps =
+        turnout_bps(voting_account.voter_count, voting_account.registered_voters);
+
+    // Announce the result to off-chain listeners
+    msg!(
+        "END|{}|{}|{}",
+        voting_account.key(),
+        winner_index,
+        voting_account.total_votes
+    );
+    emit!(VotingEnded {
+        voting_account: voting_account.key(),
+        winner_index,
+        is_tie,
+        abstain_count: voting_account.abstain_count,
+    });
+    archive_results(voting_account)?;
+
+    Ok(())
+}
+
+// Check whether an election that hasn't been ended yet is past its deadline
+fn deadline_passed(voting_account: &VotingAccount, now: i64) -> bool {
+    !voting_account.has_ended() && voting_account.end_time != 0 && now >= voting_account.end_time
+}
+
+// Refuse a vote that arrives after the election's deadline with VotingExpired
+// Called at the start of each voting instruction. The election isn't ended here, as
+// failing the vote rolls back any change; end_voting or the permissionless
+// finalize_expired crank records the outcome
+fn require_before_deadline(voting_account: &VotingAccount) -> Result<()> {
+    if deadline_passed(voting_account, Clock::get()?.unix_timestamp) {
+        return Err(ErrorCode::VotingExpired.into());
+    }
+    Ok(())
+}
+
+// The status an election effectively has at time now
+// Draft, Active and Paused elections whose deadline (if any) has passed are over even
+// though nobody has ended them yet; Ended and Cancelled are final as stored
+fn effective_status(status: VotingStatus, end_time: i64, now: i64) -> VotingStatus {
+    match status {
+        VotingStatus::Draft | VotingStatus::Active | VotingStatus::Paused
+            if end_time != 0 && now >= end_time =>
+        {
+            VotingStatus::Ended
+        }
+        _ => status,
+    }
+}
+
+// Share of registered voters who voted, in basis points (10000 = everyone)
+// Elections nobody registered for have a turnout of 0; voters without a registration
+// (e.g. keypair user accounts) can't push it past 10000
+fn turnout_bps(voter_count: u64, registered_voters: u64) -> u16 {
+    if registered_voters == 0 {
+        return 0;
+    }
+    let bps = u128::from(voter_count) * 10_000 / u128::from(registered_voters);
+    bps.min(10_000) as u16
+}
+
+// Whether total_votes reach quorum_bps of total_supply (never before a supply is recorded)
+fn quorum_met(total_votes: u64, total_supply: u64, quorum_bps: u16) -> bool {
+    total_supply > 0
+        && u128::from(total_votes) * 10_000 >= u128::from(total_supply) * u128::from(quorum_bps)
+}
+
+// Fingerprint of an election's final results: the SHA-256 of the Borsh encoding of
+// (candidates, votes), i.e. each vector's u32 length followed by its entries (strings as
+// a u32 length and UTF-8 bytes, counts as u64), little-endian
+fn results_hash(candidates: &[String], votes: &[u64]) -> Result<[u8; 32]> {
+    let data = (candidates, votes).try_to_vec()?;
+    Ok(hash(&data).to_bytes())
+}
+
+// Pack the vote counts for export_packed: a u32 count, then each count as a u64, all
+// little-endian (the same bytes as the Borsh encoding of the votes vector)
+fn pack_votes(votes: &[u64]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + 8 * votes.len());
+    data.extend_from_slice(&(votes.len() as u32).to_le_bytes());
+    for count in votes {
+        data.extend_from_slice(&count.to_le_bytes());
+    }
+    data
+}
+
+// Hash of the serialized votes vector, as stored in a Checkpoint
+fn votes_hash(votes: &[u64]) -> Result<[u8; 32]> {
+    let data = votes.try_to_vec()?;
+    Ok(hash(&data).to_bytes())
+}
+
+// Emit a ResultsArchived event committing to the election's current results
+fn archive_results(voting_account: &Account<VotingAccount>) -> Result<()> {
+    emit!(ResultsArchived {
+        voting_account: voting_account.key(),
+        hash: results_hash(&voting_account.candidates, &voting_account.votes)?,
+        total_votes: voting_account.total_votes,
+    });
+    Ok(())
+}
+
+// Whether a vote cast at last_vote_ts may still be undone (never for a 0 window)
+fn undo_window_open(last_vote_ts: i64, undo_window: i64, now: i64) -> bool {
+    undo_window > 0 && now.saturating_sub(last_vote_ts) <= undo_window
+}
+
+// Whether an election that ended at ended_at may still be reopened (never for a 0 window)
+fn reopen_window_open(ended_at: i64, reopen_window: i64, now: i64) -> bool {
+    now < ended_at.saturating_add(reopen_window)
+}
+
+// Whether an ended election is still inside the dispute window that follows end_time
+fn dispute_period_active(end_time: i64, dispute_period_secs: i64, now: i64) -> bool {
+    now < end_time.saturating_add(dispute_period_secs)
+}
+
+// Seconds from now until an election's end_time, or i64::MAX when it has no deadline
+fn seconds_until(end_time: i64, now: i64) -> i64 {
+    if end_time == 0 {
+        return i64::MAX;
+    }
+    end_time.saturating_sub(now)
+}
+
+// Check that an election's results may be reported through the result getters
+// Fails with ResultsHidden while a results_hidden election has not ended
+fn require_results_visible(voting_account: &VotingAccount) -> Result<()> {
+    if voting_account.results_hidden && !voting_account.has_ended() {
+        return Err(ErrorCode::ResultsHidden.into());
+    }
+    Ok(())
+}
+
+// Check that an election may be closed: voting has ended and the close delay has passed
+fn require_closable(voting_account: &VotingAccount) -> Result<()> {
+    // Results can only be discarded after voting has ended (or was cancelled)
+    if !voting_account.has_ended() && voting_account.status != VotingStatus::Cancelled {
+        return Err(ErrorCode::VotingNotEnded.into());
+    }
+
+    // The decommission window must have elapsed
+    let closes_at = voting_account
+        .end_time
+        .checked_add(voting_account.close_delay)
+        .ok_or(ErrorCode::Overflow)?;
+    if Clock::get()?.unix_timestamp < closes_at {
+        return Err(ErrorCode::CloseTooSoon.into());
+    }
+
+    Ok(())
+}
+
+// Check that a ballot of candidate_count candidates fits MAX_CANDIDATES
+fn require_candidate_capacity(candidate_count: usize) -> Result<()> {
+    if candidate_count > MAX_CANDIDATES {
+        msg!(
+            "A ballot of {} candidates exceeds the limit of {}",
+            candidate_count,
+            MAX_CANDIDATES
+        );
+        return Err(ErrorCode::TooManyCandidates.into());
+    }
+    Ok(())
+}
+
+// Check that a candidate index is in range
+// Error codes can't carry the index, so the attempted index and the valid range
+// are logged before failing with InvalidCandidate
+fn require_valid_candidate(voting_account: &VotingAccount, candidate_index: u32) -> Result<()> {
+    let candidate_count = voting_account.candidates.len() as u32;
+    if candidate_index >= candidate_count {
+        msg!(
+            "Invalid candidate index {}: election has {} candidates (valid indices 0..{})",
+            candidate_index,
+            candidate_count,
+            candidate_count
+        );
+    }
+    require_gt!(candidate_count, candidate_index, ErrorCode::InvalidCandidate);
+    Ok(())
+}
+
+// Find the ballot position of the candidate with the given ID
+// Logs the ID before failing with CandidateNotFound, like require_valid_candidate
+fn candidate_position(voting_account: &VotingAccount, candidate_id: u64) -> Result<u32> {
+    match voting_account.candidate_ids.iter().position(|id| *id == candidate_id) {
+        Some(position) => Ok(position as u32),
+        None => {
+            msg!("No candidate with ID {} in this election", candidate_id);
+            Err(ErrorCode::CandidateNotFound.into())
+        }
+    }
+}
+
+// Run the checks shared by every way of casting a vote for a candidate
+// reason_given says whether the vote carries a reason (only vote_with_reason's do)
+fn validate_vote(
+    election: &Pubkey,
+    voting_account: &VotingAccount,
+    user_account: &UserAccount,
+    voter: &AccountInfo,
+    candidate_index: u32,
+    reason_given: bool,
+) -> Result<()> {
+    // The per-candidate lists indexed below must be intact
+    require_consistent_candidates(voting_account)?;
+
+    // The voter must be allowed to take part at all
+    validate_voter(election, voting_account, user_account, voter, reason_given)?;
+
+    // Check if the candidate index is valid
+    require_valid_candidate(voting_account, candidate_index)?;
+
+    // Disabled candidates keep their votes but cannot receive new ones
+    if voting_account.disabled[candidate_index as usize] {
+        return Err(ErrorCode::CandidateDisabled.into());
+    }
+
+    // Neither can disqualified ones
+    if voting_account.disqualified[candidate_index as usize] {
+        return Err(ErrorCode::CandidateDisqualified.into());
+    }
+
+    // Voters restricted to some candidates (e.g. their district's) may only choose those
+    if !may_choose(&user_account.eligible_candidates, candidate_index) {
+        return Err(ErrorCode::CandidateNotEligible.into());
+    }
+
+    Ok(())
+}
+
+// The name and length of every list parallel to candidates
+fn parallel_lengths(voting_account: &VotingAccount) -> [(&'static str, usize); 8] {
+    [
+        ("votes", voting_account.votes.len()),
+        ("disabled", voting_account.disabled.len()),
+        ("encrypted_tally", voting_account.encrypted_tally.len()),
+        ("display_names", voting_account.display_names.len()),
+        ("vetoes", voting_account.vetoes.len()),
+        ("candidate_ids", voting_account.candidate_ids.len()),
+        ("reached_max_at", voting_account.reached_max_at.len()),
+        ("disqualified", voting_account.disqualified.len()),
+    ]
+}
+
+// Refuse to work on an election whose per-candidate lists disagree with candidates
+// (e.g. after a faulty migration), instead of indexing past the end of one of them
+fn require_consistent_candidates(voting_account: &VotingAccount) -> Result<()> {
+    let candidate_count = voting_account.candidates.len();
+    for (name, len) in parallel_lengths(voting_account) {
+        if len != candidate_count {
+            msg!("{} has {} entries for {} candidates", name, len, candidate_count);
+            return Err(ErrorCode::CorruptedState.into());
+        }
+    }
+    Ok(())
+}
+
+// Describe each invariant the election's state breaks (empty when it is consistent)
+// - The per-candidate lists all have one entry per candidate
+// - total_votes is the sum of the candidates' votes (weights and withdrawals included,
+//   as every path updates both)
+// - An ended election's winner is NO_WINNER or a candidate on the ballot
+fn health_issues(voting_account: &VotingAccount) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    // Every list parallel to candidates must match its length
+    let candidate_count = voting_account.candidates.len();
+    for (name, len) in parallel_lengths(voting_account) {
+        if len != candidate_count {
+            issues.push(format!(
+                "{} has {} entries for {} candidates",
+                name, len, candidate_count
+            ));
+        }
+    }
+
+    // Every extra ballot needs a count per option
+    for (index, ballot) in voting_account.ballots.iter().enumerate() {
+        if ballot.votes.len() != ballot.options.len() {
+            issues.push(format!(
+                "ballot {} has {} counts for {} options",
+                index,
+                ballot.votes.len(),
+                ballot.options.len()
+            ));
+        }
+    }
+
+    // The running total must agree with the per-candidate counts
+    let sum = voting_account
+        .votes
+        .iter()
+        .try_fold(0u64, |sum, votes| sum.checked_add(*votes));
+    if sum != Some(voting_account.total_votes) {
+        issues.push(format!(
+            "total_votes is {} but the candidates' votes sum to {:?}",
+            voting_account.total_votes, sum
+        ));
+    }
+
+    // A recorded winner must be on the ballot
+    if voting_account.has_ended()
+        && voting_account.winner_index != NO_WINNER
+        && voting_account.winner_index as usize >= candidate_count
+    {
+        issues.push(format!(
+            "winner_index {} is not a candidate",
+            voting_account.winner_index
+        ));
+    }
+
+    issues
+}
+
+// Check that a cumulative ballot's allocations add up to the points budget exactly
+// Fails with BudgetMismatch for an over- or under-spent budget
+fn check_budget(allocations: &[PointAllocation], points_budget: u32) -> Result<()> {
+    let spent: u64 = allocations
+        .iter()
+        .map(|allocation| u64::from(allocation.points))
+        .sum();
+    if spent != u64::from(points_budget) {
+        msg!("Ballot spends {} of {} points", spent, points_budget);
+        return Err(ErrorCode::BudgetMismatch.into());
+    }
+    Ok(())
+}
+
+// The weight a voter registers with: the balance of their token account for the vote mint
+// in weighted elections, 0 otherwise (or when they register without a token account)
+fn registration_weight(
+    voting_account: &VotingAccount,
+    token_account: Option<&Account<TokenAccount>>,
+    voter: &Pubkey,
+) -> Result<u64> {
+    match (voting_account.vote_mint, token_account) {
+        (Some(vote_mint), Some(token_account)) => {
+            if token_account.owner != *voter || token_account.mint != vote_mint {
+                return Err(ErrorCode::InvalidTokenAccount.into());
+            }
+            Ok(token_account.amount)
+        }
+        _ => Ok(0),
+    }
+}
+
+// Set up a freshly created user account for the voter and count the registration
+fn register_voter(
+    voting_account: &mut Account<VotingAccount>,
+    user_account: &mut UserAccount,
+    voter: Pubkey,
+    snapshot_weight: u64,
+    bump: u8,
+) -> Result<()> {
+    // Fix the voter's weight for the rest of the election
+    user_account.snapshot_weight = snapshot_weight;
+
+    // Set the initial voting state to false (user has not voted)
+    user_account.has_voted = false;
+    user_account.voted_for = None;
+
+    // Every candidate may be chosen until the authority restricts the voter
+    user_account.eligible_candidates = Vec::new();
+
+    // Bind the account to the election and voter it was registered for
+    user_account.election = voting_account.key();
+    user_account.voter = voter;
+    user_account.round = voting_account.round;
+
+    // Store the canonical bump so later derivations don't need to search for it
+    user_account.bump = bump;
+
+    // Count the registration towards the turnout denominator
+    voting_account.registered_voters = voting_account
+        .registered_voters
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+// The message a voter signs to authorize register_with_authorization for an election
+fn registration_message(election: &Pubkey, voter: &Pubkey) -> Vec<u8> {
+    [REGISTRATION_MESSAGE_PREFIX, election.as_ref(), voter.as_ref()].concat()
+}
+
+// Whether ed25519 program instruction data verifies exactly one signature, by the voter,
+// of the message, with everything stored in that same instruction
+// Layout: signature count (u8), padding (u8), then per signature seven u16 fields
+// (signature offset and instruction, public key offset and instruction, message offset,
+// size and instruction) followed by the data they point to; u16::MAX as the instruction
+// index means "this instruction"
+fn ed25519_verifies(data: &[u8], voter: &Pubkey, signature: &[u8; 64], message: &[u8]) -> bool {
+    const HEADER_LEN: usize = 2 + 14;
+    if data.len() < HEADER_LEN || data[0] != 1 {
+        return false;
+    }
+    let field = |index: usize| u16::from_le_bytes([data[2 + 2 * index], data[3 + 2 * index]]);
+    let slice = |offset: u16, len: usize| data.get(offset as usize..offset as usize + len);
+
+    // All three parts must come from the ed25519 instruction itself
+    if field(1) != u16::MAX || field(3) != u16::MAX || field(6) != u16::MAX {
+        return false;
+    }
+    slice(field(0), 64) == Some(&signature[..])
+        && slice(field(2), 32) == Some(voter.as_ref())
+        && field(5) as usize == message.len()
+        && slice(field(4), message.len()) == Some(message)
+}
+
+// Whether a voter's allow-list permits the candidate (an empty list allows everyone)
+fn may_choose(eligible_candidates: &[u32], candidate_index: u32) -> bool {
+    eligible_candidates.is_empty() || eligible_candidates.contains(&candidate_index)
+}
+
+// Run the checks on the voter shared by voting and abstaining
+fn validate_voter(
+    election: &Pubkey,
+    voting_account: &VotingAccount,
+    user_account: &UserAccount,
+    voter: &AccountInfo,
+    reason_given: bool,
+) -> Result<()> {
+    // Elections that allow revoting count every vote a user casts on the main ballot
+    let already_voted = user_account.has_voted && !voting_account.allow_revote;
+    validate_participant(
+        election,
+        voting_account,
+        user_account,
+        voter,
+        already_voted,
+        reason_given,
+    )
+}
+
+// Run the checks on the voter shared by every kind of vote; already_voted says whether
+// they have already cast the vote in question (the main ballot's, or an extra ballot's)
+// and reason_given whether it carries a reason
+fn validate_participant(
+    election: &Pubkey,
+    voting_account: &VotingAccount,
+    user_account: &UserAccount,
+    voter: &AccountInfo,
+    already_voted: bool,
+    reason_given: bool,
+) -> Result<()> {
+    // Elections that require a reason only take votes through vote_with_reason
+    if voting_account.require_reason && !reason_given {
+        return Err(ErrorCode::ReasonRequired.into());
+    }
+
+    // A user account is bound to the first election it votes in and cannot be
+    // substituted into another one
+    if user_account.election != Pubkey::default() && user_account.election != *election {
+        return Err(ErrorCode::UserAccountMismatch.into());
+    }
+
+    // Likewise only the voter the account belongs to may sign with it
+    if user_account.voter != Pubkey::default() && user_account.voter != *voter.key {
+        return Err(ErrorCode::UserAccountMismatch.into());
+    }
+
+    // Closed elections only accept accounts registered for them by the authority
+    if voting_account.registration_mode == RegistrationMode::AuthorityOnly
+        && user_account.election != *election
+    {
+        return Err(ErrorCode::RegistrationClosed.into());
+    }
+
+    // Check if the user has already voted
+    if already_voted {
+        return Err(ErrorCode::AlreadyVoted.into());
+    }
+
+    // Rate-limited elections accept at most one vote per user account per slot
+    if voting_account.rate_limit_votes
+        && user_account.last_vote_slot != 0
+        && user_account.last_vote_slot == Clock::get()?.slot
+    {
+        return Err(ErrorCode::TooFast.into());
+    }
+
+    // The election must be open and the voter eligible
+    require_voting_open(voting_account)?;
+    require_eligible(voting_account, voter)
+}
+
+// Count a vote cast in slot against the election's max_votes_per_slot cap (0 for none)
+// The counter restarts whenever a vote arrives in a new slot; once the cap is reached
+// further votes in the same slot fail with RateLimited
+fn count_slot_vote(voting_account: &mut VotingAccount, slot: u64) -> Result<()> {
+    // A new slot starts a fresh count
+    if slot != voting_account.current_slot {
+        voting_account.current_slot = slot;
+        voting_account.votes_this_slot = 0;
+    }
+
+    // Reject the vote once this slot's cap is used up
+    if voting_account.max_votes_per_slot != 0
+        && voting_account.votes_this_slot >= voting_account.max_votes_per_slot
+    {
+        return Err(ErrorCode::RateLimited.into());
+    }
+
+    voting_account.votes_this_slot += 1;
+    Ok(())
+}
+
+// Check that the election currently accepts votes
+fn require_voting_open(voting_account: &VotingAccount) -> Result<()> {
+    // Votes are only accepted while the election is active
+    match voting_account.status {
+        VotingStatus::Active => {}
+        VotingStatus::Paused => return Err(ErrorCode::VotingPaused.into()),
+        VotingStatus::Draft | VotingStatus::Ended | VotingStatus::Cancelled => {
+            return Err(ErrorCode::VotingNotActive.into())
+        }
+    }
+
+    // ...and once its start time has been reached
+    // check_schedule already pushed start_time past any registration period, so an early
+    // vote in an election with one is refused as arriving during registration
+    if Clock::get()?.unix_timestamp < voting_account.start_time {
+        if voting_account.registration_period > 0 {
+            return Err(ErrorCode::RegistrationPeriodActive.into());
+        }
+        return Err(ErrorCode::VotingNotStarted.into());
+    }
+    Ok(())
+}
+
+// Check that the voter passes the election's whitelist and balance gates
+fn require_eligible(voting_account: &VotingAccount, voter: &AccountInfo) -> Result<()> {
+    // Whitelisted elections only accept voters whose hashed key is on the list
+    if !voting_account.eligible_voter_hashes.is_empty() {
+        let voter_hash = hash(voter.key.as_ref()).to_bytes();
+        if !voting_account.eligible_voter_hashes.contains(&voter_hash) {
+            return Err(ErrorCode::NotEligible.into());
+        }
+    }
+
+    // Balance-gated elections require the voter to hold a minimum of SOL
+    if let Some(min_sol_balance) = voting_account.min_sol_balance {
+        if voter.lamports() < min_sol_balance {
+            return Err(ErrorCode::InsufficientBalance.into());
+        }
+    }
+
+    Ok(())
+}
+
+// Check that a ranked ballot lists valid, distinct candidates
+// Ballots may rank only some of the candidates, but must rank at least one
+fn is_valid_ranking(rankings: &[u32], candidate_count: usize) -> bool {
+    !rankings.is_empty()
+        && rankings.iter().all(|index| (*index as usize) < candidate_count)
+        && rankings
+            .iter()
+            .enumerate()
+            .all(|(position, index)| !rankings[..position].contains(index))
+}
+
+// Result of one instant-runoff round
+#[derive(Debug, PartialEq, Eq)]
+enum IrvOutcome {
+    // A candidate holds a majority of the ballots still in play (or is the last one left)
+    Winner(u32),
+
+    // No majority yet; this candidate had the fewest first preferences and is eliminated
+    Eliminate(u32),
+}
+
+// Run one instant-runoff round over ranked ballots
+// Each ballot counts for its highest-ranked candidate that has not been eliminated;
+// ballots whose candidates are all eliminated are exhausted and no longer count.
+// The lowest count is eliminated, breaking ties by eliminating the higher index
+fn irv_round(
+    ballots: &[Vec<u32>],
+    candidate_count: usize,
+    eliminated: &[u32],
+) -> Result<IrvOutcome> {
+    let remaining: Vec<u32> = (0..candidate_count as u32)
+        .filter(|index| !eliminated.contains(index))
+        .collect();
+    match remaining.as_slice() {
+        [] => return Err(ErrorCode::NotEnoughCandidates.into()),
+        [last] => return Ok(IrvOutcome::Winner(*last)),
+        _ => {}
+    }
+
+    // Count each ballot's top remaining preference
+    let mut counts = vec![0u64; candidate_count];
+    let mut in_play = 0u64;
+    for ballot in ballots {
+        if let Some(choice) = ballot.iter().find(|index| !eliminated.contains(index)) {
+            counts[*choice as usize] += 1;
+            in_play += 1;
+        }
+    }
+    if in_play == 0 {
+        return Err(ErrorCode::NoRankedBallots.into());
+    }
+
+    // A strict majority of the ballots in play wins
+    if let Some(winner) = remaining.iter().find(|index| counts[**index as usize] * 2 > in_play) {
+        return Ok(IrvOutcome::Winner(*winner));
+    }
+
+    // Otherwise eliminate the weakest remaining candidate
+    let lowest = remaining
+        .iter()
+        .rev()
+        .min_by_key(|index| counts[**index as usize])
+        .copied()
+        .ok_or(ErrorCode::NotEnoughCandidates)?;
+    Ok(IrvOutcome::Eliminate(lowest))
+}
+
+// Count a validated vote of the given weight and mark the user as having voted
+// - the vote is logged as a VOTE| structured log line
+// - votes and total_votes grow by the weight (checked, so saturation fails instead of wrapping)
+// - the time the candidate reached their new count is remembered for EarliestToReach ties
+// - the user is marked as having voted (see mark_voted)
+fn record_vote(
+    election: &Pubkey,
+    voting_account: &mut VotingAccount,
+    user_account: &mut UserAccount,
+    voter: &Pubkey,
+    candidate_index: u32,
+    weight: u64,
+) -> Result<()> {
+    let count = &mut voting_account.votes[candidate_index as usize];
+    *count = add_votes(*count, weight)?;
+    voting_account.total_votes = voting_account
+        .total_votes
+        .checked_add(weight)
+        .ok_or(ErrorCode::Overflow)?;
+    let clock = Clock::get()?;
+    let reached_max_at = &mut voting_account.reached_max_at[candidate_index as usize];
+    user_account.last_vote_prev_reached_max_at = *reached_max_at;
+    *reached_max_at = clock.unix_timestamp;
+    msg!("VOTE|{}|{}|{}", voter, candidate_index, clock.slot);
+
+    // Remember what was counted so undo_vote can take it back
+    user_account.last_vote_ts = clock.unix_timestamp;
+    user_account.last_vote_weight = weight;
+    user_account.last_vote_candidate_id = voting_account.candidate_ids[candidate_index as usize];
+    user_account.last_vote_blinded_increment = None;
+
+    mark_voted(election, voting_account, user_account, voter, Some(candidate_index))
+}
+
+// Check that vote_cpi is signed by the election's caller program for this voter
+// The delegate must be the [CPI_VOTER_SEED, election, voter] PDA of cpi_caller; the
+// Signer type on the context has already checked that it signed
+fn require_cpi_delegate(
+    voting_account: &VotingAccount,
+    election: &Pubkey,
+    voter: &Pubkey,
+    delegate: &Pubkey,
+    bump: u8,
+) -> Result<()> {
+    // Only elections that name a caller program accept votes through CPI
+    let caller = voting_account.cpi_caller.ok_or(ErrorCode::CpiNotAllowed)?;
+
+    // The delegate must be derived from the caller program for this election and voter
+    let expected = Pubkey::create_program_address(
+        &[CPI_VOTER_SEED, election.as_ref(), voter.as_ref(), &[bump]],
+        &caller,
+    )
+    .map_err(|_| ErrorCode::InvalidCpiDelegate)?;
+    if expected != *delegate {
+        return Err(ErrorCode::InvalidCpiDelegate.into());
+    }
+    Ok(())
+}
+
+// List the voter on the election's public roster (once, however often they vote)
+// Public roster elections require the roster; other elections must not pass one
+fn add_to_roster(
+    voting_account: &VotingAccount,
+    roster: Option<&mut Account<VoterRoster>>,
+    voter: &Pubkey,
+) -> Result<()> {
+    match roster {
+        Some(roster) if voting_account.public_roster => {
+            // The realloc constraint only made room for voters not listed yet
+            if !roster.voters.contains(voter) {
+                roster.voters.push(*voter);
+            }
+            Ok(())
+        }
+        Some(_) => Err(ErrorCode::RosterDisabled.into()),
+        None if voting_account.public_roster => Err(ErrorCode::RosterRequired.into()),
+        None => Ok(()),
+    }
+}
+
+// Bytes a roster has to grow by to list the voter: one pubkey, unless they're already on it
+fn roster_growth(roster: &VoterRoster, voter: &Pubkey) -> usize {
+    if roster.voters.contains(voter) {
+        0
+    } else {
+        32
+    }
+}
+
+// Mark the user as having taken part, with their choice (None when abstaining)
+// - voter_count grows by one the first time the user is marked as having voted
+// - the user account is bound to the election, voter and round it voted in
+// - the slot is remembered for rate-limited elections
+fn mark_voted(
+    election: &Pubkey,
+    voting_account: &mut VotingAccount,
+    user_account: &mut UserAccount,
+    voter: &Pubkey,
+    voted_for: Option<u32>,
+) -> Result<()> {
+    // Turnout counts people, not weight
+    if !user_account.has_voted {
+        voting_account.voter_count = voting_account
+            .voter_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+
+    // Mark the user as having voted and remember their choice
+    user_account.has_voted = true;
+    user_account.voted_for = voted_for;
+    user_account.election = *election;
+    user_account.voter = *voter;
+    user_account.round = voting_account.round;
+    user_account.last_vote_slot = Clock::get()?.slot;
+    Ok(())
+}
+
+// Check that an election may move from one status to another
+// Valid moves:
+// - Draft -> Active (open_voting)
+// - Active -> Paused and Paused -> Active (pause_voting / resume_voting)
+// - Draft, Active or Paused -> Ended (end_voting)
+// - Ended -> Draft (reset_voting, starting a new round)
+// - Ended -> Active (reopen_voting, only within the election's reopen window)
+// - Any status except Cancelled -> Cancelled (emergency_cancel)
+// Everything else, such as Paused -> Draft or leaving Cancelled, fails with InvalidTransition
+fn check_transition(from: VotingStatus, to: VotingStatus) -> Result<()> {
+    use VotingStatus::*;
+    match (from, to) {
+        (Draft, Active) | (Active, Paused) | (Paused, Active) | (Ended, Active) => Ok(()),
+        (Draft | Active | Paused, Ended) => Ok(()),
+        (Ended, Draft) => Ok(()),
+        (Draft | Active | Paused | Ended, Cancelled) => Ok(()),
+        _ => Err(ErrorCode::InvalidTransition.into()),
+    }
+}
+
+// Move an election to a new status if the state machine allows it
+fn transition(voting_account: &mut VotingAccount, to: VotingStatus) -> Result<()> {
+    check_transition(voting_account.status, to)?;
+    voting_account.status = to;
+    Ok(())
+}
+
+// Check that order contains every index in 0..len exactly once
+fn is_permutation(order: &[u32], len: usize) -> bool {
+    if order.len() != len {
+        return false;
+    }
+    let mut seen = vec![false; len];
+    for &index in order {
+        match seen.get_mut(index as usize) {
+            Some(slot) if !*slot => *slot = true,
+            _ => return false,
+        }
+    }
+    true
+}
+
+// Rearrange items so that position i holds items[order[i]]
+// order must already be a valid permutation (see is_permutation)
+fn permute<T: Clone>(items: &[T], order: &[u32]) -> Vec<T> {
+    order.iter().map(|&index| items[index as usize].clone()).collect()
+}
+
+// Add votes to a candidate's running count, failing on u64 overflow
+fn add_votes(count: u64, amount: u64) -> Result<u64> {
+    Ok(count.checked_add(amount).ok_or(ErrorCode::Overflow)?)
+}
+
+// Apply the election's per-voter cap to a raw token weight
+// Clamp mode reduces the weight to the cap, Reject mode fails with WeightExceedsCap
+fn apply_weight_cap(weight: u64, cap: Option<u64>, mode: WeightCapMode) -> Result<u64> {
+    match cap {
+        Some(cap) if weight > cap => match mode {
+            WeightCapMode::Clamp => Ok(cap),
+            WeightCapMode::Reject => Err(ErrorCode::WeightExceedsCap.into()),
+        },
+        _ => Ok(weight),
+    }
+}
+
+// Check that the signer of an authority-gated instruction acts for the election authority
+// - If the signer is the authority itself, the check passes
+// - Otherwise the authority must be a Governance account passed as the first remaining
+//   account, followed by the co-signing accounts; the signer plus every co-signer that is
+//   listed in the governance account counts once towards its threshold
+fn require_authority(
+    voting_account: &VotingAccount,
+    authority: &Signer,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    // A single-key authority signs directly
+    if authority.key() == voting_account.authority {
+        return Ok(());
+    }
+
+    // Otherwise the governance account must be supplied
+    let governance_info = remaining_accounts.first().ok_or(ErrorCode::Unauthorized)?;
+    if governance_info.key() != voting_account.authority || governance_info.owner != &crate::ID {
+        return Err(ErrorCode::Unauthorized.into());
+    }
+    let governance = Governance::try_deserialize(&mut &governance_info.try_borrow_data()?[..])?;
+
+    // Count each listed signer that signed this transaction exactly once
+    let mut approvals: Vec<Pubkey> = Vec::new();
+    let co_signers = remaining_accounts[1..]
+        .iter()
+        .filter(|info| info.is_signer)
+        .map(|info| info.key());
+    for key in std::iter::once(authority.key()).chain(co_signers) {
+        if governance.signers.contains(&key) && !approvals.contains(&key) {
+            approvals.push(key);
+        }
+    }
+
+    if approvals.len() < governance.threshold as usize {
+        return Err(ErrorCode::ThresholdNotMet.into());
+    }
+    Ok(())
+}
+
+// Grow an account the program decodes itself to new_len bytes, the payer topping up its
+// rent (used by the migrations, which can't rely on Anchor's realloc constraint)
+fn grow_account<'info>(
+    account_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let growth = new_len.saturating_sub(account_info.data_len());
+    if !has_rent_for_growth(account_info, payer, growth)? {
+        return Err(ErrorCode::InsufficientRent.into());
+    }
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account_info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account_info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account_info.realloc(new_len, true)?;
+    Ok(())
+}
+
+// Whether the payer can cover the extra rent an account needs to grow by `growth` bytes
+// Checked before a realloc, which would otherwise fail inside the system transfer with
+// an opaque error; logs the shortfall when it can't
+fn has_rent_for_growth(account: &AccountInfo, payer: &AccountInfo, growth: usize) -> Result<bool> {
+    let required = Rent::get()?.minimum_balance(account.data_len() + growth);
+    let delta = required.saturating_sub(account.lamports());
+    if payer.lamports() < delta {
+        msg!(
+            "Payer holds {} lamports but growing the account by {} bytes needs {} lamports of rent",
+            payer.lamports(),
+            growth,
+            delta
+        );
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+// Decode a voting account written with the original or the current layout
+// The original is recognised by its size; anything else must deserialize as the current
+// layout and carry the current version. Intermediate layouts changed more than the
+// fields at the end (votes widened from u32 to u64, has_ended became status), so
+// reading one as a zero-padded prefix would misplace fields; they fail with
+// UnsupportedLayout instead
+fn decode_legacy_voting_account(data: &[u8]) -> Result<VotingAccount> {
+    if data.len() == BASELINE_VOTING_ACCOUNT_SPACE {
+        return decode_baseline_voting_account(data);
+    }
+    let discriminator = <VotingAccount as anchor_lang::Discriminator>::DISCRIMINATOR;
+    if data.len() < 8 || data[..8] != discriminator {
+        return Err(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let voting_account = VotingAccount::try_deserialize_unchecked(&mut &data[..])
+        .map_err(|_| ErrorCode::UnsupportedLayout)?;
+    if voting_account.version != VOTING_ACCOUNT_VERSION {
+        msg!(
+            "Version {} is neither the original nor the current layout ({})",
+            voting_account.version,
+            VOTING_ACCOUNT_VERSION
+        );
+        return Err(ErrorCode::UnsupportedLayout.into());
+    }
+    Ok(voting_account)
+}
+
+// The original deployed layout of VotingAccount, which shares only its candidates with
+// the later layouts: votes were u32 and has_ended stood in for status
+#[derive(AnchorDeserialize)]
+struct BaselineVotingAccount {
+    candidates: Vec<String>,
+    votes: Vec<u32>,
+    has_ended: bool,
+}
+
+// Decode a voting account written with the original deployed layout
+// Every field the baseline lacks starts zeroed (and version as 0), the counts widen to
+// u64 and has_ended maps to Ended or Active
+fn decode_baseline_voting_account(data: &[u8]) -> Result<VotingAccount> {
+    let discriminator = <VotingAccount as anchor_lang::Discriminator>::DISCRIMINATOR;
+    if data.len() < 8 || data[..8] != discriminator {
+        return Err(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+    let baseline = BaselineVotingAccount::deserialize(&mut &data[8..])
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+
+    let mut padded = discriminator.to_vec();
+    padded.resize(8 + VotingAccount::SPACE, 0);
+    let mut voting_account = VotingAccount::try_deserialize(&mut &padded[..])?;
+    voting_account.candidates = baseline.candidates;
+    voting_account.votes = baseline.votes.into_iter().map(u64::from).collect();
+    voting_account.status = if baseline.has_ended {
+        VotingStatus::Ended
+    } else {
+        VotingStatus::Active
+    };
+    voting_account.winner_index = NO_WINNER;
+    Ok(voting_account)
+}
+
+// Decode a user account written with the current or an older layout, such as the
+// original 8 + 1 byte account holding only has_voted
+// As with voting accounts the missing fields read as zero, which is their initial value:
+// voted_for None, round 0, no election or voter bound yet, no eligibility restriction
+fn decode_legacy_user_account(data: &[u8]) -> Result<UserAccount> {
+    let mut padded = data.to_vec();
+    padded.resize(data.len() + UserAccount::INIT_SPACE, 0);
+    UserAccount::try_deserialize(&mut &padded[..])
+}
+
+// Give the fields a migrated account lacked the values init_voting_account would set
+// Zero is already the right start for counters and flags; the per-candidate vectors
+// must match the ballot, and total_votes is recounted when it was missing
+fn fill_migrated_defaults(voting_account: &mut VotingAccount) {
+    let candidate_count = voting_account.candidates.len();
+    if voting_account.display_names.len() != candidate_count {
+        voting_account.display_names = voting_account.candidates.clone();
+    }
+    if voting_account.disabled.len() != candidate_count {
+        voting_account.disabled = vec![false; candidate_count];
+    }
+    if voting_account.encrypted_tally.len() != candidate_count {
+        voting_account.encrypted_tally = vec![[0u8; 32]; candidate_count];
+    }
+    if voting_account.vetoes.len() != candidate_count {
+        voting_account.vetoes = vec![0; candidate_count];
+    }
+    if voting_account.reached_max_at.len() != candidate_count {
+        voting_account.reached_max_at = vec![0; candidate_count];
+    }
+    if voting_account.disqualified.len() != candidate_count {
+        voting_account.disqualified = vec![false; candidate_count];
+    }
+    voting_account.is_initialized = true;
+    if voting_account.candidate_ids.len() != candidate_count {
+        voting_account.candidate_ids = (0..candidate_count as u64).collect();
+        voting_account.next_candidate_id = candidate_count as u64;
+    }
+    if voting_account.total_votes == 0 {
+        voting_account.total_votes = voting_account
+            .votes
+            .iter()
+            .fold(0u64, |total, votes| total.saturating_add(*votes));
+    }
+    if voting_account.configured_end_time == 0 && !voting_account.has_ended() {
+        voting_account.configured_end_time = voting_account.end_time;
+    }
+    voting_account.version = VOTING_ACCOUNT_VERSION;
+}
+
+// Check that the signer is this program's upgrade authority
+// program_data must be the ProgramData account the upgradeable loader derives for
+// this program, so a look-alike account can't name its own upgrade authority
+fn require_upgrade_authority(
+    program_data: Option<&Account<ProgramData>>,
+    signer: &Pubkey,
+) -> Result<()> {
+    let program_data = program_data.ok_or(ErrorCode::Unauthorized)?;
+    let (expected, _) = Pubkey::find_program_address(
+        &[crate::ID.as_ref()],
+        &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+    );
+    if program_data.key() != expected || program_data.upgrade_authority_address != Some(*signer) {
+        return Err(ErrorCode::Unauthorized.into());
+    }
+    Ok(())
+}
+
+// Check that the signer is one of the election's admins or acts for its authority
+// Used for operational actions that admins may take without the owner
+fn require_admin_or_authority(
+    voting_account: &VotingAccount,
+    authority: &Signer,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    if voting_account.admins.contains(authority.key) {
+        return Ok(());
+    }
+    require_authority(voting_account, authority, remaining_accounts)
+}
+
+// Transfer the election's vote fee from the voter to the authority
+// Fails with InsufficientFundsForFee when the voter cannot afford it
+fn collect_vote_fee<'info>(
+    vote_fee: u64,
+    user: &Signer<'info>,
+    authority: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    // Free elections skip the transfer entirely
+    if vote_fee == 0 {
+        return Ok(());
+    }
+
+    // Report a clear error instead of a failed system transfer
+    if user.lamports() < vote_fee {
+        return Err(ErrorCode::InsufficientFundsForFee.into());
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: user.to_account_info(),
+                to: authority.to_account_info(),
+            },
+        ),
+        vote_fee,
+    )
+}
+
+// ACCOUNT CONTEXTS
+
+// Define the account context for the initialize instruction
+// This struct specifies which accounts are required and how they should be validated
+#[derive(Accounts)]
+#[instruction(candidates: Vec<String>)]
+pub struct Initialize<'info> {
+    // The voting_account is initialized in this instruction
+    // init: This account will be created in this transaction
+    // payer = user: The 'user' account will pay for the account creation
+    // space = VotingAccount::space_for(...): See the breakdown on VotingAccount
+    #[account(init, payer = user, space = VotingAccount::space_for(candidates.len()))]
+    pub voting_account: Account<'info, VotingAccount>,
+    
+    // The user account must be mutable as it will pay for the transaction
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for initializing a user
+#[derive(Accounts)]
+pub struct InitializeUser<'info> {
+    // The user_account is initialized in this instruction
+    // space = 8 + UserAccount::INIT_SPACE: the 8 byte account discriminator plus the
+    // size InitSpace derives from the UserAccount fields
+    #[account(init, payer = user, space = 8 + UserAccount::INIT_SPACE)]
+    pub user_account: Account<'info, UserAccount>,
+    
+    // The user account must be mutable as it will pay for the transaction
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for initializing a user account as a PDA
+#[derive(Accounts)]
+pub struct InitializeUserAccount<'info> {
+    // The election the user registers for, mutable as it counts its registered voters
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The user_account is initialized as a PDA (Program Derived Address)
+    // seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()]: The PDA is derived from:
+    //   - The string "user"
+    //   - The election's public key
+    //   - The user's public key
+    // bump: Automatically adds the bump seed for the PDA
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UserAccount::INIT_SPACE,
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    // The user account must be mutable as it will pay for the transaction
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The election authority (or a governance co-signer), required in AuthorityOnly elections
+    // Further governance co-signers are passed as remaining accounts
+    pub authority: Option<Signer<'info>>,
+    
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+
+    // The voter's token account for the vote mint, whose balance is snapshotted as the
+    // voter's weight in weighted elections (ignored otherwise)
+    pub token_account: Option<Account<'info, TokenAccount>>,
+}
+
+// Define the account context for the register_with_authorization instruction
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+#[instruction(voter: Pubkey)]
+pub struct RegisterWithAuthorization<'info> {
+    // The election the voter registers for, mutable as it counts its registered voters
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The voter's user account PDA, the same one initialize_user_account would create
+    #[account(
+        init,
+        payer = sponsor,
+        space = 8 + UserAccount::INIT_SPACE,
+        seeds = [b"user", voting_account.key().as_ref(), voter.as_ref()],
+        bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Pays for the user account on the voter's behalf
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    // The election authority (or a governance co-signer), required in AuthorityOnly elections
+    pub authority: Option<Signer<'info>>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+
+    // The voter's token account for the vote mint, whose balance is snapshotted as the
+    // voter's weight in weighted elections (ignored otherwise)
+    pub token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: The instructions sysvar, read to find the ed25519 signature check
+    #[account(address = instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+// Define the account context for the undo_vote instruction
+#[derive(Accounts)]
+pub struct UndoVote<'info> {
+    // The voting account must be mutable as we'll take the vote back off the counts
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The voter's user account PDA, which may vote again afterwards
+    #[account(
+        mut,
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Only the voter can undo their vote
+    pub user: Signer<'info>,
+}
+
+// Define the account context for the vote instruction
+#[derive(Accounts)]
+pub struct Vote<'info> {
+    // The voting account must be mutable as we'll update vote counts
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+    
+    // The user account must be mutable as we'll mark it as having voted
+    // Only the PDA created by initialize_user_account for this election and voter is accepted
+    #[account(
+        mut,
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+    
+    // The user must sign the transaction to vote
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Only receives the vote fee; the address must match the election authority
+    #[account(mut, address = voting_account.authority @ ErrorCode::Unauthorized)]
+    pub authority: UncheckedAccount<'info>,
+
+    // The system program is required to transfer the vote fee and grow the roster
+    pub system_program: Program<'info, System>,
+
+    // The election's public roster, required when public_roster is set (None otherwise)
+    // It grows by one pubkey, paid for by the voter, the first time they are listed
+    #[account(
+        mut,
+        seeds = [b"roster", voting_account.key().as_ref()],
+        bump = roster.bump,
+        realloc = roster.to_account_info().data_len() + roster_growth(roster, &user.key()),
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub roster: Option<Account<'info, VoterRoster>>,
+}
+
+// Define the account context for the vote_with_reason instruction
+// The same accounts as Vote, plus the voter's receipt
+#[derive(Accounts)]
+pub struct VoteWithReason<'info> {
+    // The voting account must be mutable as we'll update vote counts
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The user account must be mutable as we'll mark it as having voted
+    #[account(
+        mut,
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The receipt holding the voter's choice and reason, paid for by the voter
+    // Created on the first vote and overwritten if the voter votes again (after an undo
+    // or a reset)
+    // space = 8 + VoteReceipt::INIT_SPACE: discriminator, election and voter pubkeys,
+    // candidate ID, reason of up to MAX_REASON_LEN bytes and bump
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + VoteReceipt::INIT_SPACE,
+        seeds = [b"receipt", voting_account.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub vote_receipt: Account<'info, VoteReceipt>,
+
+    // The user must sign the transaction to vote
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Only receives the vote fee; the address must match the election authority
+    #[account(mut, address = voting_account.authority @ ErrorCode::Unauthorized)]
+    pub authority: UncheckedAccount<'info>,
+
+    // The system program is required to create the receipt, transfer the vote fee and
+    // grow the roster
+    pub system_program: Program<'info, System>,
+
+    // The election's public roster, required when public_roster is set (None otherwise)
+    #[account(
+        mut,
+        seeds = [b"roster", voting_account.key().as_ref()],
+        bump = roster.bump,
+        realloc = roster.to_account_info().data_len() + roster_growth(roster, &user.key()),
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub roster: Option<Account<'info, VoterRoster>>,
+}
+
+// Define the account context for the vote_cpi instruction
+#[derive(Accounts)]
+pub struct VoteCpi<'info> {
+    // The voting account must be mutable as we'll update vote counts
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The voter's user account PDA, marked as having voted
+    #[account(
+        mut,
+        seeds = [b"user", voting_account.key().as_ref(), voter.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: The voter the vote is cast for; they don't sign, the caller program's
+    /// delegate PDA vouches for them (see require_cpi_delegate)
+    pub voter: UncheckedAccount<'info>,
+
+    // The caller program's delegate PDA for this election and voter
+    pub delegate: Signer<'info>,
+
+    // Pays the vote fee and any roster growth on the voter's behalf
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Only receives the vote fee; the address must match the election authority
+    #[account(mut, address = voting_account.authority @ ErrorCode::Unauthorized)]
+    pub authority: UncheckedAccount<'info>,
+
+    // The system program is required to transfer the vote fee and grow the roster
+    pub system_program: Program<'info, System>,
+
+    // The election's public roster, required when public_roster is set (None otherwise)
+    #[account(
+        mut,
+        seeds = [b"roster", voting_account.key().as_ref()],
+        bump = roster.bump,
+        realloc = roster.to_account_info().data_len() + roster_growth(roster, &voter.key()),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub roster: Option<Account<'info, VoterRoster>>,
+}
+
+// Define the account context for the simulate_vote instruction
+// Nothing is mutable: the simulation only reads the accounts vote would change
+#[derive(Accounts)]
+pub struct SimulateVote<'info> {
+    // The election the vote would be cast in
+    #[account(constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The voter's user account PDA, as vote requires
+    #[account(
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The voter, signing as they would for vote
+    pub user: Signer<'info>,
+}
+
+// Define the account context for the vote_weighted instruction
+#[derive(Accounts)]
+pub struct VoteWeighted<'info> {
+    // The voting account must be mutable as we'll update vote counts
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The user account must be mutable as we'll mark it as having voted
+    // Only the PDA created by initialize_user_account for this election and voter is accepted
+    #[account(
+        mut,
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The user must sign the transaction to vote
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Only receives the vote fee; the address must match the election authority
+    #[account(mut, address = voting_account.authority @ ErrorCode::Unauthorized)]
+    pub authority: UncheckedAccount<'info>,
+
+    // The system program is required to transfer the vote fee
+    pub system_program: Program<'info, System>,
+
+    // The voter's token account, whose recorded balance applies in snapshot-slot elections
+    // It must belong to the voter and hold the election's vote mint
+    #[account(
+        constraint = token_account.owner == user.key() @ ErrorCode::InvalidTokenAccount,
+        constraint = Some(token_account.mint) == voting_account.vote_mint @ ErrorCode::InvalidTokenAccount,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+
+    // The recorded balance of token_account, required when the election has a snapshot slot
+    #[account(
+        seeds = [b"snapshot", voting_account.key().as_ref(), token_account.key().as_ref()],
+        bump,
+    )]
+    pub token_snapshot: Option<Account<'info, TokenSnapshot>>,
+
+    // The election's public roster, required when public_roster is set (None otherwise)
+    // It grows by one pubkey, paid for by the voter, the first time they are listed
+    #[account(
+        mut,
+        seeds = [b"roster", voting_account.key().as_ref()],
+        bump = roster.bump,
+        realloc = roster.to_account_info().data_len() + roster_growth(roster, &user.key()),
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub roster: Option<Account<'info, VoterRoster>>,
+}
+
+// Define the account context for ending the voting
+#[derive(Accounts)]
+pub struct EndVoting<'info> {
+    // The voting account must be mutable as we'll update its state
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The election authority, or a co-signer of its governance account
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for changing a registry's settings
+#[derive(Accounts)]
+pub struct ConfigureRegistry<'info> {
+    // The authority's registry
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, AuthorityRegistry>,
+
+    // Only the registry's authority can change it
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for the release_election instruction
+#[derive(Accounts)]
+pub struct ReleaseElection<'info> {
+    // The registry-tracked election being released
+    // The seeds and stored bump ensure it really is one of this registry's elections
+    #[account(
+        mut,
+        seeds = [
+            b"election",
+            voting_account.authority.as_ref(),
+            voting_account.election_id.to_le_bytes().as_ref(),
+        ],
+        bump = voting_account.bump,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The election authority's registry, whose active count is decremented
+    #[account(
+        mut,
+        seeds = [b"registry", voting_account.authority.as_ref()],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, AuthorityRegistry>,
+}
+
+// Define the account context for closing the voting account
+#[derive(Accounts)]
+pub struct CloseVoting<'info> {
+    // The voting account is closed and its lamports returned to the authority
+    // has_one = authority: Only the election authority may close it
+    #[account(
+        mut,
+        has_one = authority,
+        close = authority,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The authority signs and receives the reclaimed rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for recording a token balance snapshot
+#[derive(Accounts)]
+pub struct RecordSnapshot<'info> {
+    // The election the snapshot is recorded for
+    #[account(constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The token account whose balance is recorded
+    // It must belong to the signer and hold the election's vote mint
+    #[account(
+        constraint = token_account.owner == user.key() @ ErrorCode::InvalidTokenAccount,
+        constraint = Some(token_account.mint) == voting_account.vote_mint @ ErrorCode::InvalidTokenAccount,
+    )]
+    pub token_account: Account<'info, TokenAccount>,
+
+    // The snapshot is a PDA derived from the election and the token account,
+    // so each token account can only be recorded once per election
+    // space = 8 + TokenSnapshot::INIT_SPACE: discriminator, token account pubkey,
+    // amount and slot
+    #[account(
+        init,
+        payer = user,
+        space = 8 + TokenSnapshot::INIT_SPACE,
+        seeds = [b"snapshot", voting_account.key().as_ref(), token_account.key().as_ref()],
+        bump,
+    )]
+    pub token_snapshot: Account<'info, TokenSnapshot>,
+
+    // The token account owner pays for the snapshot account
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for creating a governance account
+#[derive(Accounts)]
+#[instruction(signers: Vec<Pubkey>)]
+pub struct CreateGovernance<'info> {
+    // The governance account is initialized in this instruction
+    // space = 8 + 4 + (32 * signers) + 1: discriminator, signer list and threshold
+    #[account(init, payer = payer, space = 8 + 4 + (32 * signers.len()) + 1)]
+    pub governance: Account<'info, Governance>,
+
+    // The payer for the account creation
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context shared by simple authority-gated instructions
+// (transfer_authority, pause_voting, resume_voting)
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct AuthorityAction<'info> {
+    // The voting account must be mutable as we'll update its state
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The election authority, or a co-signer of its governance account
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for the snapshot_supply instruction
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct SnapshotSupply<'info> {
+    // The election the supply is recorded for
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The election's vote mint
+    #[account(
+        constraint = Some(vote_mint.key()) == voting_account.vote_mint @ ErrorCode::InvalidTokenAccount,
+    )]
+    pub vote_mint: Account<'info, Mint>,
+
+    // The election authority, or a co-signer of its governance account
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for re-enabling a user account after a reset
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct ResetUser<'info> {
+    // The election whose round the user account is moved to (read-only)
+    #[account(constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The user account being reset
+    #[account(mut)]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The voter who used the account, or the election authority
+    pub signer: Signer<'info>,
+}
+
+// Define the account context for closing a user account
+#[derive(Accounts)]
+pub struct CloseUserAccount<'info> {
+    // The election the user account was registered for (read-only)
+    #[account(constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The user account PDA being closed; its rent goes back to the voter
+    // The seeds tie it to the signing voter, so nobody can close someone else's account
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The voter who owns the account receives the refund
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+// Define the account context for restricting the candidates a voter may choose
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct SetEligibleCandidates<'info> {
+    // The election the user account is registered for (read-only)
+    #[account(constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The user account whose allow-list is set
+    #[account(mut)]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The election authority, or a co-signer of its governance account
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for the check_eligibility instruction
+// The accounts vote checks, read-only; the voter doesn't need to sign
+#[derive(Accounts)]
+pub struct CheckEligibility<'info> {
+    // The election the voter would vote in
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The voter's user account PDA for this election
+    #[account(
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    /// CHECK: The voter being checked; only its key and balance are read
+    pub user: UncheckedAccount<'info>,
+}
+
+// Define the account context shared by read-only queries of an election
+#[derive(Accounts)]
+pub struct ReadVoting<'info> {
+    // The election being queried (read-only)
+    pub voting_account: Account<'info, VotingAccount>,
+}
+
+// Define the account context for creating an authority's election registry
+#[derive(Accounts)]
+pub struct InitializeRegistry<'info> {
+    // The registry is a PDA derived from the authority, one per authority
+    // space = 8 + AuthorityRegistry::INIT_SPACE: discriminator, authority,
+    // election_count, max_elections, next_election_id, bump, active_elections and
+    // max_active_elections
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AuthorityRegistry::INIT_SPACE,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump,
+    )]
+    pub registry: Account<'info, AuthorityRegistry>,
+
+    // The authority owns the registry and pays for it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for creating a registry-tracked election
+#[derive(Accounts)]
+#[instruction(candidates: Vec<String>)]
+pub struct CreateElection<'info> {
+    // The authority's registry, which counts and numbers its elections
+    // bump = registry.bump: Reuse the canonical bump stored at initialization
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, AuthorityRegistry>,
+
+    // The election is a PDA derived from the authority and the registry's next id
+    #[account(
+        init,
+        payer = authority,
+        space = VotingAccount::space_for(candidates.len()),
+        seeds = [
+            b"election",
+            authority.key().as_ref(),
+            registry.next_election_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The authority creates the election and pays for it
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for closing a registry-tracked election
+#[derive(Accounts)]
+pub struct CloseElection<'info> {
+    // The voting account is closed and its lamports returned to the authority
+    // The seeds and stored bump ensure it really is one of this registry's elections
+    #[account(
+        mut,
+        seeds = [
+            b"election",
+            authority.key().as_ref(),
+            voting_account.election_id.to_le_bytes().as_ref(),
+        ],
+        bump = voting_account.bump,
+        has_one = authority,
+        close = authority,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The authority's registry, whose count is decremented
+    #[account(
+        mut,
+        seeds = [b"registry", authority.key().as_ref()],
+        bump = registry.bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, AuthorityRegistry>,
+
+    // The authority signs and receives the reclaimed rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for creating a runoff election
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct CreateRunoff<'info> {
+    // The ended election whose top two candidates go to the runoff (read-only)
+    #[account(constraint = !parent_election.frozen @ ErrorCode::Frozen)]
+    pub parent_election: Account<'info, VotingAccount>,
+
+    // The runoff is a PDA derived from the parent, one per election, sized for two finalists
+    #[account(
+        init,
+        payer = authority,
+        space = VotingAccount::space_for(2),
+        seeds = [b"runoff", parent_election.key().as_ref()],
+        bump,
+    )]
+    pub runoff: Account<'info, VotingAccount>,
+
+    // The parent's authority (or a governance co-signer), paying for the runoff
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for casting a ranked-choice ballot
+#[derive(Accounts)]
+#[instruction(rankings: Vec<u32>)]
+pub struct CastRankedBallot<'info> {
+    // The election being voted in, which counts its ranked ballots
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The ballot is a PDA derived from the election and the voter, one per voter
+    // space = 8 + 32 + 32 + 4 + (4 * rankings): discriminator, election, voter and rankings
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 32 + 4 + (4 * rankings.len()),
+        seeds = [b"ranked", voting_account.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub ranked_ballot: Account<'info, RankedBallot>,
+
+    // The voter's registration for the election
+    // Only the PDA created by initialize_user_account for this election and voter is accepted
+    #[account(
+        seeds = [b"user", voting_account.key().as_ref(), user.key().as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // The voter pays for their ballot account
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for running an instant-runoff round
+// The election's RankedBallot accounts are passed as remaining accounts
+#[derive(Accounts)]
+pub struct TallyRanked<'info> {
+    // The election whose eliminations and ranked winner are updated
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+}
+
+// Define the account context for appending candidates
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+#[instruction(names: Vec<String>)]
+pub struct AddCandidates<'info> {
+    // The election authority (or a governance co-signer), paying for the extra space
+    // Listed first so its rent check runs before voting_account is reallocated
+    #[account(
+        mut,
+        constraint = has_rent_for_growth(
+            &voting_account.to_account_info(),
+            &authority.to_account_info(),
+            candidates_growth(voting_account.reserved_capacity, &names),
+        )? @ ErrorCode::InsufficientRent,
+    )]
+    pub authority: Signer<'info>,
+
+    // The voting account grows once by the space the new candidates need beyond any
+    // reserved slots
+    #[account(
+        mut,
+        realloc = voting_account.to_account_info().data_len()
+            + candidates_growth(voting_account.reserved_capacity, &names),
+        realloc::payer = authority,
+        realloc::zero = false,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for reserving candidate slots
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+#[instruction(additional: u16)]
+pub struct ExpandCapacity<'info> {
+    // The election authority (or a governance co-signer), paying for the extra space
+    // Listed first so its rent check runs before voting_account is reallocated
+    #[account(
+        mut,
+        constraint = has_rent_for_growth(
+            &voting_account.to_account_info(),
+            &authority.to_account_info(),
+            additional as usize * CANDIDATE_SLOT_SPACE,
+        )? @ ErrorCode::InsufficientRent,
+    )]
+    pub authority: Signer<'info>,
+
+    // The voting account grows by one CANDIDATE_SLOT_SPACE per reserved slot
+    #[account(
+        mut,
+        realloc = voting_account.to_account_info().data_len()
+            + additional as usize * CANDIDATE_SLOT_SPACE,
+        realloc::payer = authority,
+        realloc::zero = false,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for the add_ballot instruction
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+#[instruction(question: String, options: Vec<String>)]
+pub struct AddBallot<'info> {
+    // The election authority (or a governance co-signer), paying for the extra space
+    // Listed first so its rent check runs before voting_account is reallocated
+    #[account(
+        mut,
+        constraint = has_rent_for_growth(
+            &voting_account.to_account_info(),
+            &authority.to_account_info(),
+            Ballot::space(&question, &options),
+        )? @ ErrorCode::InsufficientRent,
+    )]
+    pub authority: Signer<'info>,
+
+    // The voting account grows by the size of the new ballot
+    #[account(
+        mut,
+        realloc = voting_account.to_account_info().data_len() + Ballot::space(&question, &options),
+        realloc::payer = authority,
+        realloc::zero = false,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for renaming a candidate
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+#[instruction(candidate_index: u32, new_name: String)]
+pub struct RenameCandidate<'info> {
+    // The election authority (or a governance co-signer), paying for any extra space
+    // Listed first so its rent check runs before voting_account is reallocated
+    #[account(
+        mut,
+        constraint = has_rent_for_growth(
+            &voting_account.to_account_info(),
+            &authority.to_account_info(),
+            rename_growth(&voting_account, candidate_index, &new_name),
+        )? @ ErrorCode::InsufficientRent,
+    )]
+    pub authority: Signer<'info>,
+
+    // The voting account grows by however much longer the new name is
+    #[account(
+        mut,
+        realloc = voting_account.to_account_info().data_len()
+            + rename_growth(&voting_account, candidate_index, &new_name),
+        realloc::payer = authority,
+        realloc::zero = false,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for adding a hashed voter to the whitelist
+#[derive(Accounts)]
+pub struct AddEligibleHash<'info> {
+    // The election authority (or a governance co-signer), paying for the extra space
+    // Listed first so its rent check runs before voting_account is reallocated
+    #[account(
+        mut,
+        constraint = has_rent_for_growth(
+            &voting_account.to_account_info(),
+            &authority.to_account_info(),
+            32,
+        )? @ ErrorCode::InsufficientRent,
+    )]
+    pub authority: Signer<'info>,
+
+    // The voting account grows by one 32 byte hash
+    #[account(
+        mut,
+        realloc = voting_account.to_account_info().data_len() + 32,
+        realloc::payer = authority,
+        realloc::zero = false,
+        constraint = !voting_account.frozen @ ErrorCode::Frozen,
+    )]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for migrating a voting account to the current layout
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct MigrateVotingAccount<'info> {
+    /// CHECK: An older layout can't be deserialized as the current VotingAccount, so the
+    /// instruction decodes it itself; it must be owned by this program
+    #[account(mut, owner = crate::ID)]
+    pub voting_account: UncheckedAccount<'info>,
+
+    // The election authority, or a co-signer of its governance account; pays the extra rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+
+    // This program's ProgramData account, required to migrate a baseline account: its
+    // upgrade authority must be the signer
+    pub program_data: Option<Account<'info, ProgramData>>,
+}
+
+// Define the account context for finalizing an election past its deadline
+#[derive(Accounts)]
+pub struct FinalizeExpired<'info> {
+    // The election to finalize; anyone may crank it once its deadline has passed
+    #[account(mut, constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+}
+
+// Define the account context for migrating a user account to the current layout
+#[derive(Accounts)]
+pub struct MigrateUserAccount<'info> {
+    /// CHECK: An older layout can't be deserialized as the current UserAccount, so the
+    /// instruction decodes it itself; it must be owned by this program and be the
+    /// original initialize_user_account PDA of the voter
+    #[account(mut, owner = crate::ID, seeds = [b"user", voter.key().as_ref()], bump)]
+    pub user_account: UncheckedAccount<'info>,
+
+    // The voter the account belongs to, who must sign and pays the extra rent
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    // The system program is required to fund the larger account
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for creating an election's public roster
+// Governance co-signers are passed as remaining accounts
+#[derive(Accounts)]
+pub struct InitializeRoster<'info> {
+    // The election the roster lists voters for
+    #[account(constraint = !voting_account.frozen @ ErrorCode::Frozen)]
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // The roster is a PDA derived from the election, so each election has at most one
+    // It starts empty and is reallocated as voters are added
+    #[account(
+        init,
+        payer = authority,
+        space = VoterRoster::SPACE,
+        seeds = [b"roster", voting_account.key().as_ref()],
+        bump,
+    )]
+    pub roster: Account<'info, VoterRoster>,
+
+    // The election authority (or a governance co-signer), paying for the roster account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// Define the account context for querying a user's voting state
+#[derive(Accounts)]
+pub struct HasUserVoted<'info> {
+    // The user account being queried (read-only)
+    pub user_account: Account<'info, UserAccount>,
+}
+
+// Define the account context for the aggregate_to_parent instruction
+// Governance co-signers of the parent's authority are passed as remaining accounts
+#[derive(Accounts)]
+pub struct AggregateToParent<'info> {
+    // The sub-election whose votes are added (marked as aggregated)
+    // An election can't roll up into itself, and a frozen one can't be marked aggregated
+    #[account(
+        mut,
+        constraint = child.key() != parent.key() @ ErrorCode::NotSubElection,
+        constraint = !child.frozen @ ErrorCode::Frozen,
+    )]
+    pub child: Account<'info, VotingAccount>,
+
+    // The election the votes roll up into
+    #[account(mut, constraint = !parent.frozen @ ErrorCode::Frozen)]
+    pub parent: Account<'info, VotingAccount>,
+
+    // The parent's authority (or a governance co-signer)
+    pub authority: Signer<'info>,
+}
+
+// Define the account context for the checkpoint instruction
+#[derive(Accounts)]
+pub struct WriteCheckpoint<'info> {
+    // The election whose tally is checkpointed
+    pub voting_account: Account<'info, VotingAccount>,
+
+    // One checkpoint per election, created on first use and overwritten afterwards
+    // space = 8 + Checkpoint::INIT_SPACE: discriminator, election pubkey, slot,
+    // total votes, votes hash and bump
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Checkpoint::INIT_SPACE,
+        seeds = [b"checkpoint", voting_account.key().as_ref()],
+        bump,
+    )]
+    pub checkpoint: Account<'info, Checkpoint>,
+
+    // Whoever writes the first checkpoint pays for its account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // The system program is required for creating new accounts
+    pub system_program: Program<'info, System>,
+}
+
+// ACCOUNT DATA STRUCTURES
+
+// Define the structure of the voting account's data
+#[account]
+pub struct VotingAccount {
+    // List of candidate names
+    pub candidates: Vec<String>,
+    
+    // Vote counts for each candidate (parallel array to candidates)
+    // u64 so token-weighted totals don't overflow
+    pub votes: Vec<u64>,
+    
+    // Lifecycle state of the election (Draft, Active, Paused or Ended)
+    pub status: VotingStatus,
+
+    // Flag indicating the top candidates finished with the same count (set by end_voting)
+    pub is_tie: bool,
+
+    // Index of the winning candidate, or NO_WINNER if tied or not yet ended
+    pub winner_index: u32,
+
+    // Mint whose balance weights votes cast through vote_weighted (None disables it)
+    pub vote_mint: Option<Pubkey>,
+
+    // Largest weight a single wallet may contribute (None means uncapped)
+    pub max_weight_per_voter: Option<u64>,
+
+    // How weights above max_weight_per_voter are handled
+    pub weight_cap_mode: WeightCapMode,
+
+    // The account allowed to administer the election
+    pub authority: Pubkey,
+
+    // Unix timestamp at which voting ends: the deadline while open (0 for none),
+    // then the time it actually ended
+    pub end_time: i64,
+
+    // Seconds after end_time before the account may be closed
+    pub close_delay: i64,
+
+    // Last slot at which token balances may be recorded for weighted voting (0 disables snapshots)
+    pub snapshot_slot: u64,
+
+    // Lamports each vote pays to the authority (0 for free elections)
+    pub vote_fee: u64,
+
+    // Per-candidate flag marking withdrawn candidates (parallel array to candidates)
+    pub disabled: Vec<bool>,
+
+    // Minimum lamports a voter must hold to vote (None means no gate)
+    pub min_sol_balance: Option<u64>,
+
+    // Sum of all counted vote weight (equals the number of votes without weighting)
+    pub total_votes: u64,
+
+    // Number of distinct users who have voted on-chain (turnout); votes posted with
+    // submit_batch are counted in batched_votes instead
+    pub voter_count: u64,
+
+    // Number of voters who abstained (included in voter_count, not in total_votes)
+    pub abstain_count: u64,
+
+    // Experimental XOR accumulator of client-blinded increments (parallel array to candidates)
+    pub encrypted_tally: Vec<[u8; 32]>,
+
+    // Discovery tags for portals grouping elections (at most MAX_TAGS of MAX_TAG_LEN bytes)
+    pub tags: Vec<String>,
+
+    // sha256 hashes of the voters allowed to vote (empty means anyone may vote)
+    pub eligible_voter_hashes: Vec<[u8; 32]>,
+
+    // Voting round, advanced each time the election is reset
+    pub round: u64,
+
+    // Registry id used in the election PDA seeds (0 for keypair elections)
+    pub election_id: u64,
+
+    // Canonical bump of the election PDA (0 for keypair elections)
+    pub bump: u8,
+
+    // Votes the winner must lead the runner-up by (0 for any lead)
+    pub min_victory_margin: u32,
+
+    // The election this one is a runoff of, if any
+    pub parent_election: Option<Pubkey>,
+
+    // Whether a user account may only vote once per slot (useful across reset rounds)
+    pub rate_limit_votes: bool,
+
+    // Number of RankedBallot accounts cast in this election
+    pub ranked_ballot_count: u64,
+
+    // Candidates eliminated so far by tally_ranked, in elimination order
+    pub eliminated: Vec<u32>,
+
+    // Winner of the instant-runoff tally, once a majority has emerged
+    pub ranked_winner: Option<u32>,
+
+    // Whether voters register themselves or are registered by the authority
+    pub registration_mode: RegistrationMode,
+
+    // Votes the leader needs to be declared the winner (0 for no threshold)
+    pub min_winning_votes: u32,
+
+    // Whether the result getters (get_results, get_percentages, get_rankings,
+    // get_leaderboard, ...) refuse to report counts, and get_candidate reports 0, before
+    // the end
+    pub results_hidden: bool,
+
+    // Names shown to voters, parallel to candidates (which stay the canonical IDs)
+    pub display_names: Vec<String>,
+
+    // Locale of the display names, e.g. "pt-BR" (empty if unspecified)
+    pub locale: String,
+
+    // Unix timestamp from which votes are accepted
+    pub start_time: i64,
+
+    // Candidate slots reserved by expand_capacity and not yet used by add_candidates
+    pub reserved_capacity: u32,
+
+    // Vetoes cast against each candidate, parallel to votes
+    pub vetoes: Vec<u32>,
+
+    // Operational admins allowed to pause and resume voting alongside the authority
+    pub admins: Vec<Pubkey>,
+
+    // Basis points of weight a vote loses per full day after start_time (0 for no decay)
+    pub decay_rate_bps_per_day: u16,
+
+    // Stable ID of each candidate, parallel to candidates; vote refers to candidates by ID
+    pub candidate_ids: Vec<u64>,
+
+    // ID the next added candidate receives (IDs are never reused)
+    pub next_candidate_id: u64,
+
+    // Votes cast through vote in current_slot
+    pub votes_this_slot: u32,
+
+    // Slot of the most recent vote, which votes_this_slot counts for
+    pub current_slot: u64,
+
+    // Most votes vote accepts across the whole election in one slot (0 for no cap)
+    pub max_votes_per_slot: u32,
+
+    // Set for good by emergency_halt; the account contexts of all mutating instructions
+    // then reject the election with Frozen
+    pub frozen: bool,
+
+    // Votes discarded by withdraw_candidate this round, no longer part of total_votes
+    pub withdrawn_votes: u64,
+
+    // How a tie for first place is settled when voting ends (see break_tie)
+    pub tie_break: TieBreak,
+
+    // Pseudo-random seed fixed when a Seeded election ends (zero until then)
+    pub tie_break_seed: [u8; 32],
+
+    // Layout version the account was written with (0 for accounts predating the field)
+    pub version: u8,
+
+    // When each candidate's count last grew, i.e. when they first reached their current
+    // count (0 before their first vote); parallel to candidates, used by EarliestToReach
+    pub reached_max_at: Vec<i64>,
+
+    // Number of user accounts registered through initialize_user_account
+    pub registered_voters: u64,
+
+    // Share of registered voters who voted, in basis points, fixed when voting ends
+    pub turnout_bps: u16,
+
+    // Whether who voted (but not how) is listed on the election's VoterRoster
+    pub public_roster: bool,
+
+    // Seconds after end_time during which get_winner withholds the result
+    pub dispute_period_secs: i64,
+
+    // Program allowed to cast votes for voters through vote_cpi (None disables it)
+    pub cpi_caller: Option<Pubkey>,
+
+    // Unix timestamp at which voting last ended (0 while it hasn't)
+    pub ended_at: i64,
+
+    // Seconds after ended_at during which reopen_voting may undo the end (0 disables it)
+    pub reopen_window: i64,
+
+    // Points each voter distributes through cast_cumulative (0 disables cumulative voting)
+    pub points_budget: u32,
+
+    // Share of total_supply, in basis points, the weighted votes must reach before
+    // get_winner reports a winner (0 disables the quorum)
+    pub quorum_bps: u16,
+
+    // Vote mint supply recorded by snapshot_supply (0 until it is recorded)
+    pub total_supply: u64,
+
+    // Seconds after casting a vote during which undo_vote may take it back (0 disables it)
+    pub undo_window: i64,
+
+    // Candidates disqualified by the authority, parallel to candidates: they keep their
+    // votes but can't receive new ones or win
+    pub disqualified: Vec<bool>,
+
+    // Extra questions posed alongside the main ballot, each with its own options and
+    // tally; added by add_ballot, which grows the account to fit each one
+    pub ballots: Vec<Ballot>,
+
+    // Set once init_voting_account has filled in the election, guarding against a second
+    // initialization of the same account
+    pub is_initialized: bool,
+
+    // Whether the election still counts towards its registry's active_elections
+    // (set by create_election, cleared by release_election)
+    pub registry_active: bool,
+
+    // Whether every vote must come with a reason, cast through vote_with_reason
+    pub require_reason: bool,
+
+    // Whether a user may vote on the main ballot more than once, each vote counting again
+    // (e.g. reaction polls). SYBIL WARNING: with revoting on, a single wallet can cast as
+    // many votes as it can pay fees for, so counts measure activity, not people; only
+    // voter_count (which still counts each user once) reflects participants
+    pub allow_revote: bool,
+
+    // The election's title and description shown on ballots (at most MAX_TITLE_LEN and
+    // MAX_DESCRIPTION_LEN bytes)
+    pub title: String,
+    pub description: String,
+
+    // The election this one's votes roll up into through aggregate_to_parent (e.g. a
+    // district's national election), if any; unrelated to a runoff's parent_election
+    pub rollup_parent: Option<Pubkey>,
+
+    // Whether aggregate_to_parent has already added this election's votes to its parent
+    pub aggregated: bool,
+
+    // Share of its weight, in basis points, a vote keeps however long decay has run (0
+    // lets decay_rate_bps_per_day wipe a late vote out entirely)
+    pub min_decay_weight_bps: u16,
+
+    // When the election was initialized, and the seconds after that reserved for
+    // registration, during which votes fail with RegistrationPeriodActive
+    pub init_time: i64,
+    pub registration_period: i64,
+
+    // The deadline the election was configured with (0 for none); end_voting replaces
+    // end_time with the actual end, and reopen_voting restores this
+    pub configured_end_time: i64,
+
+    // Whether release_election has taken the election out of its registry's active count
+    pub released: bool,
+
+    // Number of votes posted by submit_batch (in total_votes, not in voter_count)
+    pub batched_votes: u64,
+}
+
+// One extra question of an election and its tally
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ballot {
+    // The question put to the voters
+    pub question: String,
+
+    // The answers to choose from
+    pub options: Vec<String>,
+
+    // Vote count of each option, parallel to options
+    pub votes: Vec<u64>,
+}
+
+impl Ballot {
+    // Bytes the ballot takes in the voting account: the question, each option's name and
+    // its vote count, every vector with its 4 byte length
+    pub fn space(question: &str, options: &[String]) -> usize {
+        (4 + question.len())
+            + 4
+            + options.iter().map(|option| 4 + option.len()).sum::<usize>()
+            + (4 + 8 * options.len())
+    }
+}
+
+impl VotingAccount {
+    // Whether voting has finished, equivalent to the has_ended flag the status replaced
+    pub fn has_ended(&self) -> bool {
+        self.status == VotingStatus::Ended
+    }
+
+    // Bytes allocated for a new voting account with candidate_count candidates: SPACE plus
+    // a name and a display name of up to MAX_CANDIDATE_NAME_LEN bytes (with their 4 byte
+    // length prefixes) per candidate
+    // An account can be created with at most 10 KiB, which limits a new ballot to about 30
+    // candidates; add_candidates can grow it up to MAX_CANDIDATES
+    pub const fn space_for(candidate_count: usize) -> usize {
+        Self::SPACE + candidate_count * 2 * (4 + MAX_CANDIDATE_NAME_LEN)
+    }
+
+    // Bytes allocated for a voting account without its candidate names, one term per field
+    // in declaration order
+    // Unlike the fixed-size accounts this isn't derived with InitSpace: the names are sized
+    // per ballot by space_for and eligible_voter_hashes grows by realloc, and bounding
+    // every Vec with #[max_len] would exceed the 10 KiB an account can be created with
+    pub const SPACE: usize = 8 // account discriminator (added by Anchor)
+        + 4 // candidates length (the names themselves are counted by space_for)
+        + (4 + 8 * MAX_CANDIDATES) // votes (4 byte length + 8 bytes per u64 * MAX_CANDIDATES potential candidates)
+        + 1 // status enum
+        + 1 // is_tie flag
+        + 4 // winner_index u32
+        + 33 // vote_mint (1 byte option tag + 32 byte pubkey)
+        + 9 // max_weight_per_voter (1 byte option tag + 8 byte u64)
+        + 1 // weight_cap_mode enum
+        + 32 // authority pubkey
+        + 8 // end_time i64
+        + 8 // close_delay i64
+        + 8 // snapshot_slot u64
+        + 8 // vote_fee u64
+        + (4 + MAX_CANDIDATES) // disabled flags (4 byte length + 1 byte per MAX_CANDIDATES potential candidates)
+        + 9 // min_sol_balance (1 byte option tag + 8 byte u64)
+        + 8 // total_votes u64
+        + 8 // voter_count u64
+        + 8 // abstain_count u64
+        + (4 + 32 * MAX_CANDIDATES) // encrypted_tally (4 byte length + 32 bytes per MAX_CANDIDATES potential candidates)
+        + (4 + MAX_TAGS * (4 + MAX_TAG_LEN)) // tags (4 byte length + up to MAX_TAGS strings)
+        + 4 // eligible_voter_hashes length (add_eligible_hash reallocs 32 bytes per entry)
+        + 8 // round u64
+        + 8 // election_id u64
+        + 1 // bump u8
+        + 4 // min_victory_margin u32
+        + 33 // parent_election (1 byte option tag + 32 byte pubkey)
+        + 1 // rate_limit_votes flag
+        + 8 // ranked_ballot_count u64
+        + (4 + 4 * MAX_CANDIDATES) // eliminated (4 byte length + 4 bytes per MAX_CANDIDATES potential candidates)
+        + 5 // ranked_winner (1 byte option tag + 4 byte u32)
+        + 1 // registration_mode enum
+        + 4 // min_winning_votes u32
+        + 1 // results_hidden flag
+        + 4 // display_names length (the names themselves are counted by space_for)
+        + (4 + MAX_LOCALE_LEN) // locale (4 byte length + up to MAX_LOCALE_LEN bytes)
+        + 8 // start_time i64
+        + 4 // reserved_capacity u32
+        + (4 + 4 * MAX_CANDIDATES) // vetoes (4 byte length + 4 bytes per u32 * MAX_CANDIDATES potential candidates)
+        + (4 + 32 * MAX_ADMINS) // admins (4 byte length + up to MAX_ADMINS pubkeys)
+        + 2 // decay_rate_bps_per_day u16
+        + (4 + 8 * MAX_CANDIDATES) // candidate_ids (4 byte length + 8 bytes per u64 * MAX_CANDIDATES potential candidates)
+        + 8 // next_candidate_id u64
+        + 4 // votes_this_slot u32
+        + 8 // current_slot u64
+        + 4 // max_votes_per_slot u32
+        + 1 // frozen flag
+        + 8 // withdrawn_votes u64
+        + 1 // tie_break enum
+        + 32 // tie_break_seed [u8; 32]
+        + 1 // version u8
+        + (4 + 8 * MAX_CANDIDATES) // reached_max_at (4 byte length + 8 bytes per i64 * MAX_CANDIDATES potential candidates)
+        + 8 // registered_voters u64
+        + 2 // turnout_bps u16
+        + 1 // public_roster flag
+        + 8 // dispute_period_secs i64
+        + 33 // cpi_caller (1 byte option tag + 32 byte pubkey)
+        + 8 // ended_at i64
+        + 8 // reopen_window i64
+        + 4 // points_budget u32
+        + 2 // quorum_bps u16
+        + 8 // total_supply u64
+        + 8 // undo_window i64
+        + (4 + MAX_CANDIDATES) // disqualified flags (4 byte length + 1 byte per MAX_CANDIDATES potential candidates)
+        + 4 // ballots length (add_ballot grows the account for each ballot)
+        + 1 // is_initialized flag
+        + 1 // registry_active flag
+        + 1 // require_reason flag
+        + 1 // allow_revote flag
+        + (4 + MAX_TITLE_LEN) // title (4 byte length + up to MAX_TITLE_LEN bytes)
+        + (4 + MAX_DESCRIPTION_LEN) // description (4 byte length + up to MAX_DESCRIPTION_LEN bytes)
+        + 33 // rollup_parent (1 byte option tag + 32 byte pubkey)
+        + 1 // aggregated flag
+        + 2 // min_decay_weight_bps u16
+        + 8 // init_time i64
+        + 8 // registration_period i64
+        + 8 // configured_end_time i64
+        + 1 // released flag
+        + 8; // batched_votes u64
+}
+
+// Define the structure of the user account's data
+// Its size is derived with InitSpace, so new fields are accounted for automatically
+#[account]
+#[derive(InitSpace)]
+pub struct UserAccount {
+    // Flag indicating if the user has voted
+    pub has_voted: bool,
+
+    // Index of the candidate the user voted for, if they have voted
+    pub voted_for: Option<u32>,
+
+    // The election this account is bound to (default until registration or its first vote)
+    pub election: Pubkey,
+
+    // The voter allowed to sign with this account (default until registration or its first vote)
+    pub voter: Pubkey,
+
+    // The election round this account last voted in or was reset to
+    pub round: u64,
+
+    // Canonical bump of the [b"user", election, user] PDA (0 for keypair accounts)
+    pub bump: u8,
+
+    // Slot of the account's last vote (0 until its first vote)
+    pub last_vote_slot: u64,
+
+    // Token balance at registration, used as the weight of weighted votes
+    // (0 in elections without a vote mint)
+    pub snapshot_weight: u64,
+
+    // Candidate indices the voter may choose, set by the authority (empty allows all)
+    #[max_len(MAX_ELIGIBLE_CANDIDATES)]
+    pub eligible_candidates: Vec<u32>,
+
+    // Unix timestamp of the account's last counted vote (0 until its first vote)
+    pub last_vote_ts: i64,
+
+    // Weight the last counted vote added, which undo_vote takes back
+    pub last_vote_weight: u64,
+
+    // Stable ID of the candidate the last vote counted for (see candidate_ids), so
+    // undo_vote finds them even if the ballot was reordered since
+    pub last_vote_candidate_id: u64,
+
+    // Bit i is set once the voter has answered the election's ballots[i]
+    pub voted_ballots: u32,
+
+    // Blinded increment the last counted vote XOR-ed into encrypted_tally, which
+    // undo_vote XORs back out (None if it carried none)
+    pub last_vote_blinded_increment: Option<[u8; 32]>,
+
+    // The candidate's reached_max_at before the last counted vote set it, which
+    // undo_vote restores
+    pub last_vote_prev_reached_max_at: i64,
+}
+
+// Define the structure of an authority's election registry
+#[account]
+#[derive(InitSpace)]
+pub struct AuthorityRegistry {
+    // The authority whose elections are tracked
+    pub authority: Pubkey,
+
+    // Number of the authority's elections that have not been closed
+    pub election_count: u64,
+
+    // Cap on election_count
+    pub max_elections: u64,
+
+    // Id used to derive the next election's address (never reused)
+    pub next_election_id: u64,
+
+    // Canonical bump of the registry PDA
+    pub bump: u8,
+
+    // Number of the authority's elections that haven't ended (or been released) yet
+    pub active_elections: u64,
+
+    // Cap on active_elections (0 for no cap)
+    pub max_active_elections: u64,
+}
+
+// Define the structure of a ranked-choice ballot
+#[account]
+pub struct RankedBallot {
+    // The election the ballot was cast in
+    pub voting_account: Pubkey,
+
+    // The voter who cast it
+    pub voter: Pubkey,
+
+    // Candidate indices in order of preference
+    pub rankings: Vec<u32>,
+}
+
+// Define the structure of a multisig governance account
+// Used as an election authority so several signers must agree on admin actions
+#[account]
+pub struct Governance {
+    // The keys allowed to co-sign
+    pub signers: Vec<Pubkey>,
+
+    // How many distinct listed signers must co-sign
+    pub threshold: u8,
+}
+
+// Define the structure of an election's public roster
+// Lists who voted in a public_roster election, but not how they voted
+#[account]
+pub struct VoterRoster {
+    // The election whose voters are listed
+    pub election: Pubkey,
+
+    // Every voter who has voted, in the order they first voted
+    pub voters: Vec<Pubkey>,
+
+    // Canonical bump of the [b"roster", election] PDA
+    pub bump: u8,
+}
+
+impl VoterRoster {
+    // Space for an empty roster; each listed voter adds 32 bytes
+    pub const SPACE: usize = 8 // discriminator
+        + 32 // election pubkey
+        + 4 // voters length
+        + 1; // bump u8
+}
+
+// Define the structure of a recorded token balance used by snapshot-based weighted voting
+#[account]
+#[derive(InitSpace)]
+pub struct TokenSnapshot {
+    // The token account whose balance was recorded
+    pub token_account: Pubkey,
+
+    // The balance at the time of recording
+    pub amount: u64,
+
+    // The slot in which the balance was recorded
+    pub slot: u64,
+}
+
+// Define the structure of a tally checkpoint light clients verify counts against
+#[account]
+#[derive(InitSpace)]
+pub struct Checkpoint {
+    // The election the checkpoint belongs to
+    pub voting_account: Pubkey,
+
+    // The slot in which the checkpoint was written
+    pub slot: u64,
+
+    // The election's total_votes at that slot
+    pub total_votes: u64,
+
+    // Hash of the serialized votes vector at that slot
+    pub votes_hash: [u8; 32],
+
+    // Canonical bump of the checkpoint PDA
+    pub bump: u8,
+}
+
+// Define the structure of a vote receipt written by vote_with_reason
+#[account]
+#[derive(InitSpace)]
+pub struct VoteReceipt {
+    // The election the vote was cast in
+    pub voting_account: Pubkey,
+
+    // The voter who cast it
+    pub voter: Pubkey,
+
+    // The stable ID of the chosen candidate
+    pub candidate_id: u64,
+
+    // The voter's reason for their choice
+    #[max_len(MAX_REASON_LEN)]
+    pub reason: String,
+
+    // Canonical bump of the receipt PDA
+    pub bump: u8,
+}
+
+// INSTRUCTION ARGUMENTS AND ENUMS
+
+// Optional settings chosen by the organizer when the election is initialized
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ElectionConfig {
+    // Mint whose balance weights votes cast through vote_weighted (None disables it)
+    pub vote_mint: Option<Pubkey>,
+
+    // Largest weight a single wallet may contribute (None means uncapped)
+    pub max_weight_per_voter: Option<u64>,
+
+    // How weights above max_weight_per_voter are handled
+    pub weight_cap_mode: WeightCapMode,
+
+    // Seconds after voting ends before close_voting may reclaim the account
+    pub close_delay: i64,
+
+    // Last slot at which token balances may be recorded for weighted voting (0 disables snapshots)
+    pub snapshot_slot: u64,
+
+    // Lamports each vote pays to the authority (0 for free elections)
+    pub vote_fee: u64,
+
+    // Minimum lamports a voter must hold to vote (None means no gate)
+    pub min_sol_balance: Option<u64>,
+
+    // Discovery tags for portals grouping elections (at most MAX_TAGS of MAX_TAG_LEN bytes)
+    pub tags: Vec<String>,
+
+    // Create the election in Draft, accepting no votes until open_voting is called
+    pub start_as_draft: bool,
+
+    // Unix timestamp from which votes are accepted (0 to start immediately); may be at
+    // most MAX_START_TIME_DRIFT seconds in the past
+    pub start_time: i64,
+
+    // Unix timestamp after which the next vote ends the election instead (0 for no deadline)
+    // Must be later than the start time
+    pub end_time: i64,
+
+    // Votes the winner must lead the runner-up by for a conclusive result (0 for any lead)
+    pub min_victory_margin: u32,
+
+    // Votes the leader needs to be declared the winner at all (0 for no threshold)
+    pub min_winning_votes: u32,
+
+    // Reject a vote in the same slot as the user account's previous vote
+    pub rate_limit_votes: bool,
+
+    // Whether voters register themselves or are registered by the authority
+    pub registration_mode: RegistrationMode,
+
+    // Hide live tallies from the result getters until voting ends (advisory only)
+    pub results_hidden: bool,
+
+    // Names frontends show instead of the canonical candidate names, one per candidate
+    // (empty to display the canonical names)
+    pub display_names: Vec<String>,
+
+    // Locale of the display names (at most MAX_LOCALE_LEN bytes)
+    pub locale: String,
+
+    // Basis points of weight votes lose per full day after the start (0 for no decay)
+    pub decay_rate_bps_per_day: u16,
+
+    // Most votes vote accepts across the whole election in one slot (0 for no cap)
+    pub max_votes_per_slot: u32,
+
+    // How a tie for first place is settled when voting ends
+    pub tie_break: TieBreak,
+
+    // Whether voters are listed on a public roster (created with initialize_roster)
+    pub public_roster: bool,
+
+    // Seconds after voting ends before get_winner reports a final result
+    pub dispute_period_secs: i64,
+
+    // Program trusted to cast votes for voters through vote_cpi (None disables it)
+    pub cpi_caller: Option<Pubkey>,
+
+    // Seconds after voting ends during which reopen_voting may undo it (0 disables it)
+    pub reopen_window: i64,
+
+    // Points each voter distributes in cumulative voting (0 disables it)
+    pub points_budget: u32,
+
+    // Share of the vote mint's supply, in basis points, that must vote for a winner to be
+    // reported (0 disables the quorum; requires a vote_mint)
+    pub quorum_bps: u16,
+
+    // Seconds after casting a vote during which the voter may undo it (0 disables it)
+    pub undo_window: i64,
+
+    // Whether votes must be cast with a reason through vote_with_reason
+    pub require_reason: bool,
+
+    // Whether users may vote more than once, each vote counting (see the sybil warning on
+    // VotingAccount::allow_revote)
+    pub allow_revote: bool,
+
+    // The election's title and description (at most MAX_TITLE_LEN and MAX_DESCRIPTION_LEN
+    // bytes; either may be empty)
+    pub title: String,
+    pub description: String,
+
+    // The election this one's votes roll up into (None for a standalone election)
+    pub rollup_parent: Option<Pubkey>,
+
+    // Floor, in basis points of the original weight, that decay can't push a vote below
+    pub min_decay_weight_bps: u16,
+
+    // Seconds after initialization for voters to register before voting opens (0 for none)
+    pub registration_period: i64,
+}
+
+// Return value of has_user_voted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UserVoteStatus {
+    // Whether the user has voted
+    pub has_voted: bool,
+
+    // The candidate the user voted for, if any
+    pub voted_for: Option<u32>,
+}
+
+// Return value of time_remaining
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TimeRemaining {
+    // Seconds until end_time (zero or negative once past, i64::MAX without a deadline)
+    pub seconds: i64,
+
+    // Whether voting is paused
+    pub paused: bool,
+}
+
+// Return value of get_summary
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ElectionSummary {
+    // The election's title (empty if none was set)
+    pub title: String,
+
+    // Number of candidates on the ballot
+    pub candidate_count: u32,
+
+    // Total votes cast (0 while results_hidden keeps the results secret)
+    pub total_votes: u64,
+
+    // Whether voting has ended
+    pub has_ended: bool,
+
+    // Whether voting is paused
+    pub is_paused: bool,
+
+    // When voting opened (or opens) and its deadline (0 for none), as Unix timestamps
+    pub start_ts: i64,
+    pub end_ts: i64,
+
+    // The recorded winner once ended (NO_WINNER if there is none), None before that
+    pub winner_index: Option<u32>,
+}
+
+// Entry of the get_rankings return value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CandidateRanking {
+    // The candidate's index on the ballot
+    pub index: u32,
+
+    // The candidate's name
+    pub name: String,
+
+    // The candidate's current vote count (0 while a results_hidden election runs)
+    pub votes: u64,
+}
+
+// Points a cumulative ballot gives one candidate
+// (an (index, points) pair; the IDL can't describe tuples, so it is a struct)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PointAllocation {
+    // The candidate's index on the ballot
+    pub candidate_index: u32,
+
+    // The points given to the candidate
+    pub points: u32,
+}
+
+// Entry of the get_leaderboard return value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeaderboardEntry {
+    // The candidate's index on the ballot
+    pub candidate_index: u32,
+
+    // The candidate's current vote count
+    pub votes: u64,
+}
+
+// Lifecycle state of an election, changed only through check_transition's valid moves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VotingStatus {
+    // Set up but not yet accepting votes
+    Draft,
+
+    // Accepting votes
+    Active,
+
+    // Temporarily not accepting votes
+    Paused,
+
+    // Finished; the outcome is recorded
+    Ended,
+
+    // Declared null and void by the authority; no result is ever reported
+    Cancelled,
+}
+
+// Who may create user accounts for an election
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RegistrationMode {
+    // Voters register themselves (keypair user accounts are accepted too)
+    Open,
+
+    // Only user accounts registered with the authority's co-signature can vote
+    AuthorityOnly,
+}
+
+// Behaviour when a voter's weight exceeds max_weight_per_voter
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WeightCapMode {
+    // Count the vote at the cap
+    Clamp,
+
+    // Refuse the vote with WeightExceedsCap
+    Reject,
+}
+
+// How a tie for first place is settled when voting ends
+// (Seeded comes first so accounts written with the earlier break_ties flag keep their meaning)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TieBreak {
+    // Leave the election without a winner
+    None,
+
+    // Pick a leader with the pseudo-random tie_break_seed
+    Seeded,
+
+    // Pick the leader listed first on the ballot
+    LowestIndex,
+
+    // Pick the leader who reached the top count first
+    EarliestToReach,
+}
+
+// EVENTS
+
+// Emitted when an election is created, so indexers can discover and categorize it
+#[event]
+pub struct VotingInitialized {
+    // The new election
+    pub voting_account: Pubkey,
+
+    // The account administering the election
+    pub authority: Pubkey,
+
+    // The candidates on the ballot
+    pub candidates: Vec<String>,
+
+    // The election's discovery tags
+    pub tags: Vec<String>,
+}
+
+// Emitted by end_voting with the final outcome of the election
+#[event]
+pub struct VotingEnded {
+    // The election that ended
+    pub voting_account: Pubkey,
+
+    // Index of the winning candidate, or NO_WINNER if tied
+    pub winner_index: u32,
+
+    // Whether the top candidates finished with the same count
+    pub is_tie: bool,
+
+    // Number of voters who abstained
+    pub abstain_count: u64,
+}
+
+// Emitted when voting ends and again when the election is closed, so clients can keep a
+// fingerprint of the final results that outlives the account (see results_hash)
+#[event]
+pub struct ResultsArchived {
+    // The election whose results are archived
+    pub voting_account: Pubkey,
+
+    // SHA-256 of the final candidates and votes
+    pub hash: [u8; 32],
+
+    // The election's final total_votes
+    pub total_votes: u64,
+}
+
+// Emitted by end_voting when the leader falls short of min_winning_votes
+#[event]
+pub struct NoWinner {
+    // The election that ended without a winner
+    pub voting_account: Pubkey,
+
+    // The leader's vote count
+    pub top_votes: u64,
+
+    // The threshold the leader had to reach
+    pub min_winning_votes: u32,
+}
+
+// Emitted by end_voting when the election ends without any votes
+#[event]
+pub struct NoVotesCast {
+    // The election that ended without votes
+    pub voting_account: Pubkey,
+}
+
+// Emitted by export_packed with the election's vote counts in compact form
+#[event]
+pub struct PackedResults {
+    // The election the counts belong to
+    pub voting_account: Pubkey,
+
+    // A little-endian u32 candidate count, then a little-endian u64 per candidate
+    pub data: Vec<u8>,
+}
+
+// Emitted by emergency_cancel when an election is declared void
+#[event]
+pub struct ElectionCancelled {
+    // The election that was cancelled
+    pub voting_account: Pubkey,
+
+    // The reason given by the authority
+    pub reason: String,
+}
+
+// Emitted when emergency_halt freezes an election
+#[event]
+pub struct ElectionHalted {
+    // The election that was frozen
+    pub voting_account: Pubkey,
+}
+
+// Emitted by health_check with the outcome of the consistency checks
+#[event]
+pub struct HealthReport {
+    // The election that was checked
+    pub voting_account: Pubkey,
+
+    // Whether every invariant holds
+    pub ok: bool,
+
+    // A description of each broken invariant (empty when ok)
+    pub details: Vec<String>,
+}
+
+// Emitted by get_candidate with a single candidate's data
+#[event]
+pub struct CandidateInfo {
+    // The candidate's index on the ballot
+    pub index: u32,
+
+    // The candidate's name
+    pub name: String,
+
+    // The candidate's current vote count
+    pub votes: u64,
+}
+
+// Emitted by withdraw_candidate with the votes taken out of the tally
+#[event]
+pub struct CandidateWithdrawn {
+    // The election the candidate withdrew from
+    pub voting_account: Pubkey,
+
+    // The candidate's index on the ballot
+    pub index: u32,
+
+    // The votes the candidate had, now discarded
+    pub votes: u64,
+}
+
+// Emitted by nonzero_candidate_count
+#[event]
+pub struct NonzeroCandidateCount {
+    // The election that was counted
+    pub voting_account: Pubkey,
+
+    // How many candidates have at least one vote
+    pub count: u32,
+}
+
+// CUSTOM ERROR CODES
+
+// Define custom error codes for the program
+#[error_code]
+pub enum ErrorCode {
+    // Error when a user tries to vote more than once
+    #[msg("User has already voted")]
+    AlreadyVoted,
+    
+    // Error when a user tries to vote for a non-existent candidate
+    #[msg("Invalid candidate index")]
+    InvalidCandidate,
+
+    // Error when adding votes would overflow a candidate's count
+    #[msg("Vote count overflow")]
+    Overflow,
+
+    // Error when vote_weighted is used on an election without a vote mint
+    #[msg("Weighted voting is not enabled for this election")]
+    WeightedVotingDisabled,
+
+    // Error when the token account is not the voter's account for the vote mint
+    #[msg("Token account does not belong to the voter or holds the wrong mint")]
+    InvalidTokenAccount,
+
+    // Error when a weighted vote would count for nothing
+    #[msg("Voter has no voting weight")]
+    NoVotingWeight,
+
+    // Error when a weight exceeds the per-voter cap in reject mode
+    #[msg("Vote weight exceeds the per-voter cap")]
+    WeightExceedsCap,
+
+    // Error when the configured close delay is negative
+    #[msg("Close delay cannot be negative")]
+    InvalidCloseDelay,
+
+    // Error when an action requires the voting to have ended first
+    #[msg("Voting has not ended")]
+    VotingNotEnded,
+
+    // Error when closing before the decommission window has passed
+    #[msg("Voting account cannot be closed until the close delay has passed")]
+    CloseTooSoon,
+
+    // Error when recording a snapshot for an election without a snapshot slot
+    #[msg("This election does not use balance snapshots")]
+    SnapshotNotConfigured,
+
+    // Error when recording a balance after the snapshot slot
+    #[msg("The snapshot slot has passed")]
+    SnapshotSlotPassed,
+
+    // Error when a weighted vote is cast without a recorded balance
+    #[msg("A balance snapshot recorded before the snapshot slot is required")]
+    SnapshotRequired,
+
+    // Error when the signer does not act for the election authority
+    #[msg("Signer is not the election authority")]
+    Unauthorized,
+
+    // Error when too few governance signers co-signed an admin action
+    #[msg("Not enough governance signers approved this action")]
+    ThresholdNotMet,
+
+    // Error when a governance account's signers or threshold are invalid
+    #[msg("Governance needs unique signers and a threshold between 1 and the signer count")]
+    InvalidGovernance,
+
+    // Error when an action is attempted on an election that has already ended
+    #[msg("Voting has already ended")]
+    VotingAlreadyEnded,
+
+    // Error when voting while the election is paused
+    #[msg("Voting is paused")]
+    VotingPaused,
+
+    // Error when the voter cannot pay the election's vote fee
+    #[msg("Insufficient lamports to pay the vote fee")]
+    InsufficientFundsForFee,
+
+    // Error when voting for a candidate the authority has disabled
+    #[msg("Candidate has been disabled")]
+    CandidateDisabled,
+
+    // Error when an authority already has the maximum number of elections
+    #[msg("Election limit reached for this authority")]
+    ElectionLimitReached,
+
+    // Error when the voter holds less SOL than the election requires
+    #[msg("Voter balance is below the election's minimum")]
+    InsufficientBalance,
+
+    // Error when a candidate reordering is not a permutation of the ballot
+    #[msg("New order must list every candidate index exactly once")]
+    InvalidPermutation,
+
+    // Error when an election is given more than MAX_TAGS tags
+    #[msg("Too many tags")]
+    TooManyTags,
+
+    // Error when a tag is longer than MAX_TAG_LEN bytes
+    #[msg("Tag is too long")]
+    TagTooLong,
+
+    // Error when a voter's hashed key is not on the election's whitelist
+    #[msg("Voter is not eligible for this election")]
+    NotEligible,
+
+    // Error when adding a hash that is already on the whitelist
+    #[msg("Voter hash is already eligible")]
+    HashAlreadyEligible,
+
+    // Error when a user account bound to one election is used in another
+    #[msg("User account belongs to a different election")]
+    UserAccountMismatch,
+
+    // Error when resetting a user account that is already in the current round
+    #[msg("User account can only be reset after the election is reset")]
+    ResetNotAllowed,
+
+    // Error when an election is moved to a status its current status can't reach
+    #[msg("Invalid election status transition")]
+    InvalidTransition,
+
+    // Error when voting in an election that is in Draft or has ended
+    #[msg("Voting is not active")]
+    VotingNotActive,
+
+    // Error when a vote arrives after the deadline
+    #[msg("Voting deadline has passed")]
+    VotingExpired,
+
+    // Error when the leader does not lead the runner-up by min_victory_margin
+    #[msg("Winner did not reach the minimum margin of victory")]
+    MarginNotMet,
+
+    // Error when a runoff is requested from an election with fewer than two candidates
+    #[msg("Not enough candidates")]
+    NotEnoughCandidates,
+
+    // Error when a rate-limited user account votes twice in one slot
+    #[msg("Too many votes in the same slot")]
+    TooFast,
+
+    // Error when a ranked ballot lists an invalid or repeated candidate, or none at all
+    #[msg("Invalid ranking")]
+    InvalidRanking,
+
+    // Error when candidates are reordered after ranked ballots were cast
+    #[msg("Ranked ballots have already been cast")]
+    RankedBallotsCast,
+
+    // Error when tally_ranked is not given exactly the election's ranked ballots, in order
+    #[msg("Invalid ranked ballot accounts")]
+    InvalidRankedBallots,
+
+    // Error when no ranked ballot has a candidate left to count
+    #[msg("No ranked ballots to tally")]
+    NoRankedBallots,
+
+    // Error when running another round after the ranked-choice winner is known
+    #[msg("Ranked-choice tally is complete")]
+    TallyComplete,
+
+    // Error when registering or voting without the authority in an AuthorityOnly election
+    #[msg("Registration is closed to self-registration")]
+    RegistrationClosed,
+
+    // Error when a new candidate name is empty
+    #[msg("Invalid candidate name")]
+    InvalidCandidateName,
+
+    // Error when a new candidate is already on the ballot or repeated in the batch
+    #[msg("Duplicate candidate")]
+    DuplicateCandidate,
+
+    // Error when asking for the result of a cancelled election
+    #[msg("Election was cancelled")]
+    ElectionCancelled,
+
+    // Error when calling the deprecated keypair user account instruction
+    #[msg("Use initialize_user_account to create the user account PDA")]
+    UseAccountPda,
+
+    // Error when reading the results of a results_hidden election before it ended
+    #[msg("Results are hidden until voting ends")]
+    ResultsHidden,
+
+    // Error when the display names don't line up with the candidates
+    #[msg("Display names must match the candidates in number")]
+    DisplayNamesMismatch,
+
+    // Error when the locale tag exceeds MAX_LOCALE_LEN bytes
+    #[msg("Locale is too long")]
+    LocaleTooLong,
+
+    // Error when an election's start time lies too far in the past
+    #[msg("Start time is too far in the past")]
+    InvalidStartTime,
+
+    // Error when an election's deadline is not after its start time
+    #[msg("End time must be after the start time")]
+    InvalidEndTime,
+
+    // Error when voting before the election's start time
+    #[msg("Voting has not started yet")]
+    VotingNotStarted,
+
+    // Error when adding a key that is already an admin
+    #[msg("Admin already added")]
+    AdminAlreadyAdded,
+
+    // Error when the admin list is full
+    #[msg("Too many admins")]
+    TooManyAdmins,
+
+    // Error when removing a key that is not an admin
+    #[msg("Admin not found")]
+    AdminNotFound,
+
+    // Error when the payer cannot cover the rent of a growing account
+    #[msg("Payer cannot cover the rent for the larger account")]
+    InsufficientRent,
+
+    // Error when no candidate has the given ID
+    #[msg("Candidate not found")]
+    CandidateNotFound,
+
+    // Error when removing a candidate who already received votes or vetoes
+    #[msg("Candidate has votes")]
+    CandidateHasVotes,
+
+    // Error when the election has accepted its maximum number of votes in this slot
+    #[msg("Too many votes in this slot, try again shortly")]
+    RateLimited,
+
+    // Error when a vote targets a different round than the election's current one
+    #[msg("Vote is for a different election round")]
+    StaleRound,
+
+    // Error when an instruction would change an election frozen by emergency_halt
+    #[msg("Election is frozen")]
+    Frozen,
+
+    // Error when migrate_voting_account is called on an account in the current layout
+    #[msg("Voting account already uses the current layout")]
+    AlreadyMigrated,
+
+    // Error when a candidate or display name is longer than MAX_CANDIDATE_NAME_LEN bytes
+    #[msg("Candidate name is too long")]
+    CandidateNameTooLong,
+
+    // Error when a public roster election is voted in without its roster
+    #[msg("This election lists its voters; pass its roster account")]
+    RosterRequired,
+
+    // Error when a roster is created or passed for an election without a public roster
+    #[msg("This election does not keep a public roster")]
+    RosterDisabled,
+
+    // Error when the configured dispute period is negative
+    #[msg("Dispute period cannot be negative")]
+    InvalidDisputePeriod,
+
+    // Error when the result is requested before the dispute period has elapsed
+    #[msg("The result is not final until the dispute period has elapsed")]
+    DisputePeriodActive,
+
+    // Error when vote_cpi is used in an election without a cpi_caller
+    #[msg("This election does not accept votes through CPI")]
+    CpiNotAllowed,
+
+    // Error when vote_cpi is signed by anything but the caller program's delegate PDA
+    #[msg("Delegate is not the caller program's PDA for this voter")]
+    InvalidCpiDelegate,
+
+    // Error when the configured reopen window is negative
+    #[msg("Reopen window cannot be negative")]
+    InvalidReopenWindow,
+
+    // Error when reopen_voting is called after the reopen window has passed
+    #[msg("The window for reopening this election has closed")]
+    ReopenWindowClosed,
+
+    // Error when a ballot would hold more than MAX_CANDIDATES candidates
+    #[msg("Too many candidates")]
+    TooManyCandidates,
+
+    // Error when a voter chooses a candidate outside their eligible_candidates
+    #[msg("The voter may not choose this candidate")]
+    CandidateNotEligible,
+
+    // Error when an allow-list holds more than MAX_ELIGIBLE_CANDIDATES indices
+    #[msg("Too many eligible candidates")]
+    TooManyEligibleCandidates,
+
+    // Error when cast_cumulative is used in an election without a points budget
+    #[msg("This election does not use cumulative voting")]
+    CumulativeVotingDisabled,
+
+    // Error when a cumulative ballot doesn't spend exactly the points budget
+    #[msg("Allocated points must add up to the points budget")]
+    BudgetMismatch,
+
+    // Error when a quorum exceeds 100% or is set without a vote mint
+    #[msg("Quorum must be at most 10000 basis points of a vote mint's supply")]
+    InvalidQuorum,
+
+    // Error when too little of the token supply voted for a winner to be reported
+    #[msg("Quorum not reached")]
+    QuorumNotReached,
+
+    // Error when a sponsored registration isn't authorized by the voter's signature
+    #[msg("Missing or invalid voter signature")]
+    InvalidSignature,
+
+    // Error when seed_votes is called after voting has started
+    #[msg("Votes can only be seeded before anyone has voted")]
+    CannotSeedAfterStart,
+
+    // Error when seed_votes isn't given exactly one count per candidate
+    #[msg("Seeded counts must match the candidates")]
+    SeedCountsMismatch,
+
+    // Error when an election is configured with a negative undo window
+    #[msg("Undo window must not be negative")]
+    InvalidUndoWindow,
+
+    // Error when undo_vote is called without a single-choice vote in the current round
+    #[msg("There is no vote to undo")]
+    NoVoteToUndo,
+
+    // Error when undo_vote is called after the undo window has passed
+    #[msg("The undo window for this vote has closed")]
+    UndoWindowClosed,
+
+    // Error when voting for a candidate the authority disqualified
+    #[msg("Candidate is disqualified")]
+    CandidateDisqualified,
+
+    // Error when adding a ballot to an election that already has MAX_BALLOTS
+    #[msg("Too many ballots")]
+    TooManyBallots,
+
+    // Error when a ballot's question is empty or longer than MAX_QUESTION_LEN
+    #[msg("Invalid ballot question")]
+    InvalidQuestion,
+
+    // Error when a ballot has fewer than 2 or more than MAX_BALLOT_OPTIONS options
+    #[msg("A ballot needs between 2 and MAX_BALLOT_OPTIONS options")]
+    InvalidBallotOptions,
+
+    // Error when voting on a ballot the election doesn't have
+    #[msg("Invalid ballot")]
+    InvalidBallot,
+
+    // Error when initializing an account that already holds an election
+    #[msg("Voting account is already initialized")]
+    AlreadyInitialized,
+
+    // Error when create_election would exceed the registry's max_active_elections
+    #[msg("Too many active elections")]
+    TooManyActiveElections,
+
+    // Error when releasing an election that no longer counts as active
+    #[msg("Election was already released")]
+    ElectionAlreadyReleased,
+
+    // Error when an election requiring reasons gets a vote without one
+    #[msg("This election requires a reason with each vote")]
+    ReasonRequired,
+
+    // Error when a vote's reason exceeds MAX_REASON_LEN bytes
+    #[msg("Reason is too long")]
+    ReasonTooLong,
+
+    // Error when the election's per-candidate lists don't match its candidates
+    #[msg("Election state is corrupted")]
+    CorruptedState,
+
+    // Error when the title or description exceeds its maximum length
+    #[msg("Title or description is too long")]
+    MetadataTooLong,
+
+    // Error when a sub-election's candidates don't line up with its parent's
+    #[msg("Candidates don't match the parent election's")]
+    CandidateMismatch,
+
+    // Error when aggregating an election into one it doesn't roll up into
+    #[msg("Election does not roll up into this parent")]
+    NotSubElection,
+
+    // Error when a sub-election's votes were already added to its parent
+    #[msg("Election was already aggregated")]
+    AlreadyAggregated,
+
+    // Error when min_decay_weight_bps exceeds 10,000 basis points
+    #[msg("Minimum decay weight must be at most 10000 basis points")]
+    InvalidDecayFloor,
+
+    // Error when the registration period is negative
+    #[msg("Registration period must not be negative")]
+    InvalidRegistrationPeriod,
+
+    // Error when voting before the registration period has ended
+    #[msg("Registration period is still running")]
+    RegistrationPeriodActive,
+
+    // Error when finalizing an election whose deadline hasn't passed (or that has none)
+    #[msg("The election's deadline has not passed yet")]
+    DeadlineNotReached,
+
+    // Error when migrating an account that is neither in the original nor the current layout
+    #[msg("Account layout is not supported for migration")]
+    UnsupportedLayout,
+}
+
+// UNIT TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A voting account with every field zeroed or empty, for tests to fill in
+    fn blank_voting_account() -> VotingAccount {
+        let mut data = <VotingAccount as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        data.resize(8 + VotingAccount::SPACE, 0);
+        VotingAccount::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    // Adding to a count well below the limit succeeds
+    #[test]
+    fn add_votes_increments_count() {
+        assert_eq!(add_votes(41, 1).unwrap(), 42);
+    }
+
+    // Weighted totals can grow past the old u32 limit
+    #[test]
+    fn add_votes_exceeds_u32() {
+        assert_eq!(add_votes(u64::from(u32::MAX), 1).unwrap(), u64::from(u32::MAX) + 1);
+    }
+
+    // The last representable count can still be reached
+    #[test]
+    fn add_votes_reaches_max() {
+        assert_eq!(add_votes(u64::MAX - 1, 1).unwrap(), u64::MAX);
+    }
+
+    // Going past u64::MAX returns Overflow instead of wrapping to zero
+    #[test]
+    fn add_votes_rejects_overflow() {
+        let err = add_votes(u64::MAX, 1).unwrap_err();
+        assert_eq!(err, ErrorCode::Overflow.into());
+    }
+
+    // Clamp mode counts an oversized weight at the cap
+    #[test]
+    fn apply_weight_cap_clamps() {
+        assert_eq!(apply_weight_cap(500, Some(100), WeightCapMode::Clamp).unwrap(), 100);
+        assert_eq!(apply_weight_cap(50, Some(100), WeightCapMode::Clamp).unwrap(), 50);
+    }
+
+    // Reject mode refuses an oversized weight but accepts one at the cap
+    #[test]
+    fn apply_weight_cap_rejects() {
+        let err = apply_weight_cap(101, Some(100), WeightCapMode::Reject).unwrap_err();
+        assert_eq!(err, ErrorCode::WeightExceedsCap.into());
+        assert_eq!(apply_weight_cap(100, Some(100), WeightCapMode::Reject).unwrap(), 100);
+    }
+
+    // Only orders listing every index exactly once are permutations
+    #[test]
+    fn is_permutation_detects_invalid_orders() {
+        assert!(is_permutation(&[2, 0, 1], 3));
+        assert!(!is_permutation(&[0, 0, 1], 3));
+        assert!(!is_permutation(&[0, 1, 3], 3));
+        assert!(!is_permutation(&[0, 1], 3));
+    }
+
+    // The leader must be ahead of the runner-up by at least the margin
+    #[test]
+    fn check_margin_compares_top_two() {
+        assert!(check_margin(&[5, 8, 3], 3).is_ok());
+        assert_eq!(check_margin(&[5, 8, 3], 4).unwrap_err(), ErrorCode::MarginNotMet.into());
+        assert!(check_margin(&[4, 4], 0).is_ok());
+        assert!(check_margin(&[2], 2).is_ok());
+    }
+
+    // Starts may be slightly but not long past, and deadlines must follow the start
+    #[test]
+    fn check_schedule_validates_start_and_end() {
+        let now = 1_700_000_000;
+        assert_eq!(check_schedule(now, 0, 0, 0).unwrap(), now);
+        let earliest = now - MAX_START_TIME_DRIFT;
+        assert_eq!(check_schedule(now, earliest, 0, 0).unwrap(), earliest);
+        assert_eq!(
+            check_schedule(now, earliest - 1, 0, 0).unwrap_err(),
+            ErrorCode::InvalidStartTime.into()
+        );
+        assert!(check_schedule(now, now + 10, now + 11, 0).is_ok());
+        assert_eq!(check_schedule(now, now + 10, now + 10, 0).unwrap_err(), ErrorCode::InvalidEndTime.into());
+        assert_eq!(check_schedule(now, 0, now - 1, 0).unwrap_err(), ErrorCode::InvalidEndTime.into());
+    }
+
+    // Shares are basis points of the total, rounded down, and zero without votes
+    #[test]
+    fn vote_shares_uses_basis_points() {
+        assert_eq!(vote_shares(&[1, 1, 1]), vec![3333, 3333, 3333]);
+        assert_eq!(vote_shares(&[3, 1, 0]), vec![7500, 2500, 0]);
+        assert_eq!(vote_shares(&[u64::MAX, u64::MAX]), vec![5000, 5000]);
+        assert_eq!(vote_shares(&[0, 0]), vec![0, 0]);
+    }
+
+    // Decay is linear per full day, rounds down and stops at zero
+    #[test]
+    fn decay_weight_reduces_later_votes() {
+        assert_eq!(decay_weight(1000, 2000, 0, 0), 1000);
+        assert_eq!(decay_weight(1000, 2000, 1, 0), 800);
+        assert_eq!(decay_weight(1000, 2000, 6, 0), 0);
+        assert_eq!(decay_weight(1, 1, 1, 0), 0);
+        assert_eq!(decay_weight(u64::MAX, 0, 100, 0), u64::MAX);
+    }
+
+    // The floor only applies once decay would take a vote below it
+    #[test]
+    fn decay_weight_is_clamped_to_the_floor() {
+        assert_eq!(decay_weight(1000, 2000, 1, 5000), 800);
+        assert_eq!(decay_weight(1000, 2000, 3, 5000), 500);
+        assert_eq!(decay_weight(1000, 2000, 100, 5000), 500);
+        assert_eq!(decay_weight(1000, 0, 100, 5000), 1000);
+        assert_eq!(decay_weight(1000, 10_000, 1, 10_000), 1000);
+    }
+
+    // Only candidates with more vetoes than votes lose their votes
+    #[test]
+    fn standing_votes_disqualifies_vetoed_candidates() {
+        let qualified = [false; 3];
+        assert_eq!(standing_votes(&[5, 3, 2], &[5, 4, 0], &qualified), vec![5, 0, 2]);
+        assert_eq!(standing_votes(&[0, 1], &[0, 0], &qualified), vec![0, 1]);
+    }
+
+    // Candidates the authority disqualified count as having no votes
+    #[test]
+    fn standing_votes_excludes_disqualified_candidates() {
+        assert_eq!(
+            standing_votes(&[5, 3, 2], &[0, 0, 0], &[true, false, false]),
+            vec![0, 3, 2]
+        );
+    }
+
+    // Candidates are ordered by votes, ties by ballot position
+    #[test]
+    fn ranking_order_breaks_ties_by_index() {
+        assert_eq!(ranking_order(&[2, 5, 5, 0, 2]), vec![1, 2, 0, 4, 3]);
+        assert_eq!(ranking_order(&[]), Vec::<usize>::new());
+    }
+
+    // Rankings must be non-empty, in range and free of repeats
+    #[test]
+    fn is_valid_ranking_detects_invalid_rankings() {
+        assert!(is_valid_ranking(&[2, 0], 3));
+        assert!(!is_valid_ranking(&[], 3));
+        assert!(!is_valid_ranking(&[0, 3], 3));
+        assert!(!is_valid_ranking(&[1, 1], 3));
+    }
+
+    // A 3-candidate race without a first-round majority resolves in the second round
+    #[test]
+    fn irv_round_eliminates_then_finds_majority() {
+        let ballots = vec![vec![0, 1], vec![0, 2], vec![1, 0], vec![2, 1], vec![2, 1]];
+        assert_eq!(irv_round(&ballots, 3, &[]).unwrap(), IrvOutcome::Eliminate(1));
+        assert_eq!(irv_round(&ballots, 3, &[1]).unwrap(), IrvOutcome::Winner(0));
+    }
+
+    // Exactly the documented status moves are allowed
+    #[test]
+    fn check_transition_allows_only_valid_moves() {
+        use VotingStatus::*;
+        let valid = [
+            (Draft, Active),
+            (Draft, Ended),
+            (Active, Paused),
+            (Active, Ended),
+            (Paused, Active),
+            (Paused, Ended),
+            (Ended, Draft),
+            (Ended, Active),
+            (Draft, Cancelled),
+            (Active, Cancelled),
+            (Paused, Cancelled),
+            (Ended, Cancelled),
+        ];
+        for from in [Draft, Active, Paused, Ended, Cancelled] {
+            for to in [Draft, Active, Paused, Ended, Cancelled] {
+                let result = check_transition(from, to);
+                if valid.contains(&(from, to)) {
+                    assert!(result.is_ok(), "{:?} -> {:?} should be valid", from, to);
+                } else {
+                    assert_eq!(result.unwrap_err(), ErrorCode::InvalidTransition.into());
+                }
+            }
+        }
+    }
+
+    // Past the deadline every open status reads as Ended; final statuses never change
+    #[test]
+    fn effective_status_ends_elections_past_the_deadline() {
+        use VotingStatus::*;
+        for status in [Draft, Active, Paused] {
+            assert_eq!(effective_status(status, 0, 1_000), status);
+            assert_eq!(effective_status(status, 100, 99), status);
+            assert_eq!(effective_status(status, 100, 100), Ended);
+        }
+        assert_eq!(effective_status(Cancelled, 100, 200), Cancelled);
+        assert_eq!(effective_status(Ended, 100, 50), Ended);
+    }
+
+    // A seed always picks the same leader, and never a candidate outside the tie
+    #[test]
+    fn break_tie_is_deterministic_for_a_seed() {
+        let votes = [3, 5, 1, 5, 5];
+        let seeded = |seed: &[u8; 32]| break_tie(&votes, TieBreak::Seeded, seed, &[]);
+        for byte in 0..=u8::MAX {
+            let seed = [byte; 32];
+            let winner = seeded(&seed);
+            assert!([1, 3, 4].contains(&winner));
+            assert_eq!(seeded(&seed), winner);
+        }
+        let mut seed = [0u8; 32];
+        seed[0] = 4;
+        assert_eq!(seeded(&seed), 3);
+        assert_eq!(break_tie(&[], TieBreak::Seeded, &seed, &[]), NO_WINNER);
+    }
+
+    // The other rules pick nobody, the first leader, or the first to reach the top count
+    #[test]
+    fn break_tie_applies_each_rule() {
+        let votes = [3, 5, 1, 5, 5];
+        let reached_max_at = [10, 40, 20, 30, 30];
+        let seed = [0u8; 32];
+        let pick = |rule| break_tie(&votes, rule, &seed, &reached_max_at);
+        assert_eq!(pick(TieBreak::None), NO_WINNER);
+        assert_eq!(pick(TieBreak::LowestIndex), 1);
+        assert_eq!(pick(TieBreak::EarliestToReach), 3);
+    }
+
+    // Only the current layout decodes as is; intermediate layouts are refused, not padded
+    #[test]
+    fn migration_rejects_intermediate_layouts() {
+        let mut account = blank_voting_account();
+        account.candidates = vec!["Alice".to_string(), "Bob".to_string()];
+        account.votes = vec![3, 1];
+        account.version = VOTING_ACCOUNT_VERSION;
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert_eq!(decode_legacy_voting_account(&data).unwrap().votes, vec![3, 1]);
+
+        // An older version in the current shape, and an account cut short, are both refused
+        account.version = VOTING_ACCOUNT_VERSION - 1;
+        let mut older_data = Vec::new();
+        account.try_serialize(&mut older_data).unwrap();
+        assert_eq!(
+            decode_legacy_voting_account(&older_data).err(),
+            Some(ErrorCode::UnsupportedLayout.into())
+        );
+        let mut truncated = data.clone();
+        while truncated.last() == Some(&0) {
+            truncated.pop();
+        }
+        assert_eq!(
+            decode_legacy_voting_account(&truncated).err(),
+            Some(ErrorCode::UnsupportedLayout.into())
+        );
+
+        // Other accounts are still told apart by their discriminator
+        let user = <UserAccount as anchor_lang::Discriminator>::DISCRIMINATOR;
+        assert!(decode_legacy_voting_account(&user).is_err());
+    }
+
+    // The countdown reaches zero at the deadline and goes negative after it
+    #[test]
+    fn seconds_until_counts_down_to_the_deadline() {
+        assert_eq!(seconds_until(100, 40), 60);
+        assert_eq!(seconds_until(100, 100), 0);
+        assert_eq!(seconds_until(100, 130), -30);
+        assert_eq!(seconds_until(0, 130), i64::MAX);
+    }
+
+    // Turnout is a share of the registered voters, and 0 when nobody registered
+    #[test]
+    fn turnout_is_measured_against_registered_voters() {
+        assert_eq!(turnout_bps(2, 4), 5000);
+        assert_eq!(turnout_bps(1, 3), 3333);
+        assert_eq!(turnout_bps(4, 4), 10_000);
+        assert_eq!(turnout_bps(5, 4), 10_000);
+        assert_eq!(turnout_bps(0, 0), 0);
+        assert_eq!(turnout_bps(3, 0), 0);
+    }
+
+    // The window covers [end_time, end_time + period) and is empty without a period
+    #[test]
+    fn dispute_period_follows_the_end() {
+        assert!(dispute_period_active(100, 60, 100));
+        assert!(dispute_period_active(100, 60, 159));
+        assert!(!dispute_period_active(100, 60, 160));
+        assert!(!dispute_period_active(100, 0, 100));
+        assert!(dispute_period_active(100, i64::MAX, i64::MAX - 1));
+    }
+
+    // Reopening is possible for reopen_window seconds after the end, and never without one
+    #[test]
+    fn reopen_window_follows_the_end() {
+        assert!(reopen_window_open(100, 30, 100));
+        assert!(reopen_window_open(100, 30, 129));
+        assert!(!reopen_window_open(100, 30, 130));
+        assert!(!reopen_window_open(100, 0, 100));
+    }
+
+    // The derived sizes cover the largest value of each fixed-size account
+    #[test]
+    fn init_space_fits_the_largest_accounts() {
+        let user_account = UserAccount {
+            has_voted: true,
+            voted_for: Some(u32::MAX),
+            election: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+            round: u64::MAX,
+            bump: u8::MAX,
+            last_vote_slot: u64::MAX,
+            snapshot_weight: u64::MAX,
+            eligible_candidates: vec![u32::MAX; MAX_ELIGIBLE_CANDIDATES],
+            last_vote_ts: i64::MAX,
+            last_vote_weight: u64::MAX,
+            last_vote_candidate_id: u64::MAX,
+            voted_ballots: u32::MAX,
+            last_vote_blinded_increment: Some([u8::MAX; 32]),
+            last_vote_prev_reached_max_at: i64::MAX,
+        };
+        assert_eq!(user_account.try_to_vec().unwrap().len(), UserAccount::INIT_SPACE);
+        assert_eq!(8 + UserAccount::INIT_SPACE, 240);
+
+        let registry = AuthorityRegistry {
+            authority: Pubkey::new_unique(),
+            election_count: u64::MAX,
+            max_elections: u64::MAX,
+            next_election_id: u64::MAX,
+            bump: u8::MAX,
+            active_elections: u64::MAX,
+            max_active_elections: u64::MAX,
+        };
+        assert_eq!(registry.try_to_vec().unwrap().len(), AuthorityRegistry::INIT_SPACE);
+
+        let snapshot = TokenSnapshot {
+            token_account: Pubkey::new_unique(),
+            amount: u64::MAX,
+            slot: u64::MAX,
+        };
+        assert_eq!(snapshot.try_to_vec().unwrap().len(), TokenSnapshot::INIT_SPACE);
+    }
+
+    // A ballot may hold exactly MAX_CANDIDATES candidates, and no more
+    #[test]
+    fn candidate_capacity_stops_at_the_limit() {
+        assert!(require_candidate_capacity(MAX_CANDIDATES).is_ok());
+        let err = require_candidate_capacity(MAX_CANDIDATES + 1).unwrap_err();
+        assert_eq!(err, ErrorCode::TooManyCandidates.into());
+    }
+
+    // An empty allow-list allows every candidate, otherwise only the listed ones
+    #[test]
+    fn allow_list_restricts_choices() {
+        assert!(may_choose(&[], 7));
+        assert!(may_choose(&[1, 3], 3));
+        assert!(!may_choose(&[1, 3], 2));
+    }
+
+    // Only a ballot spending exactly the budget is accepted
+    #[test]
+    fn check_budget_requires_the_exact_budget() {
+        let ballot = |points: &[u32]| -> Vec<PointAllocation> {
+            points
+                .iter()
+                .enumerate()
+                .map(|(index, points)| PointAllocation {
+                    candidate_index: index as u32,
+                    points: *points,
+                })
+                .collect()
+        };
+        assert!(check_budget(&ballot(&[5, 3, 2]), 10).is_ok());
+        assert!(check_budget(&ballot(&[5, 3]), 10).is_err());
+        assert!(check_budget(&ballot(&[5, 3, 3]), 10).is_err());
+        assert!(check_budget(&ballot(&[u32::MAX, 1]), 0).is_err());
+    }
+
+    // A consistent account passes; each kind of corruption is reported
+    #[test]
+    fn health_issues_reports_broken_invariants() {
+        let mut account = blank_voting_account();
+        account.candidates = vec!["Alice".to_string(), "Bob".to_string()];
+        fill_migrated_defaults(&mut account);
+        account.votes = vec![3, 1];
+        account.total_votes = 4;
+        assert!(health_issues(&account).is_empty());
+
+        account.total_votes = 5;
+        account.vetoes.pop();
+        account.status = VotingStatus::Ended;
+        account.winner_index = 2;
+        assert_eq!(health_issues(&account).len(), 3);
+    }
+
+    // The quorum is met exactly at the threshold, and never without a recorded supply
+    #[test]
+    fn quorum_met_compares_votes_with_the_supply() {
+        assert!(!quorum_met(399, 1_000, 4_000));
+        assert!(quorum_met(400, 1_000, 4_000));
+        assert!(quorum_met(u64::MAX, u64::MAX, 10_000));
+        assert!(!quorum_met(10, 0, 4_000));
+    }
+
+    // Only a single signature by the voter of the exact message, held in the ed25519
+    // instruction itself, authorizes a registration
+    #[test]
+    fn ed25519_verifies_checks_signer_signature_and_message() {
+        let voter = Pubkey::new_unique();
+        let signature = [7u8; 64];
+        let message = registration_message(&Pubkey::new_unique(), &voter);
+
+        // The layout the ed25519 program (and web3.js) uses for one signature
+        let build = |signer: &Pubkey, message: &[u8]| {
+            let mut data = vec![1, 0];
+            for field in [48u16, u16::MAX, 16, u16::MAX, 112, message.len() as u16, u16::MAX] {
+                data.extend_from_slice(&field.to_le_bytes());
+            }
+            data.extend_from_slice(signer.as_ref());
+            data.extend_from_slice(&signature);
+            data.extend_from_slice(message);
+            data
+        };
+
+        assert!(ed25519_verifies(&build(&voter, &message), &voter, &signature, &message));
+        assert!(!ed25519_verifies(&build(&voter, &message), &voter, &[8u8; 64], &message));
+        assert!(!ed25519_verifies(
+            &build(&Pubkey::new_unique(), &message),
+            &voter,
+            &signature,
+            &message
+        ));
+        assert!(!ed25519_verifies(&build(&voter, b"other"), &voter, &signature, &message));
+        assert!(!ed25519_verifies(&[1, 0], &voter, &signature, &message));
+    }
+
+    // A vote can be undone up to and including undo_window seconds after it was cast
+    #[test]
+    fn undo_window_open_until_the_window_ends() {
+        assert!(undo_window_open(100, 30, 100));
+        assert!(undo_window_open(100, 30, 130));
+        assert!(!undo_window_open(100, 30, 131));
+        assert!(!undo_window_open(100, 0, 100));
+    }
+
+    // The results hash covers the names and counts in their Borsh encoding
+    #[test]
+    fn results_hash_covers_candidates_and_votes() {
+        let candidates = vec!["Alice".to_string(), "Bob".to_string()];
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for name in &candidates {
+            data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+        }
+        data.extend_from_slice(&2u32.to_le_bytes());
+        for votes in [3u64, 1] {
+            data.extend_from_slice(&votes.to_le_bytes());
+        }
+
+        let archived = results_hash(&candidates, &[3, 1]).unwrap();
+        assert_eq!(archived, hash(&data).to_bytes());
+        assert_ne!(archived, results_hash(&candidates, &[1, 3]).unwrap());
+    }
+
+    // A ballot's space matches its serialized size, at most the limits allow
+    #[test]
+    fn ballot_space_matches_the_serialized_ballot() {
+        let options = vec!["o".repeat(MAX_CANDIDATE_NAME_LEN); MAX_BALLOT_OPTIONS];
+        let ballot = Ballot {
+            question: "q".repeat(MAX_QUESTION_LEN),
+            votes: vec![u64::MAX; options.len()],
+            options,
+        };
+        assert_eq!(
+            ballot.try_to_vec().unwrap().len(),
+            Ballot::space(&ballot.question, &ballot.options)
+        );
+        assert!(MAX_BALLOTS <= u32::BITS as usize);
+    }
+
+    // Only an account that doesn't hold an election yet may be initialized
+    #[test]
+    fn require_uninitialized_rejects_initialized_accounts() {
+        let mut account = blank_voting_account();
+        assert!(require_uninitialized(&account).is_ok());
+        account.is_initialized = true;
+        assert_eq!(
+            require_uninitialized(&account).unwrap_err(),
+            ErrorCode::AlreadyInitialized.into()
+        );
+    }
+
+    // A checkpoint's votes hash covers the serialized votes vector, in order
+    #[test]
+    fn votes_hash_covers_the_serialized_votes() {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        for votes in [5u64, 0, 2] {
+            data.extend_from_slice(&votes.to_le_bytes());
+        }
+
+        let checkpointed = votes_hash(&[5, 0, 2]).unwrap();
+        assert_eq!(checkpointed, hash(&data).to_bytes());
+        assert_ne!(checkpointed, votes_hash(&[5, 2, 0]).unwrap());
+    }
+
+    // Renaming grows the account by the extra bytes of each name that changes
+    #[test]
+    fn rename_growth_counts_each_renamed_name() {
+        let mut account = blank_voting_account();
+        account.candidates = vec!["Al".to_string(), "Bob".to_string()];
+        account.display_names = vec!["Al".to_string(), "Robert".to_string()];
+
+        // A defaulted display name follows the candidate name
+        assert_eq!(rename_growth(&account, 0, "Alice"), 6);
+        // A custom display name is left alone
+        assert_eq!(rename_growth(&account, 1, "Bobby"), 2);
+        // Shorter names and unknown candidates need no space
+        assert_eq!(rename_growth(&account, 1, "B"), 0);
+        assert_eq!(rename_growth(&account, 2, "Carol"), 0);
+    }
+
+    // Votes are refused once any per-candidate list disagrees with candidates
+    #[test]
+    fn require_consistent_candidates_catches_mismatched_lists() {
+        let mut account = blank_voting_account();
+        account.candidates = vec!["Alice".to_string(), "Bob".to_string()];
+        account.votes = vec![0, 0];
+        fill_migrated_defaults(&mut account);
+        assert!(require_consistent_candidates(&account).is_ok());
+
+        account.votes.pop();
+        assert_eq!(
+            require_consistent_candidates(&account).unwrap_err(),
+            ErrorCode::CorruptedState.into()
+        );
+
+        account.votes.push(0);
+        account.disqualified.push(false);
+        assert!(require_consistent_candidates(&account).is_err());
+    }
+
+    // The outcome follows the tally and the election's rules, with no winner for no votes
+    #[test]
+    fn decide_outcome_applies_the_election_rules() {
+        let mut account = blank_voting_account();
+        account.candidates = vec!["Alice".to_string(), "Bob".to_string()];
+        account.votes = vec![0, 0];
+        fill_migrated_defaults(&mut account);
+        account.tie_break = TieBreak::LowestIndex;
+        assert_eq!(decide_outcome(&account), (NO_WINNER, false));
+
+        account.votes = vec![2, 2];
+        account.total_votes = 4;
+        assert_eq!(decide_outcome(&account), (0, true));
+
+        account.votes = vec![1, 3];
+        assert_eq!(decide_outcome(&account), (1, false));
+        account.min_victory_margin = 3;
+        assert_eq!(decide_outcome(&account), (NO_WINNER, false));
+    }
+
+    // Packed counts are a u32 length and u64 counts, matching the Borsh encoding
+    #[test]
+    fn pack_votes_lays_out_the_counts() {
+        let votes = [7u64, 0, u64::MAX];
+        let packed = pack_votes(&votes);
+        assert_eq!(packed.len(), 4 + 8 * 3);
+        assert_eq!(&packed[..4], &3u32.to_le_bytes());
+        assert_eq!(&packed[4..12], &7u64.to_le_bytes());
+        assert_eq!(&packed[20..], &u64::MAX.to_le_bytes());
+        assert_eq!(packed, votes.to_vec().try_to_vec().unwrap());
+        assert_eq!(pack_votes(&[]), 0u32.to_le_bytes().to_vec());
+    }
+
+    // The original has_voted-only account decodes with every later field at its default
+    #[test]
+    fn decode_legacy_user_account_fills_new_fields() {
+        let mut data = <UserAccount as anchor_lang::Discriminator>::DISCRIMINATOR.to_vec();
+        data.push(1);
+
+        let migrated = decode_legacy_user_account(&data).unwrap();
+        assert!(migrated.has_voted);
+        assert_eq!(migrated.voted_for, None);
+        assert_eq!(migrated.round, 0);
+        assert_eq!(migrated.election, Pubkey::default());
+        assert!(migrated.eligible_candidates.is_empty());
+
+        // Other accounts are still told apart by their discriminator
+        let voting = <VotingAccount as anchor_lang::Discriminator>::DISCRIMINATOR;
+        assert!(decode_legacy_user_account(&voting).is_err());
+    }
+
+    // The original deployed layout is read as u32 counts and has_ended, not as a prefix
+    #[test]
+    fn migration_decodes_the_baseline_layout() {
+        let discriminator = <VotingAccount as anchor_lang::Discriminator>::DISCRIMINATOR;
+        let mut data = discriminator.to_vec();
+        vec!["Alice".to_string(), "Bob".to_string(), "Charlie".to_string()]
+            .serialize(&mut data)
+            .unwrap();
+        vec![2u32, 5, 1].serialize(&mut data).unwrap();
+        true.serialize(&mut data).unwrap();
+        data.resize(BASELINE_VOTING_ACCOUNT_SPACE, 0);
+
+        let mut migrated = decode_legacy_voting_account(&data).unwrap();
+        assert_eq!(migrated.version, 0);
+        assert_eq!(migrated.votes, vec![2, 5, 1]);
+        assert!(migrated.status == VotingStatus::Ended);
+        assert_eq!(migrated.winner_index, NO_WINNER);
+        fill_migrated_defaults(&mut migrated);
+        assert_eq!(migrated.version, VOTING_ACCOUNT_VERSION);
+        assert_eq!(migrated.display_names, migrated.candidates);
+        assert_eq!(migrated.disabled, vec![false; 3]);
+        assert_eq!(migrated.candidate_ids, vec![0, 1, 2]);
+        assert_eq!(migrated.next_candidate_id, 3);
+        assert_eq!(migrated.total_votes, 8);
+        assert_eq!(decide_outcome(&migrated), (1, false));
+
+        // An election still open stays active
+        data[8 + 4 + 9 + 7 + 11 + 4 + 12] = 0;
+        let migrated = decode_legacy_voting_account(&data).unwrap();
+        assert!(migrated.status == VotingStatus::Active);
+    }
+
+    // A registration period pushes the start back to its end, and the deadline must
+    // follow the pushed-back start
+    #[test]
+    fn check_schedule_waits_for_the_registration_period() {
+        let now = 1_700_000_000;
+        assert_eq!(check_schedule(now, 0, 0, 600).unwrap(), now + 600);
+        assert_eq!(check_schedule(now, now + 900, 0, 600).unwrap(), now + 900);
+        assert_eq!(check_schedule(now, now - 60, 0, 600).unwrap(), now + 600);
+        assert_eq!(
+            check_schedule(now, 0, now + 600, 600).unwrap_err(),
+            ErrorCode::InvalidEndTime.into()
+        );
+        assert_eq!(
+            check_schedule(now, 0, 0, -1).unwrap_err(),
+            ErrorCode::InvalidRegistrationPeriod.into()
+        );
+    }
+
+    // Added candidates only pay for their names, beyond the slots already reserved
+    #[test]
+    fn candidates_growth_charges_only_the_names() {
+        let names = vec!["Dave".to_string(), "Eve".to_string()];
+        assert_eq!(candidates_growth(0, &names), 2 * (4 + 4) + 2 * (4 + 3));
+        assert_eq!(candidates_growth(1, &names), 2 * (4 + 3));
+        assert_eq!(candidates_growth(2, &names), 0);
+        assert_eq!(CANDIDATE_SLOT_SPACE, 2 * (4 + MAX_CANDIDATE_NAME_LEN));
+    }
+}
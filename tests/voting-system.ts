@@ -3,10 +3,21 @@ import * as anchor from "@project-serum/anchor";
 import { Program } from "@project-serum/anchor";
 // Import the generated TypeScript types for our program
 import { VotingSystem } from "../target/types/voting_system";
+import { MockCaller } from "../target/types/mock_caller";
 // Import Solana web3 libraries for working with accounts, keypairs, etc.
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  Ed25519Program,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from "@solana/web3.js";
+// Import SPL Token helpers for creating mints and token accounts
+import { createMint, createAccount, mintTo } from "@solana/spl-token";
 // Import testing assertion library
 import { expect } from "chai";
+// Import Node's crypto module for hashing voter keys
+import { createHash } from "crypto";
 
 /**
  * Test suite for the voting-system program
@@ -25,22 +36,168 @@ describe("voting-system", () => {
   // The type annotation ensures TypeScript knows the structure of our program
   const program = anchor.workspace.VotingSystem as Program<VotingSystem>;
 
+  // A test-only program that votes through vote_cpi, standing in for a governance program
+  const mockCaller = anchor.workspace.MockCaller as Program<MockCaller>;
+
   // === ACCOUNT GENERATION ===
 
   // Generate a new keypair for the voting account
   // This will be used to store voting data on-chain
   const votingAccount = anchor.web3.Keypair.generate();
 
-  // Generate keypairs for test user accounts
-  // These represent different voters in our system
-  const userAccount1 = anchor.web3.Keypair.generate();
-  const userAccount2 = anchor.web3.Keypair.generate();
+  // Generate keypairs for test voters
+  // Each voter gets a user account PDA derived from the election and their key
+  const voter1 = anchor.web3.Keypair.generate();
+  const voter2 = anchor.web3.Keypair.generate();
 
   // === TEST DATA ===
 
   // Define candidate names for our voting system
   const candidates = ["Alice", "Bob", "Charlie"];
 
+  // Sentinel the program stores in winnerIndex when there is no single winner
+  const NO_WINNER = 4294967295;
+
+  // Layout version the program stamps on voting accounts it writes (VOTING_ACCOUNT_VERSION)
+  const VOTING_ACCOUNT_VERSION = 22;
+
+  // The default wallet's keypair, used to pay for SPL Token setup
+  const payer = (provider.wallet as anchor.Wallet).payer;
+
+  // Vote counts are u64 on chain; read them as plain numbers
+  const voteCounts = (account: { votes: anchor.BN[] }) =>
+    account.votes.map((count) => count.toNumber());
+
+  // Build an election config with every optional feature disabled
+  // Individual tests override only the fields they exercise
+  const defaultConfig = (overrides = {}) => ({
+    voteMint: null,
+    maxWeightPerVoter: null,
+    weightCapMode: { clamp: {} },
+    closeDelay: new anchor.BN(0),
+    snapshotSlot: new anchor.BN(0),
+    voteFee: new anchor.BN(0),
+    minSolBalance: null,
+    tags: [],
+    startAsDraft: false,
+    startTime: new anchor.BN(0),
+    endTime: new anchor.BN(0),
+    minVictoryMargin: 0,
+    rateLimitVotes: false,
+    registrationMode: { open: {} },
+    minWinningVotes: 0,
+    resultsHidden: false,
+    displayNames: [],
+    locale: "",
+    decayRateBpsPerDay: 0,
+    maxVotesPerSlot: 0,
+    tieBreak: { none: {} },
+    publicRoster: false,
+    disputePeriodSecs: new anchor.BN(0),
+    cpiCaller: null,
+    reopenWindow: new anchor.BN(0),
+    pointsBudget: 0,
+    quorumBps: 0,
+    undoWindow: new anchor.BN(0),
+    requireReason: false,
+    allowRevote: false,
+    title: "",
+    description: "",
+    rollupParent: null,
+    minDecayWeightBps: 0,
+    registrationPeriod: new anchor.BN(0),
+    ...overrides,
+  });
+
+  // Create a fresh election owned by the default wallet and return its address
+  const createElection = async (config = defaultConfig(), names = candidates) => {
+    const election = anchor.web3.Keypair.generate();
+    await program.methods
+      .initialize(names, config)
+      .accounts({
+        votingAccount: election.publicKey,
+        user: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([election])
+      .rpc();
+    return election.publicKey;
+  };
+
+  // Create a keypair funded with the given amount of SOL
+  const createFundedKeypair = async (sol = 2) => {
+    const keypair = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        keypair.publicKey,
+        sol * anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    return keypair;
+  };
+
+  // Size of a user account (8 byte discriminator + UserAccount::INIT_SPACE), used to fund
+  // voters with exact balances
+  const USER_ACCOUNT_SPACE = 240;
+
+  // Derive a voter's user account PDA for an election
+  const userAccountFor = (election: PublicKey, voter: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("user"), election.toBuffer(), voter.toBuffer()],
+      program.programId
+    )[0];
+
+  // Register the voter for an election and return their user account PDA
+  // Weighted elections snapshot the balance of the voter's token account
+  const registerVoter = async (
+    election: PublicKey,
+    voter: Keypair = payer,
+    tokenAccount: PublicKey | null = null
+  ) => {
+    await program.methods
+      .initializeUserAccount()
+      .accounts({
+        votingAccount: election,
+        userAccount: userAccountFor(election, voter.publicKey),
+        user: voter.publicKey,
+        authority: null,
+        systemProgram: SystemProgram.programId,
+        tokenAccount,
+      })
+      .signers([voter])
+      .rpc();
+    return userAccountFor(election, voter.publicKey);
+  };
+
+  // Create a funded voter already registered for the election
+  // Each wallet has one user account per election, so every ballot needs its own voter
+  const createVoter = async (election: PublicKey) => {
+    const voter = await createFundedKeypair(1);
+    await registerVoter(election, voter);
+    return voter;
+  };
+
+  // Cast a plain vote from the voter through their user account PDA
+  // Candidates are chosen by ID, which matches the ballot position until the ballot changes
+  const castVote = (
+    election: PublicKey,
+    voter: Keypair,
+    candidateId: number,
+    round = 0
+  ) =>
+    program.methods
+      .vote(new anchor.BN(candidateId), new anchor.BN(round), null)
+      .accounts({
+        votingAccount: election,
+        userAccount: userAccountFor(election, voter.publicKey),
+        user: voter.publicKey,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      })
+      .signers([voter])
+      .rpc();
+
   // === TEST CASES ===
 
   /**
@@ -55,7 +212,7 @@ describe("voting-system", () => {
   it("Initializes the voting system", async () => {
     // Call the initialize instruction with our candidate list
     await program.methods
-      .initialize(candidates)
+      .initialize(candidates, defaultConfig())
       .accounts({
         votingAccount: votingAccount.publicKey,
         user: provider.wallet.publicKey, // The default wallet pays for account creation
@@ -77,12 +234,12 @@ describe("voting-system", () => {
     // Verify the votes array was created with the correct length
     expect(account.votes.length).to.equal(candidates.length);
 
-    // Verify the voting state is initially set to not ended
-    expect(account.hasEnded).to.be.false;
+    // Verify the election starts active (accepting votes)
+    expect(account.status).to.deep.equal({ active: {} });
 
     // Check that all votes are initialized to 0
     account.votes.forEach((vote) => {
-      expect(vote).to.equal(0);
+      expect(vote.toNumber()).to.equal(0);
     });
   });
 
@@ -96,35 +253,53 @@ describe("voting-system", () => {
    * - User state changes to "has voted" after voting
    */
   it("Initializes user accounts and allows users to vote", async () => {
+    // Fund both voters so they can pay for their user accounts
+    for (const voter of [voter1, voter2]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          voter.publicKey,
+          anchor.web3.LAMPORTS_PER_SOL
+        )
+      );
+    }
+
     // === USER 1 SETUP ===
 
+    // Derive the first voter's user account PDA for this election
+    const userAccount1 = userAccountFor(votingAccount.publicKey, voter1.publicKey);
+
     // Create and initialize the first user account
     await program.methods
-      .initializeUser() // Call the initializeUser instruction
+      .initializeUserAccount() // Call the initializeUserAccount instruction
       .accounts({
-        userAccount: userAccount1.publicKey, // The account to initialize
-        user: provider.wallet.publicKey, // The payer for the transaction
+        votingAccount: votingAccount.publicKey, // The election the user registers for
+        userAccount: userAccount1, // The PDA to initialize
+        user: voter1.publicKey, // The voter pays for their own account
+        authority: null, // Only needed for AuthorityOnly elections
         systemProgram: SystemProgram.programId, // For account creation
+        tokenAccount: null, // Only needed for weighted elections
       })
-      .signers([userAccount1]) // The new account must sign to be created
+      .signers([voter1]) // The voter must sign to register
       .rpc();
 
     // Verify user account was initialized with hasVoted = false
-    const user1Data = await program.account.userAccount.fetch(
-      userAccount1.publicKey
-    );
+    const user1Data = await program.account.userAccount.fetch(userAccount1);
     expect(user1Data.hasVoted).to.be.false;
 
     // === USER 1 VOTING ===
 
     // User 1 votes for candidate 0 (Alice)
     await program.methods
-      .vote(0) // Vote for the first candidate (index 0)
+      .vote(new anchor.BN(0), new anchor.BN(0), null) // Vote for the first candidate (index 0)
       .accounts({
         votingAccount: votingAccount.publicKey, // The main voting data account
-        userAccount: userAccount1.publicKey, // User's account to mark as voted
-        user: provider.wallet.publicKey, // User must sign the transaction
+        userAccount: userAccount1, // User's account to mark as voted
+        user: voter1.publicKey, // User must sign the transaction
+        authority: provider.wallet.publicKey, // The election authority receives any vote fee
+        systemProgram: SystemProgram.programId, // For transferring the vote fee
+        roster: null,
       })
+      .signers([voter1])
       .rpc();
 
     // === VERIFICATION AFTER USER 1 VOTES ===
@@ -133,12 +308,10 @@ describe("voting-system", () => {
     const votingData = await program.account.votingAccount.fetch(
       votingAccount.publicKey
     );
-    const userData = await program.account.userAccount.fetch(
-      userAccount1.publicKey
-    );
+    const userData = await program.account.userAccount.fetch(userAccount1);
 
     // Verify Alice received 1 vote
-    expect(votingData.votes[0]).to.equal(1);
+    expect(voteCounts(votingData)[0]).to.equal(1);
 
     // Verify user is now marked as having voted
     expect(userData.hasVoted).to.be.true;
@@ -146,26 +319,22 @@ describe("voting-system", () => {
     // === USER 2 SETUP ===
 
     // Create and initialize the second user account
-    await program.methods
-      .initializeUser()
-      .accounts({
-        userAccount: userAccount2.publicKey,
-        user: provider.wallet.publicKey,
-        systemProgram: SystemProgram.programId,
-      })
-      .signers([userAccount2])
-      .rpc();
+    const userAccount2 = await registerVoter(votingAccount.publicKey, voter2);
 
     // === USER 2 VOTING ===
 
     // User 2 votes for candidate 1 (Bob)
     await program.methods
-      .vote(1) // Vote for the second candidate (index 1)
+      .vote(new anchor.BN(1), new anchor.BN(0), null) // Vote for the second candidate (index 1)
       .accounts({
         votingAccount: votingAccount.publicKey,
-        userAccount: userAccount2.publicKey,
-        user: provider.wallet.publicKey,
+        userAccount: userAccount2,
+        user: voter2.publicKey,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
       })
+      .signers([voter2])
       .rpc();
 
     // === VERIFICATION AFTER USER 2 VOTES ===
@@ -174,9 +343,9 @@ describe("voting-system", () => {
     const updatedVotingData = await program.account.votingAccount.fetch(
       votingAccount.publicKey
     );
-    expect(updatedVotingData.votes[0]).to.equal(1); // Alice still has 1 vote
-    expect(updatedVotingData.votes[1]).to.equal(1); // Bob now has 1 vote
-    expect(updatedVotingData.votes[2]).to.equal(0); // Charlie has 0 votes
+    expect(voteCounts(updatedVotingData)[0]).to.equal(1); // Alice still has 1 vote
+    expect(voteCounts(updatedVotingData)[1]).to.equal(1); // Bob now has 1 vote
+    expect(voteCounts(updatedVotingData)[2]).to.equal(0); // Charlie has 0 votes
   });
 
   /**
@@ -188,14 +357,7 @@ describe("voting-system", () => {
   it("Prevents a user from voting twice", async () => {
     try {
       // User 1 tries to vote again (for Charlie this time)
-      await program.methods
-        .vote(2)
-        .accounts({
-          votingAccount: votingAccount.publicKey,
-          userAccount: userAccount1.publicKey,
-          user: provider.wallet.publicKey,
-        })
-        .rpc();
+      await castVote(votingAccount.publicKey, voter1, 2);
 
       // If we reach this point, the test failed because
       // the transaction should have been rejected
@@ -214,39 +376,31 @@ describe("voting-system", () => {
    * are rejected with the appropriate error
    */
   it("Prevents voting for an invalid candidate", async () => {
-    // Create a new user account for this test
-    const userAccount3 = anchor.web3.Keypair.generate();
-
-    // Initialize the new user account
-    await program.methods
-      .initializeUser()
-      .accounts({
-        userAccount: userAccount3.publicKey,
-        user: provider.wallet.publicKey,
-        systemProgram: SystemProgram.programId,
-      })
-      .signers([userAccount3])
-      .rpc();
+    // Register the default wallet as a third voter for this test
+    const userAccount3 = await registerVoter(votingAccount.publicKey);
 
     try {
-      // Try to vote for a non-existent candidate (index 10)
+      // Try to vote for a non-existent candidate (ID 10)
       await program.methods
-        .vote(10) // This index is out of bounds (we only have 3 candidates)
+        .vote(new anchor.BN(10), new anchor.BN(0), null) // No candidate has this ID (we only have 3 candidates)
         .accounts({
           votingAccount: votingAccount.publicKey,
-          userAccount: userAccount3.publicKey,
+          userAccount: userAccount3,
           user: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
         })
         .rpc();
 
       // If we reach this point, the test failed
       expect.fail(
-        "Expected transaction to fail due to invalid candidate index"
+        "Expected transaction to fail due to an unknown candidate ID"
       );
     } catch (error) {
       // Check for the appropriate error message
       const errorMessage = error.toString();
-      expect(errorMessage).to.include("InvalidCandidate");
+      expect(errorMessage).to.include("CandidateNotFound");
     }
   });
 
@@ -262,6 +416,7 @@ describe("voting-system", () => {
       .endVoting()
       .accounts({
         votingAccount: votingAccount.publicKey,
+        authority: provider.wallet.publicKey,
       })
       .rpc();
 
@@ -269,9 +424,5708 @@ describe("voting-system", () => {
     const votingData = await program.account.votingAccount.fetch(
       votingAccount.publicKey
     );
-    expect(votingData.hasEnded).to.be.true;
+    expect(votingData.status).to.deep.equal({ ended: {} });
 
     // Final verification of vote counts
     // At this point, Alice has 1 vote, Bob has 1 vote, and Charlie has 0 votes
+    // so the outcome is recorded as a tie with no single winner
+    expect(votingData.isTie).to.be.true;
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+  });
+
+  /**
+   * Test Case 6: Record a deliberate tie
+   *
+   * This test verifies that end_voting:
+   * - Flags a tie when the top candidates share the same count
+   * - Stores the NO_WINNER sentinel as the winner index
+   * - Emits both values in the VotingEnded event
+   */
+  it("Flags a tie between the top candidates when voting ends", async () => {
+    // Create a fresh election so the tally is fully under our control
+    const tiedVotingAccount = anchor.web3.Keypair.generate();
+    await program.methods
+      .initialize(candidates, defaultConfig())
+      .accounts({
+        votingAccount: tiedVotingAccount.publicKey,
+        user: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([tiedVotingAccount])
+      .rpc();
+
+    // Cast one vote for Bob and one for Charlie, leaving Alice at zero
+    for (const candidateIndex of [1, 2]) {
+      const voter = await createVoter(tiedVotingAccount.publicKey);
+      await castVote(tiedVotingAccount.publicKey, voter, candidateIndex);
+    }
+
+    // Listen for the VotingEnded event before ending the election
+    let endedEvent = null;
+    const listener = program.addEventListener("VotingEnded", (event) => {
+      endedEvent = event;
+    });
+
+    await program.methods
+      .endVoting()
+      .accounts({
+        votingAccount: tiedVotingAccount.publicKey,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // Verify the stored outcome
+    const votingData = await program.account.votingAccount.fetch(
+      tiedVotingAccount.publicKey
+    );
+    expect(votingData.isTie).to.be.true;
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+
+    // Give the listener a moment to receive the event, then verify it
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(endedEvent).to.not.be.null;
+    expect(endedEvent.isTie).to.be.true;
+    expect(endedEvent.winnerIndex).to.equal(NO_WINNER);
+  });
+
+  /**
+   * Helper for the weighted voting tests
+   *
+   * Creates an election weighted by a fresh mint with the given cap settings
+   * (plus any other config overrides), then a voter holding `balance` tokens
+   * with an initialized user account
+   */
+  const setupWeightedElection = async (
+    maxWeightPerVoter: number,
+    weightCapMode: object,
+    balance: number,
+    overrides = {}
+  ) => {
+    // Create the mint whose balances weight the votes
+    const voteMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      0
+    );
+
+    // Create the election with weighted voting enabled
+    const electionAccount = anchor.web3.Keypair.generate();
+    await program.methods
+      .initialize(
+        candidates,
+        defaultConfig({
+          voteMint,
+          maxWeightPerVoter: new anchor.BN(maxWeightPerVoter),
+          weightCapMode,
+          ...overrides,
+        })
+      )
+      .accounts({
+        votingAccount: electionAccount.publicKey,
+        user: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([electionAccount])
+      .rpc();
+
+    // Give the voter (the default wallet) a funded token account
+    const tokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      provider.wallet.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      voteMint,
+      tokenAccount,
+      payer,
+      balance
+    );
+
+    // Register the voter's user account, snapshotting the balance
+    const voterAccount = await registerVoter(electionAccount.publicKey, payer, tokenAccount);
+
+    return { electionAccount, voteMint, tokenAccount, voterAccount };
+  };
+
+  /**
+   * Test Case 7: Clamp weighted votes at the per-voter cap
+   *
+   * This test verifies that in clamp mode a voter holding more
+   * tokens than max_weight_per_voter only contributes the cap
+   */
+  it("Clamps a weighted vote to the per-voter cap", async () => {
+    const { electionAccount, tokenAccount, voterAccount } =
+      await setupWeightedElection(100, { clamp: {} }, 250);
+
+    // Vote for Alice with a balance of 250 tokens
+    await program.methods
+      .voteWeighted(0, new anchor.BN(0))
+      .accounts({
+        votingAccount: electionAccount.publicKey,
+        userAccount: voterAccount,
+        user: provider.wallet.publicKey,
+        tokenAccount,
+        tokenSnapshot: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      })
+      .rpc();
+
+    // Only the capped weight of 100 is counted
+    const votingData = await program.account.votingAccount.fetch(
+      electionAccount.publicKey
+    );
+    expect(voteCounts(votingData)[0]).to.equal(100);
+  });
+
+  /**
+   * Test Case 8: Reject weighted votes above the per-voter cap
+   *
+   * This test verifies that in reject mode a vote whose weight
+   * exceeds max_weight_per_voter fails with WeightExceedsCap
+   */
+  it("Rejects a weighted vote above the per-voter cap", async () => {
+    const { electionAccount, tokenAccount, voterAccount } =
+      await setupWeightedElection(100, { reject: {} }, 250);
+
+    try {
+      await program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: electionAccount.publicKey,
+          userAccount: voterAccount,
+          user: provider.wallet.publicKey,
+          tokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .rpc();
+
+      expect.fail("Expected transaction to fail due to the weight cap");
+    } catch (error) {
+      expect(error.toString()).to.include("WeightExceedsCap");
+    }
+
+    // Nothing was counted for the rejected vote
+    const votingData = await program.account.votingAccount.fetch(
+      electionAccount.publicKey
+    );
+    expect(voteCounts(votingData)[0]).to.equal(0);
+  });
+
+  /**
+   * Test Case 9: Enforce the decommission window before closing
+   *
+   * This test verifies that close_voting:
+   * - Fails with CloseTooSoon right after voting ends
+   * - Succeeds once the close delay has passed, deleting the account
+   */
+  it("Only closes the voting account after the close delay", async () => {
+    // Create an election with a two second decommission window
+    const closingAccount = anchor.web3.Keypair.generate();
+    await program.methods
+      .initialize(candidates, defaultConfig({ closeDelay: new anchor.BN(2) }))
+      .accounts({
+        votingAccount: closingAccount.publicKey,
+        user: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([closingAccount])
+      .rpc();
+
+    await program.methods
+      .endVoting()
+      .accounts({
+        votingAccount: closingAccount.publicKey,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // Closing immediately after ending is rejected
+    try {
+      await program.methods
+        .closeVoting()
+        .accounts({
+          votingAccount: closingAccount.publicKey,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      expect.fail("Expected transaction to fail due to the close delay");
+    } catch (error) {
+      expect(error.toString()).to.include("CloseTooSoon");
+    }
+
+    // Wait out the delay, then close successfully
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .closeVoting()
+      .accounts({
+        votingAccount: closingAccount.publicKey,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // The account no longer exists
+    const closed = await program.account.votingAccount.fetchNullable(
+      closingAccount.publicKey
+    );
+    expect(closed).to.be.null;
+  });
+
+  /**
+   * Test Case 10: Snapshot-based weighted voting
+   *
+   * This test verifies that when an election has a snapshot slot:
+   * - A balance recorded before the snapshot slot can be used to vote
+   * - A token account funded after the snapshot slot cannot be recorded
+   * - A weighted vote without a recorded balance is rejected
+   */
+  it("Rejects weighted votes from a token account funded after the snapshot", async () => {
+    // Create the vote mint and a token account funded before the snapshot
+    const voteMint = await createMint(
+      provider.connection,
+      payer,
+      payer.publicKey,
+      null,
+      0
+    );
+    const earlyTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      provider.wallet.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      voteMint,
+      earlyTokenAccount,
+      payer,
+      50
+    );
+
+    // Create an election whose snapshot closes a few slots from now
+    const snapshotSlot = (await provider.connection.getSlot()) + 20;
+    const snapshotElection = anchor.web3.Keypair.generate();
+    await program.methods
+      .initialize(
+        candidates,
+        defaultConfig({ voteMint, snapshotSlot: new anchor.BN(snapshotSlot) })
+      )
+      .accounts({
+        votingAccount: snapshotElection.publicKey,
+        user: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([snapshotElection])
+      .rpc();
+
+    // Derive the snapshot PDA for a token account in this election
+    const snapshotPda = (tokenAccount: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("snapshot"),
+          snapshotElection.publicKey.toBuffer(),
+          tokenAccount.toBuffer(),
+        ],
+        program.programId
+      )[0];
+
+    // Record the early balance while the snapshot is still open
+    await program.methods
+      .recordSnapshot()
+      .accounts({
+        votingAccount: snapshotElection.publicKey,
+        tokenAccount: earlyTokenAccount,
+        tokenSnapshot: snapshotPda(earlyTokenAccount),
+        user: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Wait until the snapshot slot has passed
+    while ((await provider.connection.getSlot()) <= snapshotSlot) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+
+    // Fund a brand new token account after the snapshot
+    const freshTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      provider.wallet.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      voteMint,
+      freshTokenAccount,
+      payer,
+      500
+    );
+
+    // The fresh account can no longer be recorded
+    try {
+      await program.methods
+        .recordSnapshot()
+        .accounts({
+          votingAccount: snapshotElection.publicKey,
+          tokenAccount: freshTokenAccount,
+          tokenSnapshot: snapshotPda(freshTokenAccount),
+          user: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      expect.fail("Expected transaction to fail after the snapshot slot");
+    } catch (error) {
+      expect(error.toString()).to.include("SnapshotSlotPassed");
+    }
+
+    // Voting with the fresh account and no snapshot is rejected
+    const voterAccount = await registerVoter(
+      snapshotElection.publicKey,
+      payer,
+      earlyTokenAccount
+    );
+    try {
+      await program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: snapshotElection.publicKey,
+          userAccount: voterAccount,
+          user: provider.wallet.publicKey,
+          tokenAccount: freshTokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .rpc();
+
+      expect.fail("Expected transaction to fail without a snapshot");
+    } catch (error) {
+      expect(error.toString()).to.include("SnapshotRequired");
+    }
+
+    // The rejected vote left the user account unused, so the same voter
+    // can still vote with the early account's recorded balance
+    await program.methods
+      .voteWeighted(1, new anchor.BN(0))
+      .accounts({
+        votingAccount: snapshotElection.publicKey,
+        userAccount: voterAccount,
+        user: provider.wallet.publicKey,
+        tokenAccount: earlyTokenAccount,
+        tokenSnapshot: snapshotPda(earlyTokenAccount),
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(
+      snapshotElection.publicKey
+    );
+    expect(voteCounts(votingData)[0]).to.equal(0);
+    expect(voteCounts(votingData)[1]).to.equal(50);
+  });
+
+  /**
+   * Test Case 11: Query a user's voting state
+   *
+   * This test verifies that has_user_voted returns the user's
+   * state before and after voting, including the chosen candidate
+   */
+  it("Reports whether a user has voted and for whom", async () => {
+    const election = await createElection();
+    const userAccount = await registerVoter(election);
+
+    // Before voting the user has no recorded choice
+    const before = await program.methods
+      .hasUserVoted()
+      .accounts({ userAccount })
+      .view();
+    expect(before.hasVoted).to.be.false;
+    expect(before.votedFor).to.be.null;
+
+    // After voting the returned value tracks the choice
+    await castVote(election, payer, 2);
+    const after = await program.methods
+      .hasUserVoted()
+      .accounts({ userAccount })
+      .view();
+    expect(after.hasVoted).to.be.true;
+    expect(after.votedFor).to.equal(2);
+  });
+
+  /**
+   * Test Case 12: Multisig governance authority
+   *
+   * This test verifies that with a 2-of-3 governance account as authority:
+   * - An admin action co-signed by only one listed signer fails with ThresholdNotMet
+   * - An admin action co-signed by two listed signers succeeds
+   */
+  it("Requires 2-of-3 governance signers for authority actions", async () => {
+    const election = await createElection();
+
+    // Create the 2-of-3 governance account
+    const governance = anchor.web3.Keypair.generate();
+    const signers = [0, 1, 2].map(() => anchor.web3.Keypair.generate());
+    await program.methods
+      .createGovernance(
+        signers.map((signer) => signer.publicKey),
+        2
+      )
+      .accounts({
+        governance: governance.publicKey,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([governance])
+      .rpc();
+
+    // Hand the election over to the governance account
+    await program.methods
+      .transferAuthority(governance.publicKey)
+      .accounts({
+        votingAccount: election,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // The governance account is passed first, followed by the co-signers
+    const governanceAccounts = (coSigners: Keypair[]) => [
+      { pubkey: governance.publicKey, isSigner: false, isWritable: false },
+      ...coSigners.map((signer) => ({
+        pubkey: signer.publicKey,
+        isSigner: true,
+        isWritable: false,
+      })),
+    ];
+
+    // A single signer cannot pause the election
+    try {
+      await program.methods
+        .pauseVoting()
+        .accounts({
+          votingAccount: election,
+          authority: signers[0].publicKey,
+        })
+        .remainingAccounts(governanceAccounts([]))
+        .signers([signers[0]])
+        .rpc();
+
+      expect.fail("Expected transaction to fail with only one signer");
+    } catch (error) {
+      expect(error.toString()).to.include("ThresholdNotMet");
+    }
+
+    // Two signers can end the election
+    await program.methods
+      .endVoting()
+      .accounts({
+        votingAccount: election,
+        authority: signers[0].publicKey,
+      })
+      .remainingAccounts(governanceAccounts([signers[2]]))
+      .signers([signers[0], signers[2]])
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ ended: {} });
+    expect(votingData.authority.toBase58()).to.equal(
+      governance.publicKey.toBase58()
+    );
+  });
+
+  /**
+   * Test Case 13: Per-election vote fee
+   *
+   * This test verifies that:
+   * - A vote transfers the configured fee from the voter to the authority
+   * - A voter who cannot afford the fee is rejected with InsufficientFundsForFee
+   */
+  it("Charges the vote fee and pays it to the authority", async () => {
+    const voteFee = 1_000_000;
+    const election = await createElection(
+      defaultConfig({ voteFee: new anchor.BN(voteFee) })
+    );
+
+    // Hand the election to a dedicated authority so its balance only changes by fees
+    const authority = anchor.web3.Keypair.generate();
+    await program.methods
+      .transferAuthority(authority.publicKey)
+      .accounts({
+        votingAccount: election,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // Vote from a funded voter; the default wallet still pays transaction fees
+    const voter = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        voter.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    const paidUser = await registerVoter(election, voter);
+    await program.methods
+      .vote(new anchor.BN(0), new anchor.BN(0), null)
+      .accounts({
+        votingAccount: election,
+        userAccount: paidUser,
+        user: voter.publicKey,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      })
+      .signers([voter])
+      .rpc();
+
+    // The authority received exactly the fee
+    expect(await provider.connection.getBalance(authority.publicKey)).to.equal(
+      voteFee
+    );
+
+    // A voter left with no lamports after paying for their user account cannot pay the fee
+    const brokeVoter = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        brokeVoter.publicKey,
+        await provider.connection.getMinimumBalanceForRentExemption(
+          USER_ACCOUNT_SPACE
+        )
+      )
+    );
+    const brokeUser = await registerVoter(election, brokeVoter);
+    try {
+      await program.methods
+        .vote(new anchor.BN(1), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount: brokeUser,
+          user: brokeVoter.publicKey,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([brokeVoter])
+        .rpc();
+
+      expect.fail("Expected transaction to fail due to the unpaid fee");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientFundsForFee");
+    }
+  });
+
+  /**
+   * Test Case 14: Disable a candidate mid-election
+   *
+   * This test verifies that after disable_candidate:
+   * - New votes for the candidate fail with CandidateDisabled
+   * - The votes the candidate already received are kept
+   * - Other candidates can still be voted for
+   */
+  it("Rejects votes for a disabled candidate but keeps their votes", async () => {
+    const election = await createElection();
+
+    // Bob receives a vote before withdrawing
+    await castVote(election, await createVoter(election), 1);
+
+    await program.methods
+      .disableCandidate(1)
+      .accounts({
+        votingAccount: election,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    // A new vote for Bob is rejected
+    try {
+      await castVote(election, await createVoter(election), 1);
+      expect.fail("Expected transaction to fail due to the disabled candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateDisabled");
+    }
+
+    // Alice can still receive votes
+    await castVote(election, await createVoter(election), 0);
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.disabled).to.deep.equal([false, true, false]);
+    expect(voteCounts(votingData)[0]).to.equal(1);
+    expect(voteCounts(votingData)[1]).to.equal(1);
+  });
+
+  /**
+   * Test Case 15: Read a single candidate
+   *
+   * This test verifies that get_candidate:
+   * - Emits a CandidateInfo event with the candidate's name and votes
+   * - Fails with InvalidCandidate for an out-of-range index
+   */
+  it("Returns a single candidate's data", async () => {
+    const election = await createElection();
+    await castVote(election, await createVoter(election), 2);
+
+    // Simulate the read and inspect the emitted event
+    const { events } = await program.methods
+      .getCandidate(2)
+      .accounts({ votingAccount: election })
+      .simulate();
+    expect(events[0].name).to.equal("CandidateInfo");
+    expect(events[0].data.index).to.equal(2);
+    expect(events[0].data.name).to.equal("Charlie");
+    expect(events[0].data.votes.toNumber()).to.equal(1);
+
+    // An index past the end of the ballot is rejected
+    try {
+      await program.methods
+        .getCandidate(candidates.length)
+        .accounts({ votingAccount: election })
+        .rpc();
+      expect.fail("Expected transaction to fail due to invalid candidate index");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCandidate");
+    }
+  });
+
+  /**
+   * Test Case 16: Cap elections per authority with a registry
+   *
+   * This test verifies that:
+   * - create_election derives election PDAs from the authority's registry
+   * - Creating past the registry cap fails with ElectionLimitReached
+   * - Closing an election frees a slot for a new one
+   */
+  it("Limits how many elections an authority can create", async () => {
+    // Use a dedicated authority so the registry starts empty
+    const authority = await createFundedKeypair();
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const electionPda = (id: number) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("election"),
+          authority.publicKey.toBuffer(),
+          new anchor.BN(id).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    const createElectionPda = (id: number) =>
+      program.methods
+        .createElection(candidates, defaultConfig())
+        .accounts({
+          registry,
+          votingAccount: electionPda(id),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+    // Allow at most two open elections
+    await program.methods
+      .initializeRegistry(new anchor.BN(2))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    await createElectionPda(0);
+    await createElectionPda(1);
+
+    // The third election hits the cap
+    try {
+      await createElectionPda(2);
+      expect.fail("Expected transaction to fail due to the election limit");
+    } catch (error) {
+      expect(error.toString()).to.include("ElectionLimitReached");
+    }
+
+    // End and close the first election to free a slot
+    await program.methods
+      .endVoting()
+      .accounts({
+        votingAccount: electionPda(0),
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .closeElection()
+      .accounts({
+        votingAccount: electionPda(0),
+        registry,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+
+    // A new election can now be created
+    await createElectionPda(2);
+    const registryData = await program.account.authorityRegistry.fetch(registry);
+    expect(registryData.electionCount.toNumber()).to.equal(2);
+    expect(registryData.nextElectionId.toNumber()).to.equal(3);
+  });
+
+  /**
+   * Test Case 17: SOL-balance-gated voting
+   *
+   * This test verifies the min_sol_balance gate at its boundary:
+   * - A voter holding exactly the minimum can vote
+   * - A voter holding one lamport less is rejected with InsufficientBalance
+   */
+  it("Gates voting on a minimum SOL balance", async () => {
+    const minSolBalance = anchor.web3.LAMPORTS_PER_SOL;
+    const election = await createElection(
+      defaultConfig({ minSolBalance: new anchor.BN(minSolBalance) })
+    );
+
+    // Vote from a voter holding exactly the given lamports
+    // The voter is also funded for their user account's rent; the default wallet
+    // pays the transaction fees, so the balance left after registering is unchanged
+    const userRent = await provider.connection.getMinimumBalanceForRentExemption(
+      USER_ACCOUNT_SPACE
+    );
+    const voteWithBalance = async (lamports: number, candidateIndex: number) => {
+      const voter = anchor.web3.Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(
+          voter.publicKey,
+          lamports + userRent
+        )
+      );
+      await program.methods
+        .vote(new anchor.BN(candidateIndex), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount: await registerVoter(election, voter),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+    };
+
+    // Exactly at the threshold passes
+    await voteWithBalance(minSolBalance, 0);
+
+    // One lamport below the threshold fails
+    try {
+      await voteWithBalance(minSolBalance - 1, 0);
+      expect.fail("Expected transaction to fail due to the balance gate");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientBalance");
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)[0]).to.equal(1);
+  });
+
+  /**
+   * Test Case 18: Track turnout separately from vote weight
+   *
+   * This test verifies that with a weighted and a plain vote:
+   * - voter_count counts each person once
+   * - total_votes counts the combined weight
+   */
+  it("Counts unique voters separately from total vote weight", async () => {
+    const { electionAccount, tokenAccount, voterAccount } =
+      await setupWeightedElection(1000, { clamp: {} }, 250);
+
+    // One weighted vote of 250 and one plain vote of 1
+    await program.methods
+      .voteWeighted(0, new anchor.BN(0))
+      .accounts({
+        votingAccount: electionAccount.publicKey,
+        userAccount: voterAccount,
+        user: provider.wallet.publicKey,
+        tokenAccount,
+        tokenSnapshot: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      })
+      .rpc();
+    await castVote(
+      electionAccount.publicKey,
+      await createVoter(electionAccount.publicKey),
+      1
+    );
+
+    const votingData = await program.account.votingAccount.fetch(
+      electionAccount.publicKey
+    );
+    expect(votingData.voterCount.toNumber()).to.equal(2);
+    expect(votingData.totalVotes.toNumber()).to.equal(251);
+  });
+
+  /**
+   * Test Case 19: Reorder candidates without moving votes
+   *
+   * This test verifies that reorder_candidates:
+   * - Moves each candidate's votes and flags along with it
+   * - Rejects an order that is not a permutation with InvalidPermutation
+   */
+  it("Keeps votes attached to candidates when reordering", async () => {
+    const election = await createElection();
+
+    // Alice gets one vote, Charlie gets two
+    await castVote(election, await createVoter(election), 0);
+    await castVote(election, await createVoter(election), 2);
+    await castVote(election, await createVoter(election), 2);
+
+    // Display order becomes Charlie, Alice, Bob
+    await program.methods
+      .reorderCandidates([2, 0, 1])
+      .accounts({
+        votingAccount: election,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.deep.equal(["Charlie", "Alice", "Bob"]);
+    expect(voteCounts(votingData)).to.deep.equal([2, 1, 0]);
+
+    // A duplicated index is not a permutation
+    try {
+      await program.methods
+        .reorderCandidates([0, 0, 1])
+        .accounts({
+          votingAccount: election,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("Expected transaction to fail due to an invalid permutation");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidPermutation");
+    }
+  });
+
+  /**
+   * Test Case 20: Experimental encrypted tally
+   *
+   * This test verifies that vote:
+   * - XORs each blinded increment into the candidate's encrypted_tally slot
+   * - Still updates the plaintext tally
+   */
+  it("XORs blinded increments into the encrypted tally", async () => {
+    const election = await createElection();
+    const firstBlind = Array.from({ length: 32 }, (_, i) => i);
+    const secondBlind = Array.from({ length: 32 }, () => 0xff);
+
+    // Two blinded votes for Bob
+    for (const blindedIncrement of [firstBlind, secondBlind]) {
+      const voter = await createVoter(election);
+      await program.methods
+        .vote(new anchor.BN(1), new anchor.BN(0), blindedIncrement)
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+
+    // Bob's slot holds the XOR of both increments; other slots are untouched
+    const expected = firstBlind.map((byte, i) => byte ^ secondBlind[i]);
+    expect(Array.from(votingData.encryptedTally[1])).to.deep.equal(expected);
+    expect(Array.from(votingData.encryptedTally[0])).to.deep.equal(
+      new Array(32).fill(0)
+    );
+
+    // The plaintext tally still counts both votes
+    expect(voteCounts(votingData)[1]).to.equal(2);
+  });
+
+  /**
+   * Test Case 21: Election tags for discovery
+   *
+   * This test verifies that:
+   * - Tags passed to initialize are stored and included in VotingInitialized
+   * - More than five tags are rejected with TooManyTags
+   * - A tag longer than 16 bytes is rejected with TagTooLong
+   */
+  it("Stores election tags and enforces the tag limits", async () => {
+    const tags = ["governance", "treasury"];
+
+    // Capture the VotingInitialized event for the tagged election
+    let initializedEvent = null;
+    const listener = program.addEventListener("VotingInitialized", (event) => {
+      initializedEvent = event;
+    });
+    const election = await createElection(defaultConfig({ tags }));
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.tags).to.deep.equal(tags);
+    expect(initializedEvent).to.not.be.null;
+    expect(initializedEvent.votingAccount.toBase58()).to.equal(
+      election.toBase58()
+    );
+    expect(initializedEvent.tags).to.deep.equal(tags);
+
+    // Six tags exceed the limit
+    try {
+      await createElection(
+        defaultConfig({ tags: ["a", "b", "c", "d", "e", "f"] })
+      );
+      expect.fail("Expected transaction to fail due to too many tags");
+    } catch (error) {
+      expect(error.toString()).to.include("TooManyTags");
+    }
+
+    // A 17 byte tag is too long
+    try {
+      await createElection(defaultConfig({ tags: ["x".repeat(17)] }));
+      expect.fail("Expected transaction to fail due to a long tag");
+    } catch (error) {
+      expect(error.toString()).to.include("TagTooLong");
+    }
+  });
+
+  /**
+   * Test Case 22: Hashed voter whitelist
+   *
+   * This test verifies that once hashed keys are added with add_eligible_hash:
+   * - A voter whose sha256(pubkey) is listed can vote
+   * - A voter whose hash is absent is rejected with NotEligible
+   */
+  it("Only lets voters with a whitelisted key hash vote", async () => {
+    const election = await createElection();
+    const eligibleVoter = await createFundedKeypair(1);
+    const outsider = await createFundedKeypair(1);
+
+    // Whitelist the eligible voter by the hash of their public key
+    const voterHash = createHash("sha256")
+      .update(eligibleVoter.publicKey.toBuffer())
+      .digest();
+    await program.methods
+      .addEligibleHash(Array.from(voterHash))
+      .accounts({
+        votingAccount: election,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    // Vote as the given keypair; the default wallet pays the fees
+    const voteAs = async (voter: Keypair) =>
+      program.methods
+        .vote(new anchor.BN(0), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount: await registerVoter(election, voter),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    // The whitelisted voter can vote
+    await voteAs(eligibleVoter);
+
+    // A voter who is not on the list cannot
+    try {
+      await voteAs(outsider);
+      expect.fail("Expected transaction to fail due to the whitelist");
+    } catch (error) {
+      expect(error.toString()).to.include("NotEligible");
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)[0]).to.equal(1);
+  });
+
+  /**
+   * Test Case 23: User accounts are bound to one election
+   *
+   * This test verifies that:
+   * - Registering binds the user account to the election it was derived for
+   * - Passing that user account to another election fails the PDA seeds check
+   */
+  it("Rejects a user account from a different election", async () => {
+    const firstElection = await createElection();
+    const secondElection = await createElection();
+    const userAccount = await registerVoter(firstElection);
+
+    // The account is bound to the first election and votes there
+    await castVote(firstElection, payer, 0);
+    const userData = await program.account.userAccount.fetch(userAccount);
+    expect(userData.election.toBase58()).to.equal(firstElection.toBase58());
+
+    // Reusing it in the second election is rejected
+    try {
+      await program.methods
+        .vote(new anchor.BN(0), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: secondElection,
+          userAccount,
+          user: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .rpc();
+      expect.fail("Expected transaction to fail due to a mismatched user account");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+  });
+
+  /**
+   * Test Case 24: Revoting after a reset
+   *
+   * This test verifies that:
+   * - reset_voting clears the tallies of a finished election and advances the round
+   * - reset_user lets the voter vote again in the new round, but only once
+   * - The tallies reflect a single fresh vote
+   */
+  it("Allows a fresh vote after reset_voting and reset_user", async () => {
+    const election = await createElection();
+    const userAccount = await registerVoter(election);
+
+    // Round 0: vote and end the election
+    await castVote(election, payer, 1);
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Reset the election and the user account for round 1, then reopen voting
+    await program.methods
+      .resetVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .openVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    const resetUser = () =>
+      program.methods
+        .resetUser()
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          signer: provider.wallet.publicKey,
+        })
+        .rpc();
+    await resetUser();
+
+    // Round 1: vote again
+    await castVote(election, payer, 0, 1);
+
+    // The account cannot be reset again within the same round
+    try {
+      await resetUser();
+      expect.fail("Expected transaction to fail as the round did not advance");
+    } catch (error) {
+      expect(error.toString()).to.include("ResetNotAllowed");
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.round.toNumber()).to.equal(1);
+    expect(votingData.status).to.deep.equal({ active: {} });
+    expect(voteCounts(votingData)[0]).to.equal(1);
+    expect(voteCounts(votingData)[1]).to.equal(0);
+    expect(votingData.totalVotes.toNumber()).to.equal(1);
+    expect(votingData.voterCount.toNumber()).to.equal(1);
+  });
+
+  /**
+   * Test Case 25: Stored PDA bumps
+   *
+   * This test verifies that:
+   * - The registry and election PDAs store their canonical bumps
+   * - close_election re-derives the election with the stored bump, so an address
+   *   built from a non-canonical bump or an election outside the registry is rejected
+   */
+  it("Stores canonical bumps and rejects other derivations", async () => {
+    const authority = await createFundedKeypair();
+    const [registry, registryBump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const electionSeeds = [
+      Buffer.from("election"),
+      authority.publicKey.toBuffer(),
+      new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+    ];
+    const [election, electionBump] = PublicKey.findProgramAddressSync(
+      electionSeeds,
+      program.programId
+    );
+
+    await program.methods
+      .initializeRegistry(new anchor.BN(5))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .createElection(candidates, defaultConfig())
+      .accounts({
+        registry,
+        votingAccount: election,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    // The canonical bumps are stored on the accounts
+    const registryData = await program.account.authorityRegistry.fetch(registry);
+    expect(registryData.bump).to.equal(registryBump);
+    const electionData = await program.account.votingAccount.fetch(election);
+    expect(electionData.bump).to.equal(electionBump);
+    expect(electionData.electionId.toNumber()).to.equal(0);
+
+    const closeElection = (votingAccount: PublicKey) =>
+      program.methods
+        .closeElection()
+        .accounts({ votingAccount, registry, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+    // Derive the same seeds with the highest valid non-canonical bump
+    let nonCanonical: PublicKey | null = null;
+    for (let bump = electionBump - 1; bump >= 0 && !nonCanonical; bump--) {
+      try {
+        nonCanonical = PublicKey.createProgramAddressSync(
+          [...electionSeeds, Buffer.from([bump])],
+          program.programId
+        );
+      } catch {
+        // This bump lands on the curve, try the next one
+      }
+    }
+
+    // Nothing can live at the non-canonical address
+    try {
+      await closeElection(nonCanonical);
+      expect.fail("Expected transaction to fail for a non-canonical bump");
+    } catch (error) {
+      expect(error.toString()).to.include("AccountNotInitialized");
+    }
+
+    // A keypair election owned by the same authority is not one of the registry's PDAs
+    const keypairElection = anchor.web3.Keypair.generate();
+    await program.methods
+      .initialize(candidates, defaultConfig())
+      .accounts({
+        votingAccount: keypairElection.publicKey,
+        user: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([keypairElection, authority])
+      .rpc();
+    await program.methods
+      .endVoting()
+      .accounts({
+        votingAccount: keypairElection.publicKey,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+    try {
+      await closeElection(keypairElection.publicKey);
+      expect.fail("Expected transaction to fail due to the seeds constraint");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+  });
+
+  /**
+   * Test Case 26: Election status state machine
+   *
+   * This test walks a draft election through its lifecycle and verifies that:
+   * - Votes are only accepted while the election is Active
+   * - Draft -> Active, Active <-> Paused and Paused -> Ended are allowed
+   * - Invalid moves (resuming an active election, Ended -> Active) fail with InvalidTransition
+   */
+  it("Enforces valid election status transitions", async () => {
+    const election = await createElection(defaultConfig({ startAsDraft: true }));
+    await registerVoter(election);
+    const action = (method: string) =>
+      program.methods[method]()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+    const expectError = async (promise: Promise<unknown>, code: string) => {
+      try {
+        await promise;
+        expect.fail(`Expected transaction to fail with ${code}`);
+      } catch (error) {
+        expect(error.toString()).to.include(code);
+      }
+    };
+    const status = async () =>
+      (await program.account.votingAccount.fetch(election)).status;
+
+    // Draft: no votes until the election is opened
+    expect(await status()).to.deep.equal({ draft: {} });
+    await expectError(castVote(election, payer, 0), "VotingNotActive");
+
+    // Draft -> Active
+    await action("openVoting");
+    expect(await status()).to.deep.equal({ active: {} });
+
+    // Active -> Active is not a move
+    await expectError(action("resumeVoting"), "InvalidTransition");
+
+    // Active -> Paused -> Active
+    await action("pauseVoting");
+    expect(await status()).to.deep.equal({ paused: {} });
+    await expectError(castVote(election, payer, 0), "VotingPaused");
+    await action("resumeVoting");
+    await castVote(election, payer, 0);
+
+    // Paused -> Ended
+    await action("pauseVoting");
+    await action("endVoting");
+    expect(await status()).to.deep.equal({ ended: {} });
+
+    // Ended is final until a reset: it can't be reopened, resumed or ended again
+    await expectError(action("openVoting"), "InvalidTransition");
+    await expectError(action("resumeVoting"), "InvalidTransition");
+    await expectError(action("endVoting"), "InvalidTransition");
+  });
+
+  /**
+   * Test Case 27: Abstaining
+   *
+   * This test verifies that vote_abstain:
+   * - Counts the voter towards turnout (voter_count) and abstain_count
+   * - Leaves every candidate's votes and total_votes untouched
+   * - Marks the user as having voted, without a choice
+   */
+  it("Counts abstentions towards participation but not candidates", async () => {
+    const election = await createElection();
+    const abstainerAccount = await registerVoter(election);
+
+    // One regular vote and one abstention
+    await castVote(election, await createVoter(election), 1);
+    const abstain = () =>
+      program.methods
+        .voteAbstain(new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount: abstainerAccount,
+          user: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    await abstain();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+    expect(votingData.totalVotes.toNumber()).to.equal(1);
+    expect(votingData.abstainCount.toNumber()).to.equal(1);
+    expect(votingData.voterCount.toNumber()).to.equal(2);
+
+    const userData = await program.account.userAccount.fetch(abstainerAccount);
+    expect(userData.hasVoted).to.be.true;
+    expect(userData.votedFor).to.be.null;
+
+    // Abstaining uses up the user's vote
+    try {
+      await abstain();
+      expect.fail("Expected transaction to fail as the user already abstained");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyVoted");
+    }
+  });
+
+  /**
+   * Test Case 28: Votes after the deadline
+   *
+   * This test verifies that:
+   * - finalize_expired fails with DeadlineNotReached before the deadline
+   * - Once an election's end_time deadline has passed, a vote fails with VotingExpired,
+   *   so the client sees its transaction fail, and nothing is counted or ended
+   * - Anyone can then end the election with finalize_expired, which records the outcome
+   * - Further votes are rejected because voting is no longer active
+   */
+  it("Refuses votes after the deadline until the election is finalized", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const deadline = (await clusterTime()) + 2;
+    const election = await createElection(
+      defaultConfig({ endTime: new anchor.BN(deadline) })
+    );
+
+    // The crank is sent and paid for by someone other than the authority
+    const cranker = await createFundedKeypair(1);
+    const finalizeExpired = async () => {
+      const tx = await program.methods
+        .finalizeExpired()
+        .accounts({ votingAccount: election })
+        .transaction();
+      tx.feePayer = cranker.publicKey;
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [cranker]);
+    };
+
+    // Vote before the deadline, when the election can't be finalized yet
+    await castVote(election, await createVoter(election), 1);
+    try {
+      await finalizeExpired();
+      expect.fail("Expected finalize_expired to fail before the deadline");
+    } catch (error) {
+      // Raw transactions report program errors through the logs
+      expect(error.logs.join("\n")).to.include("DeadlineNotReached");
+    }
+
+    // Wait until the cluster clock is past the deadline
+    while ((await clusterTime()) <= deadline) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+
+    // The late vote fails and changes nothing
+    const lateVoter = await createVoter(election);
+    try {
+      await castVote(election, lateVoter, 0);
+      expect.fail("Expected transaction to fail after the deadline");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingExpired");
+    }
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ active: {} });
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+
+    // The crank ends the election and records the outcome
+    await finalizeExpired();
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ ended: {} });
+    expect(votingData.endTime.toNumber()).to.equal(deadline);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+    expect(votingData.winnerIndex).to.equal(1);
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, lateVoter.publicKey)
+    );
+    expect(userData.hasVoted).to.be.false;
+
+    // Now that the election has ended, votes are rejected outright
+    try {
+      await castVote(election, lateVoter, 0);
+      expect.fail("Expected transaction to fail as voting has ended");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingNotActive");
+    }
+  });
+
+  /**
+   * Test Case 29: Minimum margin of victory
+   *
+   * This test verifies that with min_victory_margin = 2:
+   * - A two-vote lead produces a winner
+   * - A one-vote lead is inconclusive: no winner is recorded and get_winner
+   *   fails with MarginNotMet
+   */
+  it("Requires the winner to lead by the minimum margin", async () => {
+    // Run an election with the given votes per candidate and end it
+    const runElection = async (votesPerCandidate: number[]) => {
+      const election = await createElection(defaultConfig({ minVictoryMargin: 2 }));
+      for (const [index, count] of votesPerCandidate.entries()) {
+        for (let i = 0; i < count; i++) {
+          await castVote(election, await createVoter(election), index);
+        }
+      }
+      await program.methods
+        .endVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+      return election;
+    };
+    const getWinner = (election: PublicKey) =>
+      program.methods.getWinner().accounts({ votingAccount: election }).view();
+
+    // Margin met: Bob leads by two
+    const decisive = await runElection([1, 3, 0]);
+    expect(await getWinner(decisive)).to.equal(1);
+
+    // Margin not met: Bob leads by one
+    const close = await runElection([2, 3, 0]);
+    const votingData = await program.account.votingAccount.fetch(close);
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+    expect(votingData.isTie).to.be.false;
+    try {
+      await program.methods.getWinner().accounts({ votingAccount: close }).rpc();
+      expect.fail("Expected get_winner to fail as the margin was not met");
+    } catch (error) {
+      expect(error.toString()).to.include("MarginNotMet");
+    }
+  });
+
+  /**
+   * Test Case 30: Runoff elections
+   *
+   * This test verifies that create_runoff:
+   * - Requires the parent election to have ended
+   * - Creates a runoff PDA between the parent's top two candidates, linked to the parent
+   */
+  it("Creates a runoff between the top two candidates", async () => {
+    const parent = await createElection();
+    const [runoff] = PublicKey.findProgramAddressSync(
+      [Buffer.from("runoff"), parent.toBuffer()],
+      program.programId
+    );
+    const createRunoff = () =>
+      program.methods
+        .createRunoff()
+        .accounts({
+          parentElection: parent,
+          runoff,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // Charlie leads, Bob is second and Alice is eliminated
+    for (const index of [1, 2, 2, 1, 2, 0]) {
+      await castVote(parent, await createVoter(parent), index);
+    }
+
+    // The parent must have ended first
+    try {
+      await createRunoff();
+      expect.fail("Expected transaction to fail as the parent has not ended");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingNotEnded");
+    }
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: parent, authority: provider.wallet.publicKey })
+      .rpc();
+    await createRunoff();
+
+    const runoffData = await program.account.votingAccount.fetch(runoff);
+    expect(runoffData.candidates).to.deep.equal(["Charlie", "Bob"]);
+    expect(voteCounts(runoffData)).to.deep.equal([0, 0]);
+    expect(runoffData.status).to.deep.equal({ active: {} });
+    expect(runoffData.parentElection.toBase58()).to.equal(parent.toBase58());
+  });
+
+  /**
+   * Test Case 31: Per-slot vote rate limiting
+   *
+   * Two votes from one user account can only share a slot across a reset, so this
+   * test packs vote, end, reset, reopen, reset_user and a second vote into one
+   * transaction and verifies that:
+   * - With rate_limit_votes the second vote fails with TooFast
+   * - Without it the same transaction succeeds
+   */
+  it("Rejects two votes in the same slot when rate limited", async () => {
+    const authority = provider.wallet.publicKey;
+
+    // Run the whole two-round sequence in a single transaction
+    const voteTwiceInOneTransaction = async (rateLimitVotes: boolean) => {
+      const election = await createElection(defaultConfig({ rateLimitVotes }));
+      const userAccount = await registerVoter(election);
+      const voteAccounts = {
+        votingAccount: election,
+        userAccount,
+        user: authority,
+        authority,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      };
+      const adminAccounts = { votingAccount: election, authority };
+      const firstRound = [
+        await program.methods
+          .vote(new anchor.BN(0), new anchor.BN(0), null)
+          .accounts(voteAccounts)
+          .instruction(),
+        await program.methods.endVoting().accounts(adminAccounts).instruction(),
+        await program.methods.resetVoting().accounts(adminAccounts).instruction(),
+        await program.methods.openVoting().accounts(adminAccounts).instruction(),
+        await program.methods
+          .resetUser()
+          .accounts({ votingAccount: election, userAccount, signer: authority })
+          .instruction(),
+      ];
+      await program.methods
+        .vote(new anchor.BN(0), new anchor.BN(1), null)
+        .accounts(voteAccounts)
+        .preInstructions(firstRound)
+        .rpc();
+    };
+
+    // Rate limited: the second vote lands in the same slot as the first
+    try {
+      await voteTwiceInOneTransaction(true);
+      expect.fail("Expected transaction to fail due to the rate limit");
+    } catch (error) {
+      expect(error.toString()).to.include("TooFast");
+    }
+
+    // Without the limit the same sequence is accepted
+    await voteTwiceInOneTransaction(false);
+  });
+
+  /**
+   * Test Case 32: Ranked-choice (instant-runoff) tally
+   *
+   * Five ranked ballots over Alice, Bob and Charlie give Alice 2, Bob 1 and Charlie 2
+   * first preferences. This test verifies that:
+   * - The first tally_ranked round finds no majority and eliminates Bob
+   * - The second round moves Bob's ballot to Alice, who wins with a majority
+   * - Further rounds fail with TallyComplete
+   */
+  it("Resolves a ranked-choice election over two IRV rounds", async () => {
+    const election = await createElection();
+
+    // Cast the ranked ballots, each from its own voter
+    const rankings = [[0, 1], [0, 2], [1, 0], [2, 1], [2, 1]];
+    const ballots: PublicKey[] = [];
+    for (const ranking of rankings) {
+      const voter = await createVoter(election);
+      const [rankedBallot] = PublicKey.findProgramAddressSync(
+        [Buffer.from("ranked"), election.toBuffer(), voter.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .castRankedBallot(ranking, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          rankedBallot,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+      ballots.push(rankedBallot);
+    }
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Every ballot is passed once, sorted by address
+    const ballotAccounts = ballots
+      .sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()))
+      .map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }));
+    const tallyRound = () =>
+      program.methods
+        .tallyRanked()
+        .accounts({ votingAccount: election })
+        .remainingAccounts(ballotAccounts)
+        .rpc();
+
+    // Round 1: no majority, Bob has the fewest first preferences
+    await tallyRound();
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.eliminated).to.deep.equal([1]);
+    expect(votingData.rankedWinner).to.be.null;
+
+    // Round 2: Bob's ballot moves to Alice, giving her 3 of 5
+    await tallyRound();
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.rankedWinner).to.equal(0);
+
+    // The tally is finished
+    try {
+      await tallyRound();
+      expect.fail("Expected transaction to fail as the tally is complete");
+    } catch (error) {
+      expect(error.toString()).to.include("TallyComplete");
+    }
+  });
+
+  /**
+   * Test Case 33: Open and authority-only registration
+   *
+   * This test verifies that:
+   * - In an Open election a voter can self-register a user account PDA and vote
+   * - In an AuthorityOnly election self-registration fails with RegistrationClosed
+   * - Registration co-signed by the authority succeeds and lets the voter vote
+   */
+  it("Enforces the election's registration mode", async () => {
+    const authority = provider.wallet.publicKey;
+
+    // Register the voter's PDA user account, optionally co-signed by the authority
+    const register = async (
+      election: PublicKey,
+      voter: Keypair,
+      withAuthority: boolean
+    ) => {
+      const [userAccount] = PublicKey.findProgramAddressSync(
+        [Buffer.from("user"), election.toBuffer(), voter.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initializeUserAccount()
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: voter.publicKey,
+          authority: withAuthority ? authority : null,
+          systemProgram: SystemProgram.programId,
+          tokenAccount: null,
+        })
+        .signers([voter])
+        .rpc();
+      return userAccount;
+    };
+    const voteAs = (election: PublicKey, userAccount: PublicKey, voter: Keypair) =>
+      program.methods
+        .vote(new anchor.BN(0), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: voter.publicKey,
+          authority,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Open: voters register themselves
+    const openElection = await createElection();
+    const openVoter = await createFundedKeypair(1);
+    await voteAs(openElection, await register(openElection, openVoter, false), openVoter);
+
+    // AuthorityOnly: self-registration is rejected
+    const closedElection = await createElection(
+      defaultConfig({ registrationMode: { authorityOnly: {} } })
+    );
+    const closedVoter = await createFundedKeypair(1);
+    try {
+      await register(closedElection, closedVoter, false);
+      expect.fail("Expected transaction to fail due to closed registration");
+    } catch (error) {
+      expect(error.toString()).to.include("RegistrationClosed");
+    }
+
+    // With the authority's co-signature the voter is registered and can vote
+    const userAccount = await register(closedElection, closedVoter, true);
+    await voteAs(closedElection, userAccount, closedVoter);
+
+    const votingData = await program.account.votingAccount.fetch(closedElection);
+    expect(voteCounts(votingData)[0]).to.equal(1);
+  });
+
+  /**
+   * Test Case 34: Invalid candidate diagnostics
+   *
+   * This test verifies that a ballot for an out-of-range candidate index (here a veto,
+   * as vote selects candidates by ID) fails with InvalidCandidate and logs the
+   * attempted index and the valid range
+   */
+  it("Logs the attempted index and valid range for an invalid candidate", async () => {
+    const election = await createElection();
+    const voter = await createVoter(election);
+
+    try {
+      await program.methods
+        .veto(7, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+      expect.fail("Expected transaction to fail due to invalid candidate index");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCandidate");
+      expect(error.logs.join("\n")).to.include(
+        "Invalid candidate index 7: election has 3 candidates (valid indices 0..3)"
+      );
+    }
+  });
+
+  /**
+   * Test Case 35: Minimum votes to win
+   *
+   * This test verifies that when every candidate falls below min_winning_votes:
+   * - end_voting records no winner (the NO_WINNER sentinel) even with a clear leader
+   * - A NoWinner event reports the leader's count and the threshold
+   */
+  it("Declares no winner when the leader is below the vote threshold", async () => {
+    const election = await createElection(defaultConfig({ minWinningVotes: 3 }));
+
+    // Bob leads with 2 votes, one short of the threshold
+    for (const index of [1, 1, 0]) {
+      await castVote(election, await createVoter(election), index);
+    }
+
+    // Listen for the NoWinner event before ending the election
+    let noWinnerEvent = null;
+    const listener = program.addEventListener("NoWinner", (event) => {
+      noWinnerEvent = event;
+    });
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+    expect(votingData.isTie).to.be.false;
+
+    // Give the listener a moment to receive the event, then verify it
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(noWinnerEvent).to.not.be.null;
+    expect(noWinnerEvent.topVotes.toNumber()).to.equal(2);
+    expect(noWinnerEvent.minWinningVotes).to.equal(3);
+  });
+
+  /**
+   * Test Case 36: Append candidates in bulk
+   *
+   * This test verifies that add_candidates:
+   * - Appends five candidates in one call, each starting at zero votes
+   * - Lets the new candidates receive votes
+   * - Rejects a batch containing an existing candidate with DuplicateCandidate
+   */
+  it("Adds several candidates in one instruction", async () => {
+    const election = await createElection();
+    const addCandidates = (names: string[]) =>
+      program.methods
+        .addCandidates(names)
+        .accounts({
+          votingAccount: election,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const newNames = ["Dave", "Eve", "Frank", "Grace", "Heidi"];
+    await addCandidates(newNames);
+
+    // The last new candidate can be voted for
+    await castVote(election, await createVoter(election), 7);
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.deep.equal([...candidates, ...newNames]);
+    expect(voteCounts(votingData)).to.deep.equal([0, 0, 0, 0, 0, 0, 0, 1]);
+    expect(votingData.disabled).to.have.lengthOf(8);
+
+    // Names already on the ballot are rejected
+    try {
+      await addCandidates(["Ivan", "Alice"]);
+      expect.fail("Expected transaction to fail due to a duplicate candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("DuplicateCandidate");
+    }
+  });
+
+  /**
+   * Test Case 37: Authority-submitted vote batches
+   *
+   * This test verifies that submit_batch:
+   * - Counts a batch of 100 votes with the submitted distribution, in batched_votes
+   *   rather than voter_count
+   * - Rejects a batch with an out-of-range index without counting any of it
+   * - Rejects a batch collected for another round with StaleRound
+   */
+  it("Counts an authority-submitted batch of votes", async () => {
+    const election = await createElection();
+    const submitBatch = (votes: number[], round = 0) =>
+      program.methods
+        .submitBatch(votes, new anchor.BN(round))
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+
+    // 100 votes spread over the three candidates: 34, 33, 33
+    await submitBatch(Array.from({ length: 100 }, (_, i) => i % 3));
+
+    // One bad entry rejects the whole batch
+    try {
+      await submitBatch([0, 1, 9]);
+      expect.fail("Expected transaction to fail due to invalid candidate index");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCandidate");
+    }
+
+    // A batch for another round is refused
+    try {
+      await submitBatch([0], 1);
+      expect.fail("Expected transaction to fail due to a stale round");
+    } catch (error) {
+      expect(error.toString()).to.include("StaleRound");
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([34, 33, 33]);
+    expect(votingData.totalVotes.toNumber()).to.equal(100);
+    expect(votingData.batchedVotes.toNumber()).to.equal(100);
+    expect(votingData.voterCount.toNumber()).to.equal(0);
+  });
+
+  /**
+   * Test Case 38: Weighted totals beyond u32::MAX
+   *
+   * This test verifies that vote counts are u64: two weighted votes of three billion
+   * tokens each add up past u32::MAX without overflowing
+   */
+  it("Counts weighted votes beyond the u32 range", async () => {
+    const weight = 3_000_000_000;
+    const { electionAccount, voteMint, tokenAccount } =
+      await setupWeightedElection(weight, { clamp: {} }, weight);
+
+    // A second voter holding the same balance in their own token account
+    const secondVoter = await createFundedKeypair(1);
+    const secondTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      secondVoter.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      voteMint,
+      secondTokenAccount,
+      payer,
+      weight
+    );
+    await registerVoter(electionAccount.publicKey, secondVoter, secondTokenAccount);
+
+    // Both voters vote with their full balance
+    for (const [voter, voterTokenAccount] of [
+      [payer, tokenAccount],
+      [secondVoter, secondTokenAccount],
+    ] as [Keypair, PublicKey][]) {
+      await program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: electionAccount.publicKey,
+          userAccount: userAccountFor(electionAccount.publicKey, voter.publicKey),
+          user: voter.publicKey,
+          tokenAccount: voterTokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+    }
+
+    const votingData = await program.account.votingAccount.fetch(
+      electionAccount.publicKey
+    );
+    expect(votingData.votes[0].toString()).to.equal("6000000000");
+    expect(votingData.totalVotes.toString()).to.equal("6000000000");
+  });
+
+  /**
+   * Test Case 39: Emergency cancellation
+   *
+   * This test verifies that emergency_cancel on an ended election:
+   * - Moves it to Cancelled and emits ElectionCancelled with the reason
+   * - Makes get_winner fail with ElectionCancelled
+   * - Is final: the election cannot be reset afterwards
+   */
+  it("Voids an election so no winner can be queried", async () => {
+    const election = await createElection();
+    await castVote(election, await createVoter(election), 0);
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Listen for the ElectionCancelled event before cancelling
+    let cancelledEvent = null;
+    const listener = program.addEventListener("ElectionCancelled", (event) => {
+      cancelledEvent = event;
+    });
+
+    await program.methods
+      .emergencyCancel("Ballot stuffing discovered")
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ cancelled: {} });
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+
+    // Winner queries fail
+    try {
+      await program.methods.getWinner().accounts({ votingAccount: election }).rpc();
+      expect.fail("Expected get_winner to fail for a cancelled election");
+    } catch (error) {
+      expect(error.toString()).to.include("ElectionCancelled");
+    }
+
+    // Cancellation is final
+    try {
+      await program.methods
+        .resetVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+      expect.fail("Expected transaction to fail for a cancelled election");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingNotEnded");
+    }
+
+    // Give the listener a moment to receive the event, then verify it
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(cancelledEvent).to.not.be.null;
+    expect(cancelledEvent.reason).to.equal("Ballot stuffing discovered");
+  });
+
+  /**
+   * Test Case 40: Only user account PDAs can vote
+   *
+   * This test verifies that:
+   * - The deprecated initialize_user fails with UseAccountPda, so no keypair
+   *   user account is created
+   * - vote rejects that keypair address as a user account
+   * - vote rejects another voter's PDA, which fails the seeds check for the signer
+   */
+  it("Rejects user accounts that are not the voter's PDA", async () => {
+    const election = await createElection();
+    const voteWith = (userAccount: PublicKey) =>
+      program.methods
+        .vote(new anchor.BN(0), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .rpc();
+
+    // Keypair user accounts can no longer be created
+    const keypairAccount = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .initializeUser()
+        .accounts({
+          userAccount: keypairAccount.publicKey,
+          user: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([keypairAccount])
+        .rpc();
+      expect.fail("Expected initialize_user to fail as it is deprecated");
+    } catch (error) {
+      expect(error.toString()).to.include("UseAccountPda");
+    }
+
+    // ...so the keypair address is not a usable user account
+    try {
+      await voteWith(keypairAccount.publicKey);
+      expect.fail("Expected transaction to fail for a keypair user account");
+    } catch (error) {
+      expect(error.toString()).to.include("AccountNotInitialized");
+    }
+
+    // Another voter's PDA is a real user account, but not the signer's
+    const otherVoter = await createVoter(election);
+    try {
+      await voteWith(userAccountFor(election, otherVoter.publicKey));
+      expect.fail("Expected transaction to fail for another voter's PDA");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+
+    // The signer's own PDA is accepted
+    await voteWith(await registerVoter(election));
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)[0]).to.equal(1);
+  });
+
+  /**
+   * Test Case 41: Result visibility modes
+   *
+   * This test verifies that get_results and get_percentages:
+   * - Report live results at any time in a normal election
+   * - Fail with ResultsHidden before the end of a results_hidden election
+   * - Report the results of a results_hidden election once it has ended
+   */
+  it("Hides results until the end only when results_hidden is set", async () => {
+    const results = async (election: PublicKey) =>
+      (
+        await program.methods.getResults().accounts({ votingAccount: election }).view()
+      ).map((count: anchor.BN) => count.toNumber());
+    const percentages = (election: PublicKey) =>
+      program.methods.getPercentages().accounts({ votingAccount: election }).view();
+    const endVoting = (election: PublicKey) =>
+      program.methods
+        .endVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+
+    // Alice gets three votes and Bob one in each election
+    const setup = async (resultsHidden: boolean) => {
+      const election = await createElection(defaultConfig({ resultsHidden }));
+      for (const index of [0, 0, 0, 1]) {
+        await castVote(election, await createVoter(election), index);
+      }
+      return election;
+    };
+
+    // Live results are readable before and after the end
+    const liveElection = await setup(false);
+    expect(await results(liveElection)).to.deep.equal([3, 1, 0]);
+    expect(await percentages(liveElection)).to.deep.equal([7500, 2500, 0]);
+    await endVoting(liveElection);
+    expect(await results(liveElection)).to.deep.equal([3, 1, 0]);
+
+    // Hidden results are refused until the election ends
+    const hiddenElection = await setup(true);
+    for (const method of ["getResults", "getPercentages"]) {
+      try {
+        await program.methods[method]()
+          .accounts({ votingAccount: hiddenElection })
+          .rpc();
+        expect.fail("Expected the getter to fail while results are hidden");
+      } catch (error) {
+        expect(error.toString()).to.include("ResultsHidden");
+      }
+    }
+    await endVoting(hiddenElection);
+    expect(await results(hiddenElection)).to.deep.equal([3, 1, 0]);
+    expect(await percentages(hiddenElection)).to.deep.equal([7500, 2500, 0]);
+  });
+
+  /**
+   * Test Case 42: Localized display names
+   *
+   * This test verifies that:
+   * - Display names and a locale are stored alongside distinct canonical candidate names
+   * - Elections without display names show the canonical names
+   * - A display name list of a different length fails with DisplayNamesMismatch
+   */
+  it("Stores display names separately from canonical candidate names", async () => {
+    const ids = ["candidate-a", "candidate-b", "candidate-c"];
+    const displayNames = ["Alicia", "Roberto", "Carlos"];
+    const election = await createElection(
+      defaultConfig({ displayNames, locale: "es-MX" }),
+      ids
+    );
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.deep.equal(ids);
+    expect(votingData.displayNames).to.deep.equal(displayNames);
+    expect(votingData.locale).to.equal("es-MX");
+
+    // Without display names the canonical names are shown
+    const plainElection = await createElection();
+    const plainData = await program.account.votingAccount.fetch(plainElection);
+    expect(plainData.displayNames).to.deep.equal(candidates);
+
+    // Every candidate needs exactly one display name
+    try {
+      await createElection(defaultConfig({ displayNames: ["Alicia"] }), ids);
+      expect.fail("Expected transaction to fail due to mismatched display names");
+    } catch (error) {
+      expect(error.toString()).to.include("DisplayNamesMismatch");
+    }
+  });
+
+  /**
+   * Test Case 43: Election schedule sanity checks
+   *
+   * This test verifies that initialize:
+   * - Rejects a deadline before the start time with InvalidEndTime
+   * - Rejects a start time more than a day in the past with InvalidStartTime
+   * - Accepts a future start, before which votes fail with VotingNotStarted
+   */
+  it("Rejects misconfigured election schedules", async () => {
+    const now = await provider.connection.getBlockTime(
+      await provider.connection.getSlot()
+    );
+    const day = 24 * 60 * 60;
+    const createWith = (startTime: number, endTime: number) =>
+      createElection(
+        defaultConfig({
+          startTime: new anchor.BN(startTime),
+          endTime: new anchor.BN(endTime),
+        })
+      );
+
+    // The deadline comes before the start
+    try {
+      await createWith(now + 100, now + 50);
+      expect.fail("Expected transaction to fail due to the end time");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidEndTime");
+    }
+
+    // The start lies two days in the past
+    try {
+      await createWith(now - 2 * day, 0);
+      expect.fail("Expected transaction to fail due to the start time");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidStartTime");
+    }
+
+    // A start an hour from now is accepted, but votes wait for it
+    const election = await createWith(now + 3600, now + 7200);
+    try {
+      await castVote(election, await createVoter(election), 0);
+      expect.fail("Expected transaction to fail before the start time");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingNotStarted");
+    }
+  });
+
+  /**
+   * Test Case 44: Reserve candidate capacity
+   *
+   * This test verifies that:
+   * - expand_capacity grows the voting account and records the reserved slots
+   * - add_candidates fills reserved slots without growing the account again
+   * - Candidates beyond the reservation grow the account as before
+   */
+  it("Adds candidates into capacity reserved by expand_capacity", async () => {
+    const election = await createElection();
+    const dataLength = async () =>
+      (await provider.connection.getAccountInfo(election)).data.length;
+    const authorityAccounts = {
+      votingAccount: election,
+      authority: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    const addCandidates = (names: string[]) =>
+      program.methods.addCandidates(names).accounts(authorityAccounts).rpc();
+
+    // Reserve three slots
+    const initialLength = await dataLength();
+    await program.methods.expandCapacity(3).accounts(authorityAccounts).rpc();
+    const expandedLength = await dataLength();
+    expect(expandedLength).to.be.greaterThan(initialLength);
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.reservedCapacity).to.equal(3);
+
+    // Up to the reserved limit the account does not grow
+    await addCandidates(["Dave", "Eve"]);
+    await addCandidates(["Frank"]);
+    expect(await dataLength()).to.equal(expandedLength);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.reservedCapacity).to.equal(0);
+    expect(votingData.candidates).to.have.lengthOf(6);
+
+    // Past the reservation the account grows again
+    await addCandidates(["Grace"]);
+    expect(await dataLength()).to.be.greaterThan(expandedLength);
+  });
+
+  /**
+   * Test Case 45: Dry-run votes
+   *
+   * This test verifies that simulate_vote:
+   * - Succeeds for a valid vote and logs what the vote would do
+   * - Fails with the same error vote would return for an invalid one
+   * - Leaves the voting and user accounts unchanged either way
+   */
+  it("Simulates a vote without changing any account", async () => {
+    const election = await createElection();
+    const userAccount = await registerVoter(election);
+    const simulateVote = (candidateIndex: number) =>
+      program.methods
+        .simulateVote(candidateIndex)
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: provider.wallet.publicKey,
+        })
+        .rpc();
+    const snapshot = async () => ({
+      votingAccount: (await provider.connection.getAccountInfo(election)).data,
+      userAccount: (await provider.connection.getAccountInfo(userAccount)).data,
+    });
+
+    const before = await snapshot();
+
+    // A valid vote passes the pre-check
+    const signature = await simulateVote(1);
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const transaction = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+    });
+    expect(transaction.meta.logMessages.join("\n")).to.include(
+      "Vote is valid: it would add 1 vote for candidate 1 (Bob)"
+    );
+
+    // An invalid vote fails just as vote would
+    try {
+      await simulateVote(5);
+      expect.fail("Expected simulation to fail due to invalid candidate index");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCandidate");
+    }
+
+    // Neither account changed
+    expect(await snapshot()).to.deep.equal(before);
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+    const userData = await program.account.userAccount.fetch(userAccount);
+    expect(userData.hasVoted).to.be.false;
+  });
+
+  /**
+   * Test Case 46: Sorted rankings
+   *
+   * This test verifies that get_rankings returns every candidate with its original
+   * index, most votes first, and breaks a tie by the lower original index
+   */
+  it("Ranks candidates by votes with ties in ballot order", async () => {
+    const election = await createElection(defaultConfig(), [
+      "Alice",
+      "Bob",
+      "Charlie",
+      "Dave",
+    ]);
+
+    // Charlie leads with 2; Alice and Dave tie on 1; Bob has none
+    for (const index of [3, 2, 0, 2]) {
+      await castVote(election, await createVoter(election), index);
+    }
+
+    const rankings = await program.methods
+      .getRankings()
+      .accounts({ votingAccount: election })
+      .view();
+    expect(
+      rankings.map((entry) => [entry.index, entry.name, entry.votes.toNumber()])
+    ).to.deep.equal([
+      [2, "Charlie", 2],
+      [0, "Alice", 1],
+      [3, "Dave", 1],
+      [1, "Bob", 0],
+    ]);
+  });
+
+  /**
+   * Test Case 47: Veto votes
+   *
+   * This test verifies that:
+   * - veto counts against a candidate and uses up the voter's ballot
+   * - end_voting disqualifies a candidate with more vetoes than votes, so the
+   *   runner-up wins
+   */
+  it("Disqualifies a candidate with more vetoes than votes", async () => {
+    const election = await createElection();
+    const veto = (voter: Keypair, candidateIndex: number) =>
+      program.methods
+        .veto(candidateIndex, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Alice leads Bob 2 to 1, but three voters veto her
+    for (const index of [0, 0, 1]) {
+      await castVote(election, await createVoter(election), index);
+    }
+    const vetoers = [];
+    for (let i = 0; i < 3; i++) {
+      const voter = await createVoter(election);
+      await veto(voter, 0);
+      vetoers.push(voter);
+    }
+
+    // A veto uses up the ballot
+    try {
+      await castVote(election, vetoers[0], 1);
+      expect.fail("Expected transaction to fail as the voter already vetoed");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyVoted");
+    }
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.vetoes).to.deep.equal([3, 0, 0]);
+    expect(voteCounts(votingData)).to.deep.equal([2, 1, 0]);
+    expect(votingData.winnerIndex).to.equal(1);
+  });
+
+  /**
+   * Test Case 48: Delegated admins
+   *
+   * This test verifies that an admin added by the owner:
+   * - Can pause and resume voting
+   * - Cannot transfer ownership or manage admins, which stay owner-only
+   * - Loses those rights once removed
+   */
+  it("Lets admins pause and resume but keeps ownership actions owner-only", async () => {
+    const election = await createElection();
+    const admin = anchor.web3.Keypair.generate();
+    const ownerAction = (method: string, ...args: unknown[]) =>
+      program.methods[method](...args)
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+    const adminAction = (method: string, ...args: unknown[]) =>
+      program.methods[method](...args)
+        .accounts({ votingAccount: election, authority: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    const expectUnauthorized = async (promise: Promise<unknown>) => {
+      try {
+        await promise;
+        expect.fail("Expected transaction to fail for an admin");
+      } catch (error) {
+        expect(error.toString()).to.include("Unauthorized");
+      }
+    };
+
+    await ownerAction("addAdmin", admin.publicKey);
+
+    // Operational actions are open to the admin
+    await adminAction("pauseVoting");
+    await adminAction("resumeVoting");
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ active: {} });
+
+    // Ownership actions are not
+    await expectUnauthorized(adminAction("transferAuthority", admin.publicKey));
+    await expectUnauthorized(adminAction("addAdmin", anchor.web3.Keypair.generate().publicKey));
+
+    // Once removed, the admin can no longer pause
+    await ownerAction("removeAdmin", admin.publicKey);
+    await expectUnauthorized(adminAction("pauseVoting"));
+  });
+
+  /**
+   * Test Case 49: Rent pre-check before growing an account
+   *
+   * This test verifies that when the authority cannot pay for the extra rent of a
+   * realloc, the instruction fails with InsufficientRent instead of a system error
+   */
+  it("Rejects a realloc the payer cannot afford with InsufficientRent", async () => {
+    const election = await createElection();
+
+    // Hand the election to an authority holding barely more than its own rent exemption
+    const poorAuthority = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(poorAuthority.publicKey, 1_000_000)
+    );
+    await program.methods
+      .transferAuthority(poorAuthority.publicKey)
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Reserving five candidate slots needs about 2.5 million lamports of rent
+    try {
+      await program.methods
+        .expandCapacity(5)
+        .accounts({
+          votingAccount: election,
+          authority: poorAuthority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([poorAuthority])
+        .rpc();
+      expect.fail("Expected transaction to fail due to insufficient rent");
+    } catch (error) {
+      expect(error.toString()).to.include("InsufficientRent");
+    }
+  });
+
+  /**
+   * Test Case 50: Vote power decay
+   *
+   * This test starts a decaying election almost a day in the past and verifies that
+   * a weighted vote cast on day 0 counts in full while the same balance voting after
+   * the first full day counts 20% less
+   */
+  it("Counts a later vote for less when votes decay", async () => {
+    const day = 24 * 60 * 60;
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const startTime = (await clusterTime()) - day + 20;
+    const { electionAccount, voteMint, tokenAccount, voterAccount } =
+      await setupWeightedElection(1000, { clamp: {} }, 1000, {
+        startTime: new anchor.BN(startTime),
+        decayRateBpsPerDay: 2000,
+      });
+    const election = electionAccount.publicKey;
+    const voteWeighted = (
+      voter: Keypair,
+      userAccount: PublicKey,
+      voterTokenAccount: PublicKey
+    ) =>
+      program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: voter.publicKey,
+          tokenAccount: voterTokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Day 0: the full balance of 1000 counts
+    await voteWeighted(payer, voterAccount, tokenAccount);
+
+    // A second voter with the same balance waits until day 1
+    const lateVoter = await createFundedKeypair(1);
+    const lateTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      lateVoter.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(provider.connection, payer, voteMint, lateTokenAccount, payer, 1000);
+    await registerVoter(election, lateVoter, lateTokenAccount);
+    while ((await clusterTime()) < startTime + day + 1) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    await voteWeighted(
+      lateVoter,
+      userAccountFor(election, lateVoter.publicKey),
+      lateTokenAccount
+    );
+
+    // The later vote lost 20% of its weight
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)[0]).to.equal(1800);
+  });
+
+  /**
+   * Test Case 51: Stable candidate IDs
+   *
+   * This test verifies that:
+   * - Candidates get IDs counting up from 0, continued by add_candidates
+   * - After remove_candidate shifts later candidates up, votes by ID still land on
+   *   the intended candidates
+   * - Votes for the removed ID fail with CandidateNotFound
+   */
+  it("Keeps votes by candidate ID correct after a removal", async () => {
+    const election = await createElection();
+    const authorityAccounts = {
+      votingAccount: election,
+      authority: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    };
+    await program.methods.addCandidates(["Dave"]).accounts(authorityAccounts).rpc();
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidateIds.map((id) => id.toNumber())).to.deep.equal([0, 1, 2, 3]);
+
+    // Remove Bob (ID 1); Charlie and Dave move up one position
+    await program.methods
+      .removeCandidate(new anchor.BN(1))
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Votes by ID reach Charlie (ID 2) and Dave (ID 3) at their new positions
+    await castVote(election, await createVoter(election), 2);
+    await castVote(election, await createVoter(election), 3);
+    await castVote(election, await createVoter(election), 3);
+
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.deep.equal(["Alice", "Charlie", "Dave"]);
+    expect(votingData.candidateIds.map((id) => id.toNumber())).to.deep.equal([0, 2, 3]);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 2]);
+
+    // The removed ID no longer exists
+    try {
+      await castVote(election, await createVoter(election), 1);
+      expect.fail("Expected transaction to fail for a removed candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateNotFound");
+    }
+  });
+
+  /**
+   * Test Case 52: Update discovery tags
+   *
+   * This test verifies that tags set at creation can be replaced with update_tags,
+   * which applies the same limits as initialize and is restricted to the authority
+   */
+  it("Round-trips tags through create and update_tags", async () => {
+    const election = await createElection(defaultConfig({ tags: ["treasury"] }));
+    const updateTags = (tags: string[], authority: Keypair = payer) =>
+      program.methods
+        .updateTags(tags)
+        .accounts({ votingAccount: election, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    const storedTags = async () =>
+      (await program.account.votingAccount.fetch(election)).tags;
+
+    expect(await storedTags()).to.deep.equal(["treasury"]);
+    await updateTags(["grants", "q3-2024"]);
+    expect(await storedTags()).to.deep.equal(["grants", "q3-2024"]);
+
+    // The limits from initialize still apply
+    try {
+      await updateTags(["a-tag-well-over-sixteen-bytes"]);
+      expect.fail("Expected transaction to fail due to a long tag");
+    } catch (error) {
+      expect(error.toString()).to.include("TagTooLong");
+    }
+
+    // Only the authority can change the tags
+    try {
+      await updateTags([], anchor.web3.Keypair.generate());
+      expect.fail("Expected transaction to fail for a non-authority");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+    expect(await storedTags()).to.deep.equal(["grants", "q3-2024"]);
+  });
+
+  /**
+   * Test Case 53: Effective status past the deadline
+   *
+   * This test verifies that get_status reports an election whose end_time has passed
+   * as ended even though nobody called end_voting, while the stored status stays active
+   */
+  it("Reports an election past its deadline as ended", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const deadline = (await clusterTime()) + 2;
+    const election = await createElection(
+      defaultConfig({ endTime: new anchor.BN(deadline) })
+    );
+    const getStatus = () =>
+      program.methods.getStatus().accounts({ votingAccount: election }).view();
+
+    // Before the deadline the derived status matches the stored one
+    expect(await getStatus()).to.deep.equal({ active: {} });
+
+    // Wait until the cluster clock is past the deadline
+    while ((await clusterTime()) <= deadline) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+
+    // The election reads as ended, but the stored status is untouched
+    expect(await getStatus()).to.deep.equal({ ended: {} });
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ active: {} });
+  });
+
+  /**
+   * Test Case 54: Election-wide per-slot rate limit
+   *
+   * This test verifies that with max_votes_per_slot = 2, a transaction casting three
+   * votes (so all in the same slot) fails with RateLimited and counts nothing, while
+   * two votes in one transaction are accepted
+   */
+  it("Rejects votes beyond the per-slot cap", async () => {
+    const election = await createElection(defaultConfig({ maxVotesPerSlot: 2 }));
+    const voters = [];
+    for (let i = 0; i < 3; i++) {
+      voters.push(await createVoter(election));
+    }
+
+    // Send one transaction with a vote from each of the given voters
+    const voteTogether = async (batch: Keypair[]) => {
+      const tx = new anchor.web3.Transaction();
+      for (const voter of batch) {
+        tx.add(
+          await program.methods
+            .vote(new anchor.BN(0), new anchor.BN(0), null)
+            .accounts({
+              votingAccount: election,
+              userAccount: userAccountFor(election, voter.publicKey),
+              user: voter.publicKey,
+              authority: provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+              roster: null,
+            })
+            .instruction()
+        );
+      }
+      await provider.sendAndConfirm(tx, batch);
+    };
+
+    // Three votes in one slot exceed the cap
+    try {
+      await voteTogether(voters);
+      expect.fail("Expected transaction to fail due to the per-slot cap");
+    } catch (error) {
+      // Raw transactions report program errors through the logs
+      expect(error.logs.join("\n")).to.include("RateLimited");
+    }
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+
+    // Two votes fit
+    await voteTogether(voters.slice(0, 2));
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(2);
+    expect(votingData.votesThisSlot).to.equal(2);
+  });
+
+  /**
+   * Test Case 55: Votes are bound to a round
+   *
+   * This test verifies that after reset_voting starts round 1, a vote carrying
+   * the previous round (as a replayed round-0 transaction would) fails with StaleRound
+   * and is not counted, whether it is a vote, an abstention or a veto, while a vote for
+   * the current round is accepted
+   */
+  it("Rejects votes for a stale round", async () => {
+    const election = await createElection();
+    const voter = await createVoter(election);
+    const adminAccounts = { votingAccount: election, authority: provider.wallet.publicKey };
+
+    // Round 0: vote, then end and reset the election and the voter
+    await castVote(election, voter, 1, 0);
+    await program.methods.endVoting().accounts(adminAccounts).rpc();
+    await program.methods.resetVoting().accounts(adminAccounts).rpc();
+    await program.methods.openVoting().accounts(adminAccounts).rpc();
+    await program.methods
+      .resetUser()
+      .accounts({
+        votingAccount: election,
+        userAccount: userAccountFor(election, voter.publicKey),
+        signer: voter.publicKey,
+      })
+      .signers([voter])
+      .rpc();
+
+    // The round-0 vote is rejected in round 1
+    try {
+      await castVote(election, voter, 1, 0);
+      expect.fail("Expected transaction to fail due to a stale round");
+    } catch (error) {
+      expect(error.toString()).to.include("StaleRound");
+    }
+    const voteAccounts = {
+      votingAccount: election,
+      userAccount: userAccountFor(election, voter.publicKey),
+      user: voter.publicKey,
+      authority: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+      roster: null,
+    };
+    for (const staleVote of [
+      program.methods.voteAbstain(new anchor.BN(0)),
+      program.methods.veto(1, new anchor.BN(0)),
+    ]) {
+      try {
+        await staleVote.accounts(voteAccounts).signers([voter]).rpc();
+        expect.fail("Expected transaction to fail due to a stale round");
+      } catch (error) {
+        expect(error.toString()).to.include("StaleRound");
+      }
+    }
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+    expect(votingData.abstainCount.toNumber()).to.equal(0);
+    expect(votingData.vetoes).to.deep.equal([0, 0, 0]);
+
+    // A vote for the current round counts
+    await castVote(election, voter, 1, 1);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+  });
+
+  /**
+   * Test Case 56: Emergency halt
+   *
+   * This test verifies that after emergency_halt:
+   * - Votes and end_voting fail with Frozen
+   * - There is no way back: resume_voting fails too, and halting again is refused
+   * - It can't be rolled up into a parent with aggregate_to_parent, and a registry
+   *   election can't be released from its registry
+   * - The recorded votes stay readable
+   */
+  it("Freezes an election with emergency_halt", async () => {
+    const election = await createElection();
+    await castVote(election, await createVoter(election), 2);
+    const voter = await createVoter(election);
+    const adminAccounts = { votingAccount: election, authority: provider.wallet.publicKey };
+
+    await program.methods.emergencyHalt().accounts(adminAccounts).rpc();
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.frozen).to.be.true;
+
+    // Every change is refused
+    const parent = await createElection();
+    const attempts = [
+      () => castVote(election, voter, 0),
+      () => program.methods.endVoting().accounts(adminAccounts).rpc(),
+      () => program.methods.resumeVoting().accounts(adminAccounts).rpc(),
+      () => program.methods.emergencyHalt().accounts(adminAccounts).rpc(),
+      () =>
+        program.methods
+          .aggregateToParent()
+          .accounts({ child: election, parent, authority: provider.wallet.publicKey })
+          .rpc(),
+    ];
+    for (const attempt of attempts) {
+      try {
+        await attempt();
+        expect.fail("Expected transaction to fail as the election is frozen");
+      } catch (error) {
+        expect(error.toString()).to.include("Frozen");
+      }
+    }
+
+    // The state is preserved and still readable
+    const results = await program.methods
+      .getResults()
+      .accounts({ votingAccount: election })
+      .view();
+    expect(results.map((count) => count.toNumber())).to.deep.equal([0, 0, 1]);
+    const frozenData = await program.account.votingAccount.fetch(election);
+    expect(frozenData.status).to.deep.equal({ active: {} });
+    expect(frozenData.aggregated).to.be.false;
+
+    // A halted registry election keeps its slot in the registry
+    const authority = await createFundedKeypair();
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const [registryElection] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("election"),
+        authority.publicKey.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    await program.methods
+      .initializeRegistry(new anchor.BN(10))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .createElection(candidates, defaultConfig())
+      .accounts({
+        registry,
+        votingAccount: registryElection,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .emergencyHalt()
+      .accounts({ votingAccount: registryElection, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    try {
+      await program.methods
+        .releaseElection()
+        .accounts({ votingAccount: registryElection, registry })
+        .rpc();
+      expect.fail("Expected release_election to fail as the election is frozen");
+    } catch (error) {
+      expect(error.toString()).to.include("Frozen");
+    }
+    const registryData = await program.account.authorityRegistry.fetch(registry);
+    expect(registryData.activeElections.toNumber()).to.equal(1);
+  });
+
+  /**
+   * Test Case 57: Count candidates with votes
+   *
+   * This test verifies that nonzero_candidate_count emits the number of candidates
+   * with at least one vote, leaving out those still at zero
+   */
+  it("Counts only candidates that received votes", async () => {
+    const election = await createElection(defaultConfig(), [
+      "Alice",
+      "Bob",
+      "Charlie",
+      "Dana",
+    ]);
+    const countNonzero = async () => {
+      const { events } = await program.methods
+        .nonzeroCandidateCount()
+        .accounts({ votingAccount: election })
+        .simulate();
+      expect(events[0].name).to.equal("NonzeroCandidateCount");
+      return events[0].data.count;
+    };
+
+    // Nobody has votes yet
+    expect(await countNonzero()).to.equal(0);
+
+    // Alice gets two votes and Dana one; Bob and Charlie stay at zero
+    for (const id of [0, 0, 3]) {
+      await castVote(election, await createVoter(election), id);
+    }
+    expect(await countNonzero()).to.equal(2);
+  });
+
+  /**
+   * Test Case 58: Withdraw a candidate
+   *
+   * This test verifies that withdraw_candidate:
+   * - Zeroes the candidate's votes and subtracts them from total_votes
+   * - Records them in withdrawn_votes and disables the candidate
+   * - Is restricted to the authority and refused once voting has ended
+   */
+  it("Withdraws a candidate and adjusts the totals", async () => {
+    const election = await createElection();
+    for (const id of [0, 1, 1]) {
+      await castVote(election, await createVoter(election), id);
+    }
+    const withdraw = (index: number, authority: Keypair = payer) =>
+      program.methods
+        .withdrawCandidate(index)
+        .accounts({ votingAccount: election, authority: authority.publicKey })
+        .signers([authority])
+        .rpc();
+
+    // Only the authority can withdraw a candidate
+    try {
+      await withdraw(1, anchor.web3.Keypair.generate());
+      expect.fail("Expected transaction to fail for a non-authority");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+
+    // Bob's two votes leave the tally
+    await withdraw(1);
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([1, 0, 0]);
+    expect(votingData.totalVotes.toNumber()).to.equal(1);
+    expect(votingData.withdrawnVotes.toNumber()).to.equal(2);
+    expect(votingData.disabled[1]).to.be.true;
+
+    // The tally is fixed once voting has ended
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    try {
+      await withdraw(0);
+      expect.fail("Expected transaction to fail as voting has ended");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingAlreadyEnded");
+    }
+  });
+
+  /**
+   * Test Case 59: Seeded tie-break
+   *
+   * This test verifies that an election with the Seeded tie-break rule:
+   * - Stores a tie_break_seed when voting ends
+   * - Still records the tie, but names the leader the seed picks: the first 8 seed
+   *   bytes (little-endian) modulo the number of leaders, in ballot order
+   */
+  it("Breaks a tie deterministically with the stored seed", async () => {
+    const election = await createElection(defaultConfig({ tieBreak: { seeded: {} } }));
+    for (const id of [0, 2, 1, 2, 0]) {
+      await castVote(election, await createVoter(election), id);
+    }
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    const seed = Buffer.from(votingData.tieBreakSeed);
+    expect(seed.equals(Buffer.alloc(32))).to.be.false;
+    expect(votingData.isTie).to.be.true;
+
+    // Alice (0) and Charlie (2) are tied; the seed selects between them
+    const leaders = [0, 2];
+    const pick = new anchor.BN(seed.subarray(0, 8), "le").modn(leaders.length);
+    expect(votingData.winnerIndex).to.equal(leaders[pick]);
+    const winner = await program.methods
+      .getWinner()
+      .accounts({ votingAccount: election })
+      .view();
+    expect(winner).to.equal(leaders[pick]);
+  });
+
+  /**
+   * Test Case 60: Vote weight snapshot at registration
+   *
+   * This test verifies that:
+   * - Registering for a weighted election with a token account for another mint
+   *   fails with InvalidTokenAccount
+   * - The balance at registration is the vote weight, even if it grows afterwards
+   * - Voters registered without a token account have no weight
+   */
+  it("Weights votes by the balance snapshotted at registration", async () => {
+    const { electionAccount, voteMint, tokenAccount, voterAccount } =
+      await setupWeightedElection(1000, { clamp: {} }, 100);
+    const election = electionAccount.publicKey;
+    const userData = await program.account.userAccount.fetch(voterAccount);
+    expect(userData.snapshotWeight.toNumber()).to.equal(100);
+
+    // Registration with a token account for another mint is rejected
+    const otherMint = await createMint(provider.connection, payer, payer.publicKey, null, 0);
+    const voter = await createFundedKeypair(1);
+    const otherTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      otherMint,
+      voter.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    try {
+      await registerVoter(election, voter, otherTokenAccount);
+      expect.fail("Expected registration to fail due to the wrong mint");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidTokenAccount");
+    }
+
+    // Registering without a token account snapshots no weight
+    const plainAccount = await registerVoter(election, voter);
+    const plainData = await program.account.userAccount.fetch(plainAccount);
+    expect(plainData.snapshotWeight.toNumber()).to.equal(0);
+
+    // The balance grows after registration, but the vote counts the snapshot
+    await mintTo(provider.connection, payer, voteMint, tokenAccount, payer, 900);
+    await program.methods
+      .voteWeighted(0, new anchor.BN(0))
+      .accounts({
+        votingAccount: election,
+        userAccount: voterAccount,
+        user: provider.wallet.publicKey,
+        tokenAccount,
+        tokenSnapshot: null,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        roster: null,
+      })
+      .rpc();
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)[0]).to.equal(100);
+  });
+
+  /**
+   * Test Case 61: Migrate a voting account from the original layout
+   *
+   * The validator is started with tests/fixtures/legacy-voting-account.json, an ended
+   * election written by the original program: 449 bytes holding the candidates, u32
+   * vote counts and has_ended, with no authority. This test verifies that:
+   * - The old account can't be deserialized with the current layout
+   * - migrate_voting_account grows and rewrites it, widening the counts, mapping
+   *   has_ended to status, recording the outcome and giving the new fields their
+   *   initial values
+   * - Only the program's upgrade authority (the test wallet, as Anchor.toml deploys the
+   *   program as upgradeable) may migrate it, and it becomes the election's authority;
+   *   a random signer is rejected with Unauthorized, with or without ProgramData
+   * - A second migration fails with AlreadyMigrated
+   */
+  it("Migrates a voting account from the original layout", async () => {
+    const legacyElection = new PublicKey("GsKqqPbeBqQRQSmygutTj3r9Dcz1LBoi6NAn4CSHAoS6");
+    const [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    const migrateAs = (signer: Keypair, programDataAccount: PublicKey | null) =>
+      program.methods
+        .migrateVotingAccount()
+        .accounts({
+          votingAccount: legacyElection,
+          authority: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+          programData: programDataAccount,
+        })
+        .signers([signer])
+        .rpc();
+    const migrate = () => migrateAs(payer, programData);
+
+    // A random signer can't claim the authority-less election
+    const intruder = await createFundedKeypair();
+    for (const programDataAccount of [programData, null]) {
+      try {
+        await migrateAs(intruder, programDataAccount);
+        expect.fail("Expected a random signer to be rejected");
+      } catch (error) {
+        expect(error.toString()).to.include("Unauthorized");
+      }
+    }
+
+    // The old layout can't be read as the current one
+    try {
+      await program.account.votingAccount.fetch(legacyElection);
+      expect.fail("Expected the old layout to fail to deserialize");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Expected the old layout");
+    }
+
+    await migrate();
+
+    // The old data survives and the new fields start out as for a new election
+    const votingData = await program.account.votingAccount.fetch(legacyElection);
+    expect(votingData.version).to.equal(VOTING_ACCOUNT_VERSION);
+    expect(votingData.candidates).to.deep.equal(["Alice", "Bob", "Charlie"]);
+    expect(voteCounts(votingData)).to.deep.equal([2, 5, 1]);
+    expect(votingData.totalVotes.toNumber()).to.equal(8);
+    expect(votingData.status).to.deep.equal({ ended: {} });
+    expect(votingData.winnerIndex).to.equal(1);
+    expect(votingData.isTie).to.be.false;
+    expect(votingData.authority.toBase58()).to.equal(payer.publicKey.toBase58());
+    expect(votingData.displayNames).to.deep.equal(["Alice", "Bob", "Charlie"]);
+    expect(votingData.candidateIds.map((id) => id.toNumber())).to.deep.equal([0, 1, 2]);
+    expect(votingData.frozen).to.be.false;
+    expect(votingData.reachedMaxAt.map((at) => at.toNumber())).to.deep.equal([0, 0, 0]);
+    const accountInfo = await provider.connection.getAccountInfo(legacyElection);
+    expect(accountInfo.data.length).to.be.above(449);
+
+    // The account is migrated only once
+    try {
+      await migrate();
+      expect.fail("Expected the second migration to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyMigrated");
+    }
+  });
+
+  /**
+   * Test Case 62: Candidate name length limit
+   *
+   * This test verifies that names of exactly MAX_CANDIDATE_NAME_LEN (32) bytes are
+   * accepted at initialization and by add_candidates, while a name one byte longer
+   * fails with CandidateNameTooLong in both places
+   */
+  it("Enforces the candidate name length limit", async () => {
+    const atLimit = "a".repeat(32);
+    const overLimit = "b".repeat(33);
+
+    // At the limit the election is created and sized to hold the names
+    const election = await createElection(defaultConfig(), [atLimit, "Bob"]);
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.deep.equal([atLimit, "Bob"]);
+
+    // One byte over is rejected at initialization, for display names too
+    for (const [names, displayNames] of [
+      [[overLimit, "Bob"], []],
+      [["Alice", "Bob"], ["Alice", overLimit]],
+    ]) {
+      try {
+        await createElection(defaultConfig({ displayNames }), names);
+        expect.fail("Expected initialization to fail due to a long name");
+      } catch (error) {
+        expect(error.toString()).to.include("CandidateNameTooLong");
+      }
+    }
+
+    // add_candidates applies the same limit
+    const addCandidates = (names: string[]) =>
+      program.methods
+        .addCandidates(names)
+        .accounts({
+          authority: provider.wallet.publicKey,
+          votingAccount: election,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    await addCandidates(["c".repeat(32)]);
+    try {
+      await addCandidates([overLimit]);
+      expect.fail("Expected add_candidates to fail due to a long name");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateNameTooLong");
+    }
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.have.length(3);
+  });
+
+  /**
+   * Test Case 63: Remaining voting time
+   *
+   * This test verifies that time_remaining reports a positive countdown that shrinks
+   * as the cluster clock advances, and whether voting is paused
+   */
+  it("Counts down the time until the deadline", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const startedAt = await clusterTime();
+    const election = await createElection(
+      defaultConfig({ endTime: new anchor.BN(startedAt + 600) })
+    );
+    const timeRemaining = () =>
+      program.methods.timeRemaining().accounts({ votingAccount: election }).view();
+
+    const first = await timeRemaining();
+    const firstAt = await clusterTime();
+    expect(first.seconds.toNumber()).to.be.within(1, 600);
+    expect(first.paused).to.be.false;
+
+    // Wait for the clock to move on
+    while ((await clusterTime()) <= firstAt + 1) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    const second = await timeRemaining();
+    expect(second.seconds.toNumber()).to.be.above(0);
+    expect(second.seconds.toNumber()).to.be.below(first.seconds.toNumber());
+
+    // Pausing is reported alongside the countdown
+    await program.methods
+      .pauseVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    expect((await timeRemaining()).paused).to.be.true;
+  });
+
+  /**
+   * Test Case 64: Tie-break rules
+   *
+   * This test verifies that when Alice (0) and Charlie (2) tie, with Charlie reaching
+   * the top count first:
+   * - None leaves the election without a winner
+   * - LowestIndex names Alice
+   * - EarliestToReach names Charlie, using the recorded reached_max_at timestamps
+   */
+  it("Settles a tie with the configured tie-break rule", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const runTiedElection = async (tieBreak: object) => {
+      const election = await createElection(defaultConfig({ tieBreak }));
+      for (const id of [2, 2]) {
+        await castVote(election, await createVoter(election), id);
+      }
+      // Make sure Alice catches up in a later second than Charlie
+      const charlieAt = await clusterTime();
+      while ((await clusterTime()) <= charlieAt) {
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+      for (const id of [0, 0]) {
+        await castVote(election, await createVoter(election), id);
+      }
+      await program.methods
+        .endVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+      return program.account.votingAccount.fetch(election);
+    };
+
+    const none = await runTiedElection({ none: {} });
+    expect(none.isTie).to.be.true;
+    expect(none.winnerIndex).to.equal(NO_WINNER);
+    expect(Buffer.from(none.tieBreakSeed).equals(Buffer.alloc(32))).to.be.true;
+
+    const lowestIndex = await runTiedElection({ lowestIndex: {} });
+    expect(lowestIndex.isTie).to.be.true;
+    expect(lowestIndex.winnerIndex).to.equal(0);
+
+    const earliest = await runTiedElection({ earliestToReach: {} });
+    expect(earliest.isTie).to.be.true;
+    const reachedMaxAt = earliest.reachedMaxAt.map((at) => at.toNumber());
+    expect(reachedMaxAt[2]).to.be.below(reachedMaxAt[0]);
+    expect(reachedMaxAt[1]).to.equal(0);
+    expect(earliest.winnerIndex).to.equal(2);
+  });
+
+  /**
+   * Test Case 65: Turnout
+   *
+   * This test verifies that registrations are counted, and that ending voting stores
+   * the share of registered voters who voted in basis points
+   */
+  it("Records turnout against registered voters", async () => {
+    const election = await createElection(defaultConfig());
+    const voters = [];
+    for (let i = 0; i < 4; i++) {
+      voters.push(await createVoter(election));
+    }
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.registeredVoters.toNumber()).to.equal(4);
+    expect(votingData.turnoutBps).to.equal(0);
+
+    await castVote(election, voters[0], 0);
+    await castVote(election, voters[1], 1);
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.voterCount.toNumber()).to.equal(2);
+    expect(votingData.turnoutBps).to.equal(5000);
+  });
+
+  /**
+   * Test Case 66: Public voter roster
+   *
+   * This test verifies that in an election with public_roster:
+   * - The authority creates an empty roster PDA
+   * - Voting without the roster fails with RosterRequired
+   * - Each voter is listed on the roster (without their choice) after voting
+   * - Other elections reject a roster with RosterDisabled
+   */
+  it("Lists voters on the public roster", async () => {
+    const election = await createElection(defaultConfig({ publicRoster: true }));
+    const [roster] = PublicKey.findProgramAddressSync(
+      [Buffer.from("roster"), election.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeRoster()
+      .accounts({
+        votingAccount: election,
+        roster,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    let rosterData = await program.account.voterRoster.fetch(roster);
+    expect(rosterData.election.toBase58()).to.equal(election.toBase58());
+    expect(rosterData.voters).to.have.length(0);
+
+    const voteWith = (voter: Keypair, rosterAccount: PublicKey | null) =>
+      program.methods
+        .vote(new anchor.BN(0), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: rosterAccount,
+        })
+        .signers([voter])
+        .rpc();
+
+    // The roster can't be left out
+    const first = await createVoter(election);
+    try {
+      await voteWith(first, null);
+      expect.fail("Expected the vote to fail without the roster");
+    } catch (error) {
+      expect(error.toString()).to.include("RosterRequired");
+    }
+
+    const second = await createVoter(election);
+    await voteWith(first, roster);
+    await voteWith(second, roster);
+    rosterData = await program.account.voterRoster.fetch(roster);
+    expect(rosterData.voters.map((voter) => voter.toBase58())).to.deep.equal([
+      first.publicKey.toBase58(),
+      second.publicKey.toBase58(),
+    ]);
+
+    // Elections without a public roster don't keep one
+    const privateElection = await createElection(defaultConfig());
+    const [privateRoster] = PublicKey.findProgramAddressSync(
+      [Buffer.from("roster"), privateElection.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .initializeRoster()
+        .accounts({
+          votingAccount: privateElection,
+          roster: privateRoster,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("Expected initialize_roster to fail without public_roster");
+    } catch (error) {
+      expect(error.toString()).to.include("RosterDisabled");
+    }
+  });
+
+  /**
+   * Test Case 67: Dispute period
+   *
+   * This test verifies that get_winner fails with DisputePeriodActive until the
+   * dispute period after the end has elapsed, and reports the winner afterwards
+   */
+  it("Withholds the winner until the dispute period has elapsed", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const election = await createElection(
+      defaultConfig({ disputePeriodSecs: new anchor.BN(3) })
+    );
+    await castVote(election, await createVoter(election), 1);
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    const getWinner = () =>
+      program.methods.getWinner().accounts({ votingAccount: election }).view();
+
+    // Inside the window the result isn't final
+    try {
+      await getWinner();
+      expect.fail("Expected get_winner to fail during the dispute period");
+    } catch (error) {
+      expect(error.toString()).to.include("DisputePeriodActive");
+    }
+
+    // Once the window has passed the winner is reported
+    const { endTime } = await program.account.votingAccount.fetch(election);
+    while ((await clusterTime()) < endTime.toNumber() + 3) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    expect(await getWinner()).to.equal(1);
+  });
+
+  /**
+   * Test Case 68: Closing a user account
+   *
+   * This test verifies that:
+   * - User accounts can't be closed while voting is open (VotingNotEnded)
+   * - Another wallet can't close a voter's account
+   * - Once voting has ended the voter closes their account and gets its rent back
+   */
+  it("Refunds a voter's rent when they close their user account", async () => {
+    const election = await createElection(defaultConfig());
+    const voter = await createVoter(election);
+    await castVote(election, voter, 0);
+    const userAccount = userAccountFor(election, voter.publicKey);
+    const closeUserAccount = (user: Keypair, account: PublicKey = userAccount) =>
+      program.methods
+        .closeUserAccount()
+        .accounts({ votingAccount: election, userAccount: account, user: user.publicKey })
+        .signers([user])
+        .rpc();
+
+    // Not while the election is running
+    try {
+      await closeUserAccount(voter);
+      expect.fail("Expected close_user_account to fail before voting ended");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingNotEnded");
+    }
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Not by anyone else
+    const other = await createFundedKeypair(1);
+    try {
+      await closeUserAccount(other);
+      expect.fail("Expected close_user_account to fail for another wallet");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+
+    // The owner gets the account's lamports back (the provider pays the fee)
+    const rent = await provider.connection.getBalance(userAccount);
+    const before = await provider.connection.getBalance(voter.publicKey);
+    await closeUserAccount(voter);
+    const after = await provider.connection.getBalance(voter.publicKey);
+    expect(after - before).to.equal(rent);
+    expect(await provider.connection.getAccountInfo(userAccount)).to.be.null;
+  });
+
+  /**
+   * Test Case 69: Voting through CPI
+   *
+   * This test verifies that:
+   * - The election's cpi_caller program can vote for a registered voter by signing
+   *   with its delegate PDA, without the voter's signature
+   * - Any other signer is rejected with InvalidCpiDelegate
+   * - Elections without a cpi_caller reject CPI votes with CpiNotAllowed
+   */
+  it("Accepts votes cast through CPI by the allowed caller program", async () => {
+    const castThroughCaller = (election: PublicKey, voter: Keypair) => {
+      const [delegate] = PublicKey.findProgramAddressSync(
+        [Buffer.from("cpi_voter"), election.toBuffer(), voter.publicKey.toBuffer()],
+        mockCaller.programId
+      );
+      return mockCaller.methods
+        .castVote(new anchor.BN(2), new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          voter: voter.publicKey,
+          delegate,
+          payer: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          votingProgram: program.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    };
+
+    // The caller program votes for Charlie on the voter's behalf
+    const election = await createElection(
+      defaultConfig({ cpiCaller: mockCaller.programId })
+    );
+    const voter = await createVoter(election);
+    await castThroughCaller(election, voter);
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 0, 1]);
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, voter.publicKey)
+    );
+    expect(userData.hasVoted).to.be.true;
+    expect(userData.votedFor).to.equal(2);
+
+    // A keypair signing as the delegate is not the caller program's PDA
+    const other = await createVoter(election);
+    const impostor = await createFundedKeypair(1);
+    try {
+      await program.methods
+        .voteCpi(new anchor.BN(0), new anchor.BN(0), 255)
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, other.publicKey),
+          voter: other.publicKey,
+          delegate: impostor.publicKey,
+          payer: provider.wallet.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([impostor])
+        .rpc();
+      expect.fail("Expected vote_cpi to fail for a keypair delegate");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCpiDelegate");
+    }
+
+    // Elections that don't name a caller don't accept CPI votes
+    const closedElection = await createElection(defaultConfig());
+    const closedVoter = await createVoter(closedElection);
+    try {
+      await castThroughCaller(closedElection, closedVoter);
+      expect.fail("Expected the CPI vote to fail without a cpi_caller");
+    } catch (error) {
+      expect(error.toString()).to.include("CpiNotAllowed");
+    }
+  });
+
+  /**
+   * Test Case 70: Leaderboard
+   *
+   * This test verifies that get_leaderboard lists candidates by votes, with the
+   * leader first, and stops after 10 entries
+   */
+  it("Returns the leaderboard with the top candidate first", async () => {
+    const election = await createElection(defaultConfig());
+    for (const id of [1, 2, 1]) {
+      await castVote(election, await createVoter(election), id);
+    }
+    const getLeaderboard = () =>
+      program.methods.getLeaderboard().accounts({ votingAccount: election }).view();
+
+    let leaderboard = await getLeaderboard();
+    expect(
+      leaderboard.map((entry) => [entry.candidateIndex, entry.votes.toNumber()])
+    ).to.deep.equal([
+      [1, 2],
+      [2, 1],
+      [0, 0],
+    ]);
+
+    // Long ballots are cut off after the top 10
+    await program.methods
+      .addCandidates(Array.from({ length: 9 }, (_, i) => `Extra ${i}`))
+      .accounts({
+        authority: provider.wallet.publicKey,
+        votingAccount: election,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    leaderboard = await getLeaderboard();
+    expect(leaderboard).to.have.length(10);
+    expect(leaderboard[0].candidateIndex).to.equal(1);
+  });
+
+  /**
+   * Test Case 71: Reopening an ended election
+   *
+   * This test verifies that:
+   * - Within the reopen window, reopen_voting makes the election Active again with its
+   *   votes intact and the recorded outcome cleared, and voting continues
+   * - The reopened election gets back the deadline end_voting replaced
+   * - After the window, reopen_voting fails with ReopenWindowClosed
+   */
+  it("Reopens an election only within the reopen window", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const endVoting = (election: PublicKey) =>
+      program.methods
+        .endVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+    const reopenVoting = (election: PublicKey) =>
+      program.methods
+        .reopenVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+
+    // Reopened within the window
+    const deadline = (await clusterTime()) + 3600;
+    const election = await createElection(
+      defaultConfig({ reopenWindow: new anchor.BN(600), endTime: new anchor.BN(deadline) })
+    );
+    await castVote(election, await createVoter(election), 0);
+    await endVoting(election);
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.endedAt.toNumber()).to.be.above(0);
+    expect(votingData.endTime.toNumber()).to.be.below(deadline);
+    expect(votingData.winnerIndex).to.equal(0);
+
+    await reopenVoting(election);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ active: {} });
+    expect(votingData.endTime.toNumber()).to.equal(deadline);
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+    expect(voteCounts(votingData)).to.deep.equal([1, 0, 0]);
+
+    // Voting carries on until the election is ended again
+    await castVote(election, await createVoter(election), 1);
+    await castVote(election, await createVoter(election), 1);
+    await endVoting(election);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.winnerIndex).to.equal(1);
+
+    // Too late once the window has passed
+    const shortWindow = await createElection(
+      defaultConfig({ reopenWindow: new anchor.BN(1) })
+    );
+    await endVoting(shortWindow);
+    const { endedAt } = await program.account.votingAccount.fetch(shortWindow);
+    while ((await clusterTime()) <= endedAt.toNumber() + 1) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    try {
+      await reopenVoting(shortWindow);
+      expect.fail("Expected reopen_voting to fail after the window");
+    } catch (error) {
+      expect(error.toString()).to.include("ReopenWindowClosed");
+    }
+  });
+
+  /**
+   * Test Case 72: Derived account sizes
+   *
+   * This test verifies that user accounts and registries are created at the sizes
+   * InitSpace derives for them
+   */
+  it("Initializes accounts at their derived sizes", async () => {
+    const dataLength = async (address: PublicKey) =>
+      (await provider.connection.getAccountInfo(address)).data.length;
+
+    // User account: discriminator + UserAccount::INIT_SPACE
+    const election = await createElection(defaultConfig());
+    const voter = await createVoter(election);
+    expect(await dataLength(userAccountFor(election, voter.publicKey))).to.equal(
+      USER_ACCOUNT_SPACE
+    );
+    expect(await dataLength(userAccountFor(election, voter.publicKey))).to.equal(
+      program.account.userAccount.size
+    );
+
+    // Registry: discriminator + authority + three counters + bump
+    const authority = await createFundedKeypair(1);
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeRegistry(new anchor.BN(1))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    expect(await dataLength(registry)).to.equal(8 + 32 + 8 + 8 + 8 + 1);
+  });
+
+  /**
+   * Test Case 73: Candidate limit
+   *
+   * This test verifies that a ballot can be grown to exactly MAX_CANDIDATES (100)
+   * candidates with add_candidates, and that one more fails with TooManyCandidates
+   */
+  it("Accepts up to MAX_CANDIDATES candidates and rejects one more", async () => {
+    const MAX_CANDIDATES = 100;
+    const election = await createElection(defaultConfig());
+    const addCandidates = (names: string[]) =>
+      program.methods
+        .addCandidates(names)
+        .accounts({
+          authority: provider.wallet.publicKey,
+          votingAccount: election,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // Fill the ballot from 3 to 100 candidates in batches that fit a transaction
+    const names = Array.from({ length: MAX_CANDIDATES - 3 }, (_, i) => `C${i}`);
+    for (let start = 0; start < names.length; start += 25) {
+      await addCandidates(names.slice(start, start + 25));
+    }
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.have.length(MAX_CANDIDATES);
+
+    // The 101st candidate doesn't fit
+    try {
+      await addCandidates(["One too many"]);
+      expect.fail("Expected add_candidates to fail past MAX_CANDIDATES");
+    } catch (error) {
+      expect(error.toString()).to.include("TooManyCandidates");
+    }
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.have.length(MAX_CANDIDATES);
+  });
+
+  /**
+   * Test Case 74: Structured logs
+   *
+   * This test verifies that initialization, voting and ending voting each log a
+   * pipe-delimited line: INIT|election|authority|candidates, VOTE|voter|candidate|slot
+   * and END|election|winner|total votes
+   */
+  it("Logs pipe-delimited lines for log parsers", async () => {
+    const logsOf = async (signature: string) => {
+      await provider.connection.confirmTransaction(signature, "confirmed");
+      const transaction = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+      });
+      return transaction.meta.logMessages;
+    };
+    const election = Keypair.generate();
+    const authority = provider.wallet.publicKey.toBase58();
+
+    const initLogs = await logsOf(
+      await program.methods
+        .initialize(candidates, defaultConfig())
+        .accounts({
+          votingAccount: election.publicKey,
+          user: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([election])
+        .rpc()
+    );
+    expect(initLogs).to.include(
+      `Program log: INIT|${election.publicKey.toBase58()}|${authority}|3`
+    );
+
+    const voter = await createVoter(election.publicKey);
+    const voteSignature = await castVote(election.publicKey, voter, 2);
+    const voteLine = (await logsOf(voteSignature)).find((line) =>
+      line.startsWith("Program log: VOTE|")
+    );
+    const [, votedBy, candidateIndex, slot] = voteLine
+      .replace("Program log: ", "")
+      .split("|");
+    expect(votedBy).to.equal(voter.publicKey.toBase58());
+    expect(candidateIndex).to.equal("2");
+    expect(Number(slot)).to.be.above(0);
+
+    const endLogs = await logsOf(
+      await program.methods
+        .endVoting()
+        .accounts({ votingAccount: election.publicKey, authority: provider.wallet.publicKey })
+        .rpc()
+    );
+    expect(endLogs).to.include(`Program log: END|${election.publicKey.toBase58()}|2|1`);
+  });
+
+  /**
+   * Test Case 75: Eligible candidates
+   *
+   * This test verifies that:
+   * - A voter restricted to Alice and Charlie can't vote for Bob (CandidateNotEligible)
+   *   but can vote for Charlie
+   * - Only the authority can set the restriction, and only to real candidates
+   * - Unrestricted voters can still choose anyone
+   */
+  it("Restricts a voter to their eligible candidates", async () => {
+    const election = await createElection(defaultConfig());
+    const voter = await createVoter(election);
+    const userAccount = userAccountFor(election, voter.publicKey);
+    const setEligible = (indices: number[], authority?: Keypair) =>
+      program.methods
+        .setEligibleCandidates(indices)
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    // Only the authority sets the allow-list, and only with valid indices
+    try {
+      await setEligible([1], voter);
+      expect.fail("Expected set_eligible_candidates to fail for the voter");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+    try {
+      await setEligible([0, 5]);
+      expect.fail("Expected set_eligible_candidates to fail for a missing candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCandidate");
+    }
+
+    await setEligible([0, 2]);
+    const userData = await program.account.userAccount.fetch(userAccount);
+    expect(userData.eligibleCandidates).to.deep.equal([0, 2]);
+
+    // Bob is off limits, Charlie isn't
+    try {
+      await castVote(election, voter, 1);
+      expect.fail("Expected the vote for Bob to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateNotEligible");
+    }
+    await castVote(election, voter, 2);
+
+    // Other voters are unaffected
+    await castVote(election, await createVoter(election), 1);
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 1]);
+  });
+
+  /**
+   * Test Case 76: Cumulative voting
+   *
+   * This test verifies that in an election with a points budget of 10:
+   * - A ballot of 5, 3 and 2 points adds those points to the three candidates and
+   *   marks the voter as having voted without a single choice
+   * - Ballots spending fewer or more points fail with BudgetMismatch
+   * - Elections without a budget reject cumulative ballots
+   */
+  it("Distributes a points budget across candidates", async () => {
+    const election = await createElection(defaultConfig({ pointsBudget: 10 }));
+    const castCumulative = (
+      target: PublicKey,
+      voter: Keypair,
+      points: number[]
+    ) =>
+      program.methods
+        .castCumulative(
+          points.map((value, index) => ({ candidateIndex: index, points: value })),
+          new anchor.BN(0)
+        )
+        .accounts({
+          votingAccount: target,
+          userAccount: userAccountFor(target, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Under and over budget
+    const voter = await createVoter(election);
+    for (const points of [[5, 3, 1], [5, 3, 3]]) {
+      try {
+        await castCumulative(election, voter, points);
+        expect.fail("Expected the ballot to fail due to the budget");
+      } catch (error) {
+        expect(error.toString()).to.include("BudgetMismatch");
+      }
+    }
+
+    await castCumulative(election, voter, [5, 3, 2]);
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([5, 3, 2]);
+    expect(votingData.totalVotes.toNumber()).to.equal(10);
+    expect(votingData.voterCount.toNumber()).to.equal(1);
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, voter.publicKey)
+    );
+    expect(userData.hasVoted).to.be.true;
+    expect(userData.votedFor).to.be.null;
+
+    // Plain elections have no budget to distribute
+    const plainElection = await createElection(defaultConfig());
+    try {
+      await castCumulative(plainElection, await createVoter(plainElection), [1, 0, 0]);
+      expect.fail("Expected the ballot to fail without a points budget");
+    } catch (error) {
+      expect(error.toString()).to.include("CumulativeVotingDisabled");
+    }
+  });
+
+  /**
+   * Test Case 77: Checking eligibility before voting
+   *
+   * This test verifies that check_eligibility:
+   * - Returns true for a registered voter in an open election
+   * - Returns false once the voter has voted, and for voters in a paused election
+   * - Doesn't cast a vote itself
+   */
+  it("Reports whether a voter is eligible", async () => {
+    const election = await createElection(defaultConfig());
+    const voter = await createVoter(election);
+    const checkEligibility = (target: PublicKey, user: Keypair) =>
+      program.methods
+        .checkEligibility()
+        .accounts({
+          votingAccount: target,
+          userAccount: userAccountFor(target, user.publicKey),
+          user: user.publicKey,
+        })
+        .view();
+
+    // Eligible, and checking doesn't vote
+    expect(await checkEligibility(election, voter)).to.be.true;
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+
+    // Ineligible after voting
+    await castVote(election, voter, 0);
+    expect(await checkEligibility(election, voter)).to.be.false;
+
+    // Ineligible while the election is paused
+    const pausedElection = await createElection(defaultConfig());
+    const pausedVoter = await createVoter(pausedElection);
+    await program.methods
+      .pauseVoting()
+      .accounts({
+        votingAccount: pausedElection,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    expect(await checkEligibility(pausedElection, pausedVoter)).to.be.false;
+  });
+
+  /**
+   * Test Case 78: Health check
+   *
+   * This test verifies that health_check reports a valid election as healthy, both while
+   * voting and after it has ended. (Instructions can't corrupt the account, so the
+   * failure cases are covered by the Rust unit tests.)
+   */
+  it("Reports a consistent election as healthy", async () => {
+    const election = await createElection();
+    const healthCheck = async () => {
+      const { events } = await program.methods
+        .healthCheck()
+        .accounts({ votingAccount: election })
+        .simulate();
+      expect(events[0].name).to.equal("HealthReport");
+      return events[0].data;
+    };
+
+    for (const id of [0, 2, 2]) {
+      await castVote(election, await createVoter(election), id);
+    }
+    let report = await healthCheck();
+    expect(report.ok).to.be.true;
+    expect(report.details).to.be.empty;
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    report = await healthCheck();
+    expect(report.ok).to.be.true;
+  });
+
+  /**
+   * Test Case 79: Quorum as a share of the token supply
+   *
+   * This test verifies that with a 40% quorum over a supply of 1000 tokens:
+   * - 300 weighted votes leave the election without a reported winner (QuorumNotReached)
+   * - 400 weighted votes meet the quorum and get_winner reports the winner
+   * - Quorums above 100% are rejected at creation
+   */
+  it("Requires a quorum of the token supply", async () => {
+    const runElection = async (balance: number) => {
+      const { electionAccount, voteMint, tokenAccount, voterAccount } =
+        await setupWeightedElection(1_000, { clamp: {} }, balance, {
+          quorumBps: 4_000,
+        });
+
+      // The rest of the 1000 tokens sit in an account that doesn't vote
+      const idleAccount = await createAccount(
+        provider.connection,
+        payer,
+        voteMint,
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate()
+      );
+      await mintTo(
+        provider.connection,
+        payer,
+        voteMint,
+        idleAccount,
+        payer,
+        1_000 - balance
+      );
+      await program.methods
+        .snapshotSupply()
+        .accounts({
+          votingAccount: electionAccount.publicKey,
+          voteMint,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: electionAccount.publicKey,
+          userAccount: voterAccount,
+          user: provider.wallet.publicKey,
+          tokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .rpc();
+      await program.methods
+        .endVoting()
+        .accounts({
+          votingAccount: electionAccount.publicKey,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+      return program.methods
+        .getWinner()
+        .accounts({ votingAccount: electionAccount.publicKey })
+        .view();
+    };
+
+    // 30% of the supply voted
+    try {
+      await runElection(300);
+      expect.fail("Expected get_winner to fail below the quorum");
+    } catch (error) {
+      expect(error.toString()).to.include("QuorumNotReached");
+    }
+
+    // 40% of the supply voted
+    expect(await runElection(400)).to.equal(0);
+
+    // A quorum can't exceed the whole supply
+    try {
+      await setupWeightedElection(1_000, { clamp: {} }, 1, { quorumBps: 10_001 });
+      expect.fail("Expected initialize to fail for a quorum above 100%");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidQuorum");
+    }
+  });
+
+  /**
+   * Test Case 80: Sponsored registration with the voter's signature
+   *
+   * This test verifies that register_with_authorization:
+   * - Creates the voter's user account, paid for by the sponsor, when the preceding
+   *   ed25519 instruction verifies the voter's signature of the registration message
+   * - Rejects a signature of a different message, and a missing signature check, with
+   *   InvalidSignature
+   */
+  it("Registers a voter authorized by their signature", async () => {
+    const election = await createElection();
+    const registrationMessage = (target: PublicKey, voter: PublicKey) =>
+      Buffer.concat([
+        Buffer.from("solana-voting:register:"),
+        target.toBuffer(),
+        voter.toBuffer(),
+      ]);
+    const register = (voter: Keypair, message: Buffer | null) => {
+      const builder = program.methods
+        .registerWithAuthorization(
+          voter.publicKey,
+          Array.from(
+            message
+              ? Ed25519Program.createInstructionWithPrivateKey({
+                  privateKey: voter.secretKey,
+                  message,
+                }).data.subarray(48, 112)
+              : Buffer.alloc(64)
+          )
+        )
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          sponsor: provider.wallet.publicKey,
+          authority: null,
+          systemProgram: SystemProgram.programId,
+          tokenAccount: null,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        });
+      return message
+        ? builder
+            .preInstructions([
+              Ed25519Program.createInstructionWithPrivateKey({
+                privateKey: voter.secretKey,
+                message,
+              }),
+            ])
+            .rpc()
+        : builder.rpc();
+    };
+
+    // A signature for another election, and no signature check at all
+    const voter = Keypair.generate();
+    for (const message of [registrationMessage(PublicKey.unique(), voter.publicKey), null]) {
+      try {
+        await register(voter, message);
+        expect.fail("Expected the registration to fail without a valid signature");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidSignature");
+      }
+    }
+
+    // A valid authorization registers the voter
+    await register(voter, registrationMessage(election, voter.publicKey));
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, voter.publicKey)
+    );
+    expect(userData.voter.toBase58()).to.equal(voter.publicKey.toBase58());
+    expect(userData.election.toBase58()).to.equal(election.toBase58());
+    expect(userData.hasVoted).to.be.false;
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.registeredVoters.toNumber()).to.equal(1);
+  });
+
+  /**
+   * Test Case 81: Hidden tallies across every getter
+   *
+   * This test verifies that while a results_hidden election runs:
+   * - get_candidate reports the candidate with a vote count of 0
+   * - get_leaderboard and get_rankings fail with ResultsHidden
+   * and that after it ends every getter reports the real tallies
+   */
+  it("Hides every live tally until the end", async () => {
+    const election = await createElection(defaultConfig({ resultsHidden: true }));
+    for (const index of [1, 1, 0]) {
+      await castVote(election, await createVoter(election), index);
+    }
+    const candidateVotes = async (index: number) => {
+      const { events } = await program.methods
+        .getCandidate(index)
+        .accounts({ votingAccount: election })
+        .simulate();
+      expect(events[0].data.name).to.equal(candidates[index]);
+      return events[0].data.votes.toNumber();
+    };
+
+    // Hidden during voting
+    expect(await candidateVotes(1)).to.equal(0);
+    for (const method of ["getLeaderboard", "getRankings"]) {
+      try {
+        await program.methods[method]().accounts({ votingAccount: election }).view();
+        expect.fail("Expected the getter to fail while results are hidden");
+      } catch (error) {
+        expect(error.toString()).to.include("ResultsHidden");
+      }
+    }
+
+    // Revealed once voting has ended
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    expect(await candidateVotes(1)).to.equal(2);
+    const leaderboard = await program.methods
+      .getLeaderboard()
+      .accounts({ votingAccount: election })
+      .view();
+    expect(leaderboard[0].candidateIndex).to.equal(1);
+    expect(leaderboard[0].votes.toNumber()).to.equal(2);
+  });
+
+  /**
+   * Test Case 82: Seeding vote counts for a migration
+   *
+   * This test verifies that seed_votes:
+   * - Sets the starting counts and total_votes, which later votes add to
+   * - Is restricted to the authority and to one count per candidate
+   * - Fails with CannotSeedAfterStart once votes have been cast
+   */
+  it("Seeds starting vote counts before voting starts", async () => {
+    const election = await createElection();
+    const seedVotes = (counts: number[], authority?: Keypair) =>
+      program.methods
+        .seedVotes(counts.map((count) => new anchor.BN(count)))
+        .accounts({
+          votingAccount: election,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc();
+
+    // Only the authority, and only with a count per candidate
+    try {
+      await seedVotes([4, 2, 1], await createFundedKeypair(1));
+      expect.fail("Expected seed_votes to fail for a non-authority");
+    } catch (error) {
+      expect(error.toString()).to.include("Unauthorized");
+    }
+    try {
+      await seedVotes([4, 2]);
+      expect.fail("Expected seed_votes to fail for a short list of counts");
+    } catch (error) {
+      expect(error.toString()).to.include("SeedCountsMismatch");
+    }
+
+    await seedVotes([4, 2, 1]);
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([4, 2, 1]);
+    expect(votingData.totalVotes.toNumber()).to.equal(7);
+
+    // Votes add to the seeded counts, after which seeding is closed
+    await castVote(election, await createVoter(election), 2);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([4, 2, 2]);
+    try {
+      await seedVotes([0, 0, 9]);
+      expect.fail("Expected seed_votes to fail after voting started");
+    } catch (error) {
+      expect(error.toString()).to.include("CannotSeedAfterStart");
+    }
+  });
+
+  /**
+   * Test Case 83: Undoing a vote
+   *
+   * This test verifies that:
+   * - Within the undo window a voter can take their vote back and vote again
+   * - After the window has passed undo_vote fails with UndoWindowClosed
+   * - Users without a vote to undo get NoVoteToUndo
+   */
+  it("Undoes a vote within the undo window", async () => {
+    const election = await createElection(
+      defaultConfig({ undoWindow: new anchor.BN(2) })
+    );
+    const voter = await createVoter(election);
+    const undoVote = () =>
+      program.methods
+        .undoVote()
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Nothing to undo before voting
+    try {
+      await undoVote();
+      expect.fail("Expected undo_vote to fail without a vote");
+    } catch (error) {
+      expect(error.toString()).to.include("NoVoteToUndo");
+    }
+
+    // Undo right away, then vote for someone else
+    await castVote(election, voter, 0);
+    await undoVote();
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 0, 0]);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, voter.publicKey)
+    );
+    expect(userData.hasVoted).to.be.false;
+    expect(userData.votedFor).to.be.null;
+
+    await castVote(election, voter, 1);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+
+    // Once the window has passed the vote stands
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    try {
+      await undoVote();
+      expect.fail("Expected undo_vote to fail after the window");
+    } catch (error) {
+      expect(error.toString()).to.include("UndoWindowClosed");
+    }
+  });
+
+  /**
+   * Test Case 84: Archived results hash
+   *
+   * This test verifies that end_voting and close_voting both emit a ResultsArchived
+   * event whose hash matches the SHA-256 of the Borsh-encoded final candidates and votes
+   */
+  it("Emits a verifiable hash of the final results", async () => {
+    const election = await createElection();
+    for (const index of [0, 2, 2]) {
+      await castVote(election, await createVoter(election), index);
+    }
+
+    // Recompute the hash from the encoding: u32 lengths, strings, u64 counts
+    const u32 = (value: number) => {
+      const buffer = Buffer.alloc(4);
+      buffer.writeUInt32LE(value);
+      return buffer;
+    };
+    const u64 = (value: number) => {
+      const buffer = Buffer.alloc(8);
+      buffer.writeBigUInt64LE(BigInt(value));
+      return buffer;
+    };
+    const expected = createHash("sha256")
+      .update(
+        Buffer.concat([
+          u32(candidates.length),
+          ...candidates.flatMap((name) => [u32(Buffer.byteLength(name)), Buffer.from(name)]),
+          u32(3),
+          ...[1, 0, 2].map(u64),
+        ])
+      )
+      .digest();
+    const archived = (events: { name: string; data: any }[]) => {
+      const event = events.find((event) => event.name === "ResultsArchived");
+      expect(event.data.totalVotes.toNumber()).to.equal(3);
+      return Buffer.from(event.data.hash);
+    };
+
+    const accounts = { votingAccount: election, authority: provider.wallet.publicKey };
+    const ended = await program.methods.endVoting().accounts(accounts).simulate();
+    expect(archived(ended.events).equals(expected)).to.be.true;
+    await program.methods.endVoting().accounts(accounts).rpc();
+
+    const closed = await program.methods.closeVoting().accounts(accounts).simulate();
+    expect(archived(closed.events).equals(expected)).to.be.true;
+  });
+
+  /**
+   * Test Case 85: Disqualifying a candidate
+   *
+   * This test verifies that after the leading candidate is disqualified:
+   * - Their votes stay on record, but new votes for them fail with CandidateDisqualified
+   * - end_voting declares the runner-up the winner
+   */
+  it("Disqualifies a candidate without removing them", async () => {
+    const election = await createElection();
+    for (const index of [0, 0, 0, 1, 1, 2]) {
+      await castVote(election, await createVoter(election), index);
+    }
+
+    await program.methods
+      .disqualifyCandidate(0)
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.disqualified).to.deep.equal([true, false, false]);
+    expect(voteCounts(votingData)).to.deep.equal([3, 2, 1]);
+
+    // No new votes for the disqualified candidate
+    try {
+      await castVote(election, await createVoter(election), 0);
+      expect.fail("Expected the vote to fail for a disqualified candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateDisqualified");
+    }
+
+    // The runner-up wins
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.winnerIndex).to.equal(1);
+    expect(voteCounts(votingData)).to.deep.equal([3, 2, 1]);
+  });
+
+  /**
+   * Test Case 86: Several questions in one election
+   *
+   * This test verifies that:
+   * - add_ballot adds extra questions, each with its own options and tally
+   * - A voter answers each question once with vote_ballot, independently of the main
+   *   ballot, and a second answer to the same question fails with AlreadyVoted
+   * - Unknown ballots fail with InvalidBallot
+   */
+  it("Votes on two ballots within one election", async () => {
+    const election = await createElection();
+    const addBallot = (question: string, options: string[]) =>
+      program.methods
+        .addBallot(question, options)
+        .accounts({
+          authority: provider.wallet.publicKey,
+          votingAccount: election,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    await addBallot("Approve the budget?", ["Yes", "No"]);
+    await addBallot("Meeting day?", ["Monday", "Wednesday", "Friday"]);
+    const voteBallot = (voter: Keypair, ballotIndex: number, optionIndex: number) =>
+      program.methods
+        .voteBallot(ballotIndex, optionIndex, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Two voters answer both questions; one also votes on the main ballot
+    const first = await createVoter(election);
+    const second = await createVoter(election);
+    await castVote(election, first, 1);
+    await voteBallot(first, 0, 0);
+    await voteBallot(first, 1, 2);
+    await voteBallot(second, 0, 1);
+    await voteBallot(second, 1, 2);
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+    expect(votingData.ballots.map((ballot) => ballot.question)).to.deep.equal([
+      "Approve the budget?",
+      "Meeting day?",
+    ]);
+    expect(votingData.ballots.map((ballot) => voteCounts(ballot))).to.deep.equal([
+      [1, 1],
+      [0, 0, 2],
+    ]);
+
+    // One answer per question
+    try {
+      await voteBallot(first, 0, 1);
+      expect.fail("Expected a second answer to the same ballot to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyVoted");
+    }
+    try {
+      await voteBallot(second, 2, 0);
+      expect.fail("Expected the vote to fail for a missing ballot");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidBallot");
+    }
+  });
+
+  /**
+   * Test Case 87: Double initialization
+   *
+   * This test verifies that an election account is marked initialized and can't be
+   * initialized a second time, neither as a keypair account through initialize nor as
+   * a PDA passed to create_election again
+   */
+  it("Refuses to initialize an election twice", async () => {
+    // Keypair path: initializing the same account again fails
+    const electionKeypair = anchor.web3.Keypair.generate();
+    const initialize = () =>
+      program.methods
+        .initialize(candidates, defaultConfig())
+        .accounts({
+          votingAccount: electionKeypair.publicKey,
+          user: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([electionKeypair])
+        .rpc();
+    await initialize();
+    const votingData = await program.account.votingAccount.fetch(
+      electionKeypair.publicKey
+    );
+    expect(votingData.isInitialized).to.be.true;
+    try {
+      await initialize();
+      expect.fail("Expected the second initialize to fail");
+    } catch (error) {
+      // The system program refuses to create an account that is already in use
+      expect(error.toString()).to.not.include("Expected the second initialize");
+    }
+
+    // PDA path: an existing election can't be passed to create_election again (the
+    // registry has moved on to the next address)
+    const authority = await createFundedKeypair();
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const [election] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("election"),
+        authority.publicKey.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    await program.methods
+      .initializeRegistry(new anchor.BN(5))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    const createElection = () =>
+      program.methods
+        .createElection(candidates, defaultConfig())
+        .accounts({
+          registry,
+          votingAccount: election,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    await createElection();
+    try {
+      await createElection();
+      expect.fail("Expected the second create_election to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+    const registryData = await program.account.authorityRegistry.fetch(registry);
+    expect(registryData.electionCount.toNumber()).to.equal(1);
+  });
+
+  /**
+   * Test Case 88: Capping an authority's active elections
+   *
+   * This test verifies that with max_active_elections set to 2:
+   * - Creating a third running election fails with TooManyActiveElections
+   * - Ending an election alone doesn't free a slot; releasing it (or closing it) does
+   * - An election can only be released once it has ended, and only once
+   */
+  it("Limits how many elections an authority runs at once", async () => {
+    const authority = await createFundedKeypair();
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const electionPda = (id: number) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("election"),
+          authority.publicKey.toBuffer(),
+          new anchor.BN(id).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+    const createElectionPda = (id: number) =>
+      program.methods
+        .createElection(candidates, defaultConfig())
+        .accounts({
+          registry,
+          votingAccount: electionPda(id),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+    const releaseElection = (id: number) =>
+      program.methods
+        .releaseElection()
+        .accounts({ votingAccount: electionPda(id), registry })
+        .rpc();
+    const expectCapReached = async (id: number) => {
+      try {
+        await createElectionPda(id);
+        expect.fail("Expected create_election to fail at the active cap");
+      } catch (error) {
+        expect(error.toString()).to.include("TooManyActiveElections");
+      }
+    };
+
+    await program.methods
+      .initializeRegistry(new anchor.BN(10))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .setMaxActiveElections(new anchor.BN(2))
+      .accounts({ registry, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+
+    await createElectionPda(0);
+    await createElectionPda(1);
+    await expectCapReached(2);
+
+    // A running election can't be released
+    try {
+      await releaseElection(0);
+      expect.fail("Expected release_election to fail for a running election");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingNotEnded");
+    }
+
+    // Ending is not enough, releasing frees the slot
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: electionPda(0), authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await expectCapReached(2);
+    await releaseElection(0);
+    await createElectionPda(2);
+    try {
+      await releaseElection(0);
+      expect.fail("Expected a second release to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("ElectionAlreadyReleased");
+    }
+
+    // Closing an ended election frees its slot too
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: electionPda(1), authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .closeElection()
+      .accounts({ votingAccount: electionPda(1), registry, authority: authority.publicKey })
+      .signers([authority])
+      .rpc();
+    await createElectionPda(3);
+    const registryData = await program.account.authorityRegistry.fetch(registry);
+    expect(registryData.activeElections.toNumber()).to.equal(2);
+  });
+
+  /**
+   * Test Case 89: Tally checkpoints
+   *
+   * This test verifies that checkpoint writes the vote total and a SHA-256 hash of the
+   * Borsh-serialized votes vector to the election's checkpoint PDA, and that checkpointing
+   * again after a vote overwrites it with a different hash
+   */
+  it("Checkpoints the tally for light clients", async () => {
+    const election = await createElection();
+    const [checkpoint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("checkpoint"), election.toBuffer()],
+      program.programId
+    );
+    const writeCheckpoint = () =>
+      program.methods
+        .checkpoint()
+        .accounts({
+          votingAccount: election,
+          checkpoint,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    // What a light client computes from the counts it was given
+    const expectedHash = (votes: number[]) => {
+      const data = Buffer.alloc(4 + 8 * votes.length);
+      data.writeUInt32LE(votes.length, 0);
+      votes.forEach((count, i) => data.writeBigUInt64LE(BigInt(count), 4 + 8 * i));
+      return createHash("sha256").update(data).digest();
+    };
+
+    await writeCheckpoint();
+    const first = await program.account.checkpoint.fetch(checkpoint);
+    expect(first.votingAccount.toBase58()).to.equal(election.toBase58());
+    expect(first.totalVotes.toNumber()).to.equal(0);
+    expect(Buffer.from(first.votesHash)).to.deep.equal(expectedHash([0, 0, 0]));
+
+    const voter = await createVoter(election);
+    await castVote(election, voter, 1);
+    await writeCheckpoint();
+    const second = await program.account.checkpoint.fetch(checkpoint);
+    expect(second.totalVotes.toNumber()).to.equal(1);
+    expect(Buffer.from(second.votesHash)).to.deep.equal(expectedHash([0, 1, 0]));
+    expect(Buffer.from(second.votesHash)).to.not.deep.equal(Buffer.from(first.votesHash));
+    expect(second.slot.toNumber()).to.be.at.least(first.slot.toNumber());
+  });
+
+  /**
+   * Test Case 90: Renaming a candidate
+   *
+   * This test verifies that rename_candidate:
+   * - Replaces the name (growing the account for a longer one) and leaves the votes alone
+   * - Rejects an index outside the ballot with InvalidCandidate
+   * - Rejects another candidate's name with DuplicateCandidate
+   */
+  it("Renames a candidate without touching the votes", async () => {
+    const election = await createElection(defaultConfig(), ["Alice", "Bbo", "Charlie"]);
+    const voter = await createVoter(election);
+    await castVote(election, voter, 1);
+    const renameCandidate = (index: number, name: string) =>
+      program.methods
+        .renameCandidate(index, name)
+        .accounts({
+          votingAccount: election,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await renameCandidate(1, "Bob Builder");
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.candidates).to.deep.equal(["Alice", "Bob Builder", "Charlie"]);
+    expect(votingData.displayNames[1]).to.equal("Bob Builder");
+    expect(voteCounts(votingData)).to.deep.equal([0, 1, 0]);
+
+    try {
+      await renameCandidate(3, "Dave");
+      expect.fail("Expected an unknown candidate to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidCandidate");
+    }
+
+    try {
+      await renameCandidate(0, "Charlie");
+      expect.fail("Expected a duplicate name to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("DuplicateCandidate");
+    }
+  });
+
+  /**
+   * Test Case 91: Votes with a reason
+   *
+   * This test verifies that:
+   * - vote_with_reason counts the vote and stores the choice and reason on the receipt
+   * - With require_reason set, plain vote and an empty reason fail with ReasonRequired
+   * - A reason over 200 bytes fails with ReasonTooLong
+   * - Elections without require_reason still take plain votes
+   */
+  it("Records a reason with each vote when required", async () => {
+    const receiptFor = (election: PublicKey, voter: Keypair) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("receipt"), election.toBuffer(), voter.publicKey.toBuffer()],
+        program.programId
+      )[0];
+    const voteWithReason = (
+      election: PublicKey,
+      voter: Keypair,
+      candidateIndex: number,
+      reason: string
+    ) =>
+      program.methods
+        .voteWithReason(candidateIndex, new anchor.BN(0), reason)
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          voteReceipt: receiptFor(election, voter),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    const deliberative = await createElection(defaultConfig({ requireReason: true }));
+    const voter = await createVoter(deliberative);
+    try {
+      await castVote(deliberative, voter, 0);
+      expect.fail("Expected a plain vote to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("ReasonRequired");
+    }
+    try {
+      await voteWithReason(deliberative, voter, 0, " ");
+      expect.fail("Expected an empty reason to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("ReasonRequired");
+    }
+    try {
+      await voteWithReason(deliberative, voter, 0, "x".repeat(201));
+      expect.fail("Expected an overlong reason to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("ReasonTooLong");
+    }
+
+    await voteWithReason(deliberative, voter, 2, "Best plan for the library");
+    const receipt = await program.account.voteReceipt.fetch(receiptFor(deliberative, voter));
+    expect(receipt.voter.toBase58()).to.equal(voter.publicKey.toBase58());
+    expect(receipt.candidateId.toNumber()).to.equal(2);
+    expect(receipt.reason).to.equal("Best plan for the library");
+    const votingData = await program.account.votingAccount.fetch(deliberative);
+    expect(voteCounts(votingData)).to.deep.equal([0, 0, 1]);
+
+    // Without require_reason the plain vote still works
+    const plain = await createElection();
+    const plainVoter = await createVoter(plain);
+    await castVote(plain, plainVoter, 1);
+    const plainData = await program.account.votingAccount.fetch(plain);
+    expect(voteCounts(plainData)).to.deep.equal([0, 1, 0]);
+  });
+
+  /**
+   * Test Case 92: Ending an election nobody voted in
+   *
+   * This test verifies that when an election ends with no votes:
+   * - winnerIndex is the NO_WINNER sentinel (u32::MAX, the program's "-1") and no tie is
+   *   recorded, even with a tie-break rule that would otherwise pick candidate 0
+   * - A NoVotesCast event is emitted
+   */
+  it("Records no winner when no votes were cast", async () => {
+    const election = await createElection(
+      defaultConfig({ tieBreak: { lowestIndex: {} } })
+    );
+
+    // Listen for the NoVotesCast event before ending the election
+    let noVotesEvent = null;
+    const listener = program.addEventListener("NoVotesCast", (event) => {
+      noVotesEvent = event;
+    });
+
+    await program.methods
+      .endVoting()
+      .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+      .rpc();
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+    expect(votingData.winnerIndex).to.equal(NO_WINNER);
+    expect(votingData.isTie).to.be.false;
+
+    // Give the listener a moment to receive the event, then verify it
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(noVotesEvent).to.not.be.null;
+    expect(noVotesEvent.votingAccount.toBase58()).to.equal(election.toBase58());
+  });
+
+  /**
+   * Test Case 93: Revoting
+   *
+   * This test verifies that with allow_revote set a user's second vote counts again
+   * (while voter_count still counts them once), and that without it the second vote
+   * fails with AlreadyVoted
+   */
+  it("Counts repeat votes only when the election allows revoting", async () => {
+    const reactions = await createElection(defaultConfig({ allowRevote: true }));
+    const voter = await createVoter(reactions);
+    // (Different candidates, so the two transactions aren't identical duplicates)
+    await castVote(reactions, voter, 0);
+    await castVote(reactions, voter, 2);
+    const votingData = await program.account.votingAccount.fetch(reactions);
+    expect(voteCounts(votingData)).to.deep.equal([1, 0, 1]);
+    expect(votingData.totalVotes.toNumber()).to.equal(2);
+    expect(votingData.voterCount.toNumber()).to.equal(1);
+
+    const strict = await createElection();
+    const strictVoter = await createVoter(strict);
+    await castVote(strict, strictVoter, 0);
+    try {
+      await castVote(strict, strictVoter, 0);
+      expect.fail("Expected the second vote to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyVoted");
+    }
+  });
+
+  /**
+   * Test Case 94: Title and description
+   *
+   * This test verifies that the title and description given at initialization are
+   * stored, that update_metadata replaces them, and that a title over 128 bytes or a
+   * description over 512 bytes fails with MetadataTooLong
+   */
+  it("Stores and updates the election's title and description", async () => {
+    const election = await createElection(
+      defaultConfig({
+        title: "Library board 2026",
+        description: "Choose one candidate for the open board seat.",
+      })
+    );
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.title).to.equal("Library board 2026");
+    expect(votingData.description).to.equal("Choose one candidate for the open board seat.");
+
+    const updateMetadata = (title: string, description: string) =>
+      program.methods
+        .updateMetadata(title, description)
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+    const longDescription = "d".repeat(512);
+    await updateMetadata("t".repeat(128), longDescription);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.title).to.equal("t".repeat(128));
+    expect(votingData.description).to.equal(longDescription);
+
+    for (const [title, description] of [
+      ["t".repeat(129), ""],
+      ["", "d".repeat(513)],
+    ]) {
+      try {
+        await updateMetadata(title, description);
+        expect.fail("Expected oversized metadata to be rejected");
+      } catch (error) {
+        expect(error.toString()).to.include("MetadataTooLong");
+      }
+    }
+  });
+
+  /**
+   * Test Case 95: Election summary
+   *
+   * This test verifies that get_summary reports the title, candidate count, vote total,
+   * pause and end flags, schedule and (once ended) the winner
+   */
+  it("Summarizes the election in one read", async () => {
+    const election = await createElection(defaultConfig({ title: "Park renovation" }));
+    const getSummary = () =>
+      program.methods.getSummary().accounts({ votingAccount: election }).view();
+    const authorityAccounts = {
+      votingAccount: election,
+      authority: provider.wallet.publicKey,
+    };
+    await castVote(election, await createVoter(election), 1);
+
+    let summary = await getSummary();
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(summary.title).to.equal("Park renovation");
+    expect(summary.candidateCount).to.equal(candidates.length);
+    expect(summary.totalVotes.toNumber()).to.equal(1);
+    expect(summary.hasEnded).to.be.false;
+    expect(summary.isPaused).to.be.false;
+    expect(summary.startTs.toNumber()).to.equal(votingData.startTime.toNumber());
+    expect(summary.endTs.toNumber()).to.equal(0);
+    expect(summary.winnerIndex).to.be.null;
+
+    await program.methods.pauseVoting().accounts(authorityAccounts).rpc();
+    summary = await getSummary();
+    expect(summary.isPaused).to.be.true;
+
+    await program.methods.endVoting().accounts(authorityAccounts).rpc();
+    summary = await getSummary();
+    expect(summary.hasEnded).to.be.true;
+    expect(summary.isPaused).to.be.false;
+    expect(summary.winnerIndex).to.equal(1);
+  });
+
+  /**
+   * Test Case 96: Rolling sub-elections up into a parent
+   *
+   * This test verifies that aggregate_to_parent:
+   * - Adds each ended child's votes to the ended parent's, and recomputes its winner
+   * - Refuses to aggregate the same child twice (AlreadyAggregated)
+   * - Refuses a child whose candidates differ from the parent's (CandidateMismatch)
+   * - Refuses a child that is still running (VotingNotEnded)
+   */
+  it("Aggregates sub-election votes into the parent election", async () => {
+    const endVoting = (election: PublicKey) =>
+      program.methods
+        .endVoting()
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+    const aggregate = (child: PublicKey, parent: PublicKey) =>
+      program.methods
+        .aggregateToParent()
+        .accounts({ child, parent, authority: provider.wallet.publicKey })
+        .rpc();
+    const expectAggregateError = async (
+      child: PublicKey,
+      parent: PublicKey,
+      code: string
+    ) => {
+      try {
+        await aggregate(child, parent);
+        expect.fail(`Expected aggregate_to_parent to fail with ${code}`);
+      } catch (error) {
+        expect(error.toString()).to.include(code);
+      }
+    };
+
+    const national = await createElection();
+    const district = (names = candidates) =>
+      createElection(defaultConfig({ rollupParent: national }), names);
+    const north = await district();
+    const south = await district();
+    for (const [election, ids] of [
+      [north, [0, 0, 1]],
+      [south, [1, 1, 1, 2]],
+    ] as [PublicKey, number[]][]) {
+      for (const id of ids) {
+        await castVote(election, await createVoter(election), id);
+      }
+    }
+
+    // A running child can't be rolled up yet
+    await endVoting(national);
+    await expectAggregateError(north, national, "VotingNotEnded");
+
+    await endVoting(north);
+    await endVoting(south);
+    await aggregate(north, national);
+    await aggregate(south, national);
+    const votingData = await program.account.votingAccount.fetch(national);
+    expect(voteCounts(votingData)).to.deep.equal([2, 4, 1]);
+    expect(votingData.totalVotes.toNumber()).to.equal(7);
+    expect(votingData.winnerIndex).to.equal(1);
+
+    await expectAggregateError(north, national, "AlreadyAggregated");
+
+    const mismatched = await district(["Alice", "Charlie", "Bob"]);
+    await endVoting(mismatched);
+    await expectAggregateError(mismatched, national, "CandidateMismatch");
+  });
+
+  /**
+   * Test Case 97: Decay floor
+   *
+   * This test starts an election decaying 90% a day almost a day in the past, with votes
+   * keeping at least 50% of their weight. It verifies that an early weighted vote counts
+   * in full while the same balance voting after the first full day counts half (not 10%),
+   * and that a floor above 10,000 basis points is rejected
+   */
+  it("Clamps decayed vote weight to the configured minimum", async () => {
+    const day = 24 * 60 * 60;
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const startTime = (await clusterTime()) - day + 20;
+    const { electionAccount, voteMint, tokenAccount, voterAccount } =
+      await setupWeightedElection(1000, { clamp: {} }, 1000, {
+        startTime: new anchor.BN(startTime),
+        decayRateBpsPerDay: 9000,
+        minDecayWeightBps: 5000,
+      });
+    const election = electionAccount.publicKey;
+    const voteWeighted = (
+      voter: Keypair,
+      userAccount: PublicKey,
+      voterTokenAccount: PublicKey
+    ) =>
+      program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: voter.publicKey,
+          tokenAccount: voterTokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+
+    // Early: the full balance of 1000 counts
+    await voteWeighted(payer, voterAccount, tokenAccount);
+    let votingData = await program.account.votingAccount.fetch(election);
+    const early = voteCounts(votingData)[0];
+    expect(early).to.equal(1000);
+
+    // Late: after a full day decay would leave 10%, the floor keeps 50%
+    const lateVoter = await createFundedKeypair(1);
+    const lateTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      lateVoter.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(provider.connection, payer, voteMint, lateTokenAccount, payer, 1000);
+    await registerVoter(election, lateVoter, lateTokenAccount);
+    while ((await clusterTime()) < startTime + day + 1) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    await voteWeighted(
+      lateVoter,
+      userAccountFor(election, lateVoter.publicKey),
+      lateTokenAccount
+    );
+    votingData = await program.account.votingAccount.fetch(election);
+    const late = voteCounts(votingData)[0] - early;
+    expect(late).to.equal(500);
+    expect(late).to.be.below(early);
+
+    try {
+      await createElection(defaultConfig({ minDecayWeightBps: 10001 }));
+      expect.fail("Expected a floor above 100% to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidDecayFloor");
+    }
+  });
+
+  /**
+   * Test Case 98: Registration period
+   *
+   * This test verifies that with a registration period of 3 seconds:
+   * - The start time is moved to init_time + 3
+   * - Voters can register straight away but their votes fail with
+   *   RegistrationPeriodActive until the period is over, and count afterwards
+   */
+  it("Holds votes back until the registration period is over", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const election = await createElection(
+      defaultConfig({ registrationPeriod: new anchor.BN(3) })
+    );
+    let votingData = await program.account.votingAccount.fetch(election);
+    const opensAt = votingData.initTime.toNumber() + 3;
+    expect(votingData.startTime.toNumber()).to.equal(opensAt);
+
+    const voter = await createVoter(election);
+    try {
+      await castVote(election, voter, 0);
+      expect.fail("Expected a vote during the registration period to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("RegistrationPeriodActive");
+    }
+
+    while ((await clusterTime()) <= opensAt) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    await castVote(election, voter, 0);
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([1, 0, 0]);
+  });
+
+  /**
+   * Test Case 99: Packed results export
+   *
+   * This test verifies that export_packed emits a PackedResults event whose data is a
+   * little-endian u32 candidate count followed by one little-endian u64 count per
+   * candidate, which decodes back to the election's vote counts
+   */
+  it("Exports the vote counts as a packed byte blob", async () => {
+    const election = await createElection();
+    for (const id of [2, 0, 2]) {
+      await castVote(election, await createVoter(election), id);
+    }
+
+    // Listen for the PackedResults event before exporting
+    let packedEvent = null;
+    const listener = program.addEventListener("PackedResults", (event) => {
+      packedEvent = event;
+    });
+    await program.methods.exportPacked().accounts({ votingAccount: election }).rpc();
+
+    // Give the listener a moment to receive the event, then decode it
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    expect(packedEvent).to.not.be.null;
+    expect(packedEvent.votingAccount.toBase58()).to.equal(election.toBase58());
+    const data = Buffer.from(packedEvent.data);
+    const count = data.readUInt32LE(0);
+    expect(data.length).to.equal(4 + 8 * count);
+    const decoded = Array.from({ length: count }, (_, i) =>
+      Number(data.readBigUInt64LE(4 + 8 * i))
+    );
+    expect(decoded).to.deep.equal([1, 0, 2]);
+  });
+
+  /**
+   * Test Case 100: Migrating a legacy user account
+   *
+   * This test uses a fixture holding an original 8 + 1 byte user account (has_voted set)
+   * at the [b"user", voter] PDA of a voter derived from a fixed seed, and verifies that:
+   * - It can't be read with the current layout until migrate_user_account grows it
+   * - Only its voter can migrate it; another signer fails the seeds check
+   * - After migrating, has_voted survives, voter is set and the new fields hold their
+   *   defaults (votedFor null, round 0, no election)
+   * - The migrated account is read-only: voting with it fails the seeds check
+   * - A second migration fails with AlreadyMigrated
+   */
+  it("Migrates a user account from the original layout", async () => {
+    const legacyUser = new PublicKey("AyB9HVhLaz63rBbiGQho4gPLbHFtYxYitViBUh4rYbka");
+    const legacyVoter = Keypair.fromSeed(
+      createHash("sha256").update("legacy-voter").digest()
+    );
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(
+        legacyVoter.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      )
+    );
+    const migrate = (voter: Keypair) =>
+      program.methods
+        .migrateUserAccount()
+        .accounts({
+          userAccount: legacyUser,
+          voter: voter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+    // The old layout can't be read as the current one
+    try {
+      await program.account.userAccount.fetch(legacyUser);
+      expect.fail("Expected the old layout to fail to deserialize");
+    } catch (error) {
+      expect(error.toString()).to.not.include("Expected the old layout");
+    }
+
+    // Someone else can't migrate (and pay to grow) the voter's account
+    try {
+      await migrate(await createFundedKeypair());
+      expect.fail("Expected migration by another signer to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+
+    await migrate(legacyVoter);
+    const userData = await program.account.userAccount.fetch(legacyUser);
+    expect(userData.hasVoted).to.be.true;
+    expect(userData.voter.toBase58()).to.equal(legacyVoter.publicKey.toBase58());
+    expect(userData.election.toBase58()).to.equal(PublicKey.default.toBase58());
+    expect(userData.votedFor).to.be.null;
+    expect(userData.round.toNumber()).to.equal(0);
+    expect(userData.eligibleCandidates).to.deep.equal([]);
+    const accountInfo = await provider.connection.getAccountInfo(legacyUser);
+    expect(accountInfo.data.length).to.equal(USER_ACCOUNT_SPACE);
+
+    // The migrated account isn't any election's user account PDA, so it can't vote
+    const election = await createElection();
+    try {
+      await program.methods
+        .vote(new anchor.BN(0), new anchor.BN(0), null)
+        .accounts({
+          votingAccount: election,
+          userAccount: legacyUser,
+          user: legacyVoter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([legacyVoter])
+        .rpc();
+      expect.fail("Expected voting with a migrated account to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("ConstraintSeeds");
+    }
+
+    try {
+      await migrate(legacyVoter);
+      expect.fail("Expected the second migration to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyMigrated");
+    }
+  });
+
+  /**
+   * Test Case 101: Layout version of new elections
+   *
+   * This test verifies that a freshly initialized election is stamped with the current
+   * layout version, so migrate_voting_account refuses it with AlreadyMigrated
+   */
+  it("Stamps new elections with the current layout version", async () => {
+    const election = await createElection();
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.version).to.equal(VOTING_ACCOUNT_VERSION);
+
+    try {
+      await program.methods
+        .migrateVotingAccount()
+        .accounts({
+          votingAccount: election,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          programData: null,
+        })
+        .rpc();
+      expect.fail("Expected a new election not to need migrating");
+    } catch (error) {
+      expect(error.toString()).to.include("AlreadyMigrated");
+    }
+  });
+
+  /**
+   * Test Case 102: The per-slot cap covers weighted votes
+   *
+   * This test verifies that with max_votes_per_slot = 1, a transaction casting two
+   * token-weighted votes fails with RateLimited and counts nothing, while a single
+   * weighted vote is accepted and counted against the slot
+   */
+  it("Applies the per-slot cap to weighted votes", async () => {
+    const { electionAccount, voteMint, tokenAccount, voterAccount } =
+      await setupWeightedElection(1000, { clamp: {} }, 100, { maxVotesPerSlot: 1 });
+    const election = electionAccount.publicKey;
+
+    // A second registered voter with their own balance
+    const secondVoter = await createFundedKeypair(1);
+    const secondTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      voteMint,
+      secondVoter.publicKey,
+      anchor.web3.Keypair.generate()
+    );
+    await mintTo(provider.connection, payer, voteMint, secondTokenAccount, payer, 100);
+    const secondVoterAccount = await registerVoter(election, secondVoter, secondTokenAccount);
+
+    const voteWeightedIx = (
+      voter: Keypair,
+      userAccount: PublicKey,
+      voterTokenAccount: PublicKey
+    ) =>
+      program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          userAccount,
+          user: voter.publicKey,
+          tokenAccount: voterTokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .instruction();
+
+    // Two weighted votes in one slot exceed the cap
+    const tx = new anchor.web3.Transaction()
+      .add(await voteWeightedIx(payer, voterAccount, tokenAccount))
+      .add(await voteWeightedIx(secondVoter, secondVoterAccount, secondTokenAccount));
+    try {
+      await provider.sendAndConfirm(tx, [secondVoter]);
+      expect.fail("Expected transaction to fail due to the per-slot cap");
+    } catch (error) {
+      // Raw transactions report program errors through the logs
+      expect(error.logs.join("\n")).to.include("RateLimited");
+    }
+    let votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+
+    // One weighted vote fits
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(await voteWeightedIx(payer, voterAccount, tokenAccount))
+    );
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.totalVotes.toNumber()).to.equal(100);
+    expect(votingData.votesThisSlot).to.equal(1);
+  });
+
+  /**
+   * Test Case 103: Elections requiring a reason refuse weighted votes
+   *
+   * This test verifies that in an election with require_reason set, a token-weighted
+   * vote (which can't carry a reason) fails with ReasonRequired and counts nothing
+   */
+  it("Rejects weighted votes in elections that require a reason", async () => {
+    const { electionAccount, tokenAccount, voterAccount } = await setupWeightedElection(
+      1000,
+      { clamp: {} },
+      100,
+      { requireReason: true }
+    );
+    try {
+      await program.methods
+        .voteWeighted(0, new anchor.BN(0))
+        .accounts({
+          votingAccount: electionAccount.publicKey,
+          userAccount: voterAccount,
+          user: provider.wallet.publicKey,
+          tokenAccount,
+          tokenSnapshot: null,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .rpc();
+      expect.fail("Expected a weighted vote to be rejected");
+    } catch (error) {
+      expect(error.toString()).to.include("ReasonRequired");
+    }
+    const votingData = await program.account.votingAccount.fetch(electionAccount.publicKey);
+    expect(votingData.totalVotes.toNumber()).to.equal(0);
+  });
+
+  /**
+   * Test Case 104: Undoing a blinded vote
+   *
+   * This test verifies that undo_vote XORs the vote's blinded increment back out of
+   * the candidate's encrypted_tally slot, leaving another voter's increment in place
+   */
+  it("Takes an undone vote's blinded increment out of the encrypted tally", async () => {
+    const election = await createElection(defaultConfig({ undoWindow: new anchor.BN(60) }));
+    const keptBlind = Array.from({ length: 32 }, (_, i) => i);
+    const undoneBlind = Array.from({ length: 32 }, () => 0xff);
+    const voters = [];
+    for (const blindedIncrement of [keptBlind, undoneBlind]) {
+      const voter = await createVoter(election);
+      await program.methods
+        .vote(new anchor.BN(1), new anchor.BN(0), blindedIncrement)
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          roster: null,
+        })
+        .signers([voter])
+        .rpc();
+      voters.push(voter);
+    }
+
+    await program.methods
+      .undoVote()
+      .accounts({
+        votingAccount: election,
+        userAccount: userAccountFor(election, voters[1].publicKey),
+        user: voters[1].publicKey,
+      })
+      .signers([voters[1]])
+      .rpc();
+
+    // Only the remaining vote's increment is left in Bob's slot
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(Array.from(votingData.encryptedTally[1])).to.deep.equal(keptBlind);
+    expect(voteCounts(votingData)[1]).to.equal(1);
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, voters[1].publicKey)
+    );
+    expect(userData.lastVoteBlindedIncrement).to.be.null;
+  });
+
+  /**
+   * Test Case 105: Undoing a vote restores reached_max_at
+   *
+   * This test verifies that undo_vote gives the candidate back the reached_max_at it had
+   * before the undone vote, so an EarliestToReach tie-break isn't skewed by a vote that
+   * no longer counts
+   */
+  it("Restores reached_max_at when a vote is undone", async () => {
+    const election = await createElection(
+      defaultConfig({ undoWindow: new anchor.BN(60), tieBreak: { earliestToReach: {} } })
+    );
+    const reachedMaxAt = async () =>
+      (await program.account.votingAccount.fetch(election)).reachedMaxAt.map((at) =>
+        at.toNumber()
+      );
+
+    // A first vote for Alice sets her timestamp
+    await castVote(election, await createVoter(election), 0);
+    const [before] = await reachedMaxAt();
+    expect(before).to.be.above(0);
+
+    // Make sure the second vote lands in a later second
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    while ((await clusterTime()) <= before) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    const voter = await createVoter(election);
+    await castVote(election, voter, 0);
+    expect((await reachedMaxAt())[0]).to.be.above(before);
+
+    // Undoing the second vote brings back the first vote's timestamp
+    await program.methods
+      .undoVote()
+      .accounts({
+        votingAccount: election,
+        userAccount: userAccountFor(election, voter.publicKey),
+        user: voter.publicKey,
+      })
+      .signers([voter])
+      .rpc();
+    expect((await reachedMaxAt())[0]).to.equal(before);
+
+    // The only voter's undo clears it entirely
+    const onlyElection = await createElection(
+      defaultConfig({ undoWindow: new anchor.BN(60), tieBreak: { earliestToReach: {} } })
+    );
+    const onlyVoter = await createVoter(onlyElection);
+    await castVote(onlyElection, onlyVoter, 2);
+    await program.methods
+      .undoVote()
+      .accounts({
+        votingAccount: onlyElection,
+        userAccount: userAccountFor(onlyElection, onlyVoter.publicKey),
+        user: onlyVoter.publicKey,
+      })
+      .signers([onlyVoter])
+      .rpc();
+    const onlyData = await program.account.votingAccount.fetch(onlyElection);
+    expect(onlyData.reachedMaxAt[2].toNumber()).to.equal(0);
+  });
+
+  /**
+   * Test Case 106: Ranked ballots go through the voter checks
+   *
+   * This test verifies that cast_ranked_ballot:
+   * - Can't be used without a registered user account, so in an AuthorityOnly election
+   *   a voter whose self-registration was refused can't rank, while one registered with
+   *   the authority's co-signature can
+   * - Is refused with VotingExpired once the deadline has passed
+   */
+  it("Applies the registration and deadline checks to ranked ballots", async () => {
+    const castRanked = (election: PublicKey, voter: Keypair) =>
+      program.methods
+        .castRankedBallot([0, 1], new anchor.BN(0))
+        .accounts({
+          votingAccount: election,
+          rankedBallot: PublicKey.findProgramAddressSync(
+            [Buffer.from("ranked"), election.toBuffer(), voter.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([voter])
+        .rpc();
+
+    // AuthorityOnly: the unregistered voter has no user account to rank with
+    const closedElection = await createElection(
+      defaultConfig({ registrationMode: { authorityOnly: {} } })
+    );
+    const outsider = await createFundedKeypair(1);
+    try {
+      await registerVoter(closedElection, outsider);
+      expect.fail("Expected self-registration to be refused");
+    } catch (error) {
+      expect(error.toString()).to.include("RegistrationClosed");
+    }
+    try {
+      await castRanked(closedElection, outsider);
+      expect.fail("Expected a ranked ballot without registration to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("AccountNotInitialized");
+    }
+
+    // The authority's co-signature registers the voter, who can then rank
+    const member = await createFundedKeypair(1);
+    await program.methods
+      .initializeUserAccount()
+      .accounts({
+        votingAccount: closedElection,
+        userAccount: userAccountFor(closedElection, member.publicKey),
+        user: member.publicKey,
+        authority: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenAccount: null,
+      })
+      .signers([member])
+      .rpc();
+    await castRanked(closedElection, member);
+    let votingData = await program.account.votingAccount.fetch(closedElection);
+    expect(votingData.rankedBallotCount.toNumber()).to.equal(1);
+
+    // After the deadline a ranked ballot is refused
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const deadline = (await clusterTime()) + 2;
+    const election = await createElection(
+      defaultConfig({ endTime: new anchor.BN(deadline) })
+    );
+    const lateVoter = await createVoter(election);
+    while ((await clusterTime()) <= deadline) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+    try {
+      await castRanked(election, lateVoter);
+      expect.fail("Expected a ranked ballot after the deadline to fail");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingExpired");
+    }
+    votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.rankedBallotCount.toNumber()).to.equal(0);
+  });
+
+  /**
+   * Test Case 107: Released elections stay ended
+   *
+   * This test verifies that once release_election has taken an ended election out of
+   * its registry's active count, reopen_voting fails with ElectionAlreadyReleased even
+   * within the reopen window, so no running election escapes max_active_elections
+   */
+  it("Refuses to reopen a released election", async () => {
+    const authority = await createFundedKeypair();
+    const [registry] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const [election] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("election"),
+        authority.publicKey.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const adminAccounts = { votingAccount: election, authority: authority.publicKey };
+
+    await program.methods
+      .initializeRegistry(new anchor.BN(10))
+      .accounts({
+        registry,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods
+      .createElection(candidates, defaultConfig({ reopenWindow: new anchor.BN(600) }))
+      .accounts({
+        registry,
+        votingAccount: election,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+    await program.methods.endVoting().accounts(adminAccounts).signers([authority]).rpc();
+    await program.methods
+      .releaseElection()
+      .accounts({ votingAccount: election, registry })
+      .rpc();
+
+    try {
+      await program.methods.reopenVoting().accounts(adminAccounts).signers([authority]).rpc();
+      expect.fail("Expected reopen_voting to fail for a released election");
+    } catch (error) {
+      expect(error.toString()).to.include("ElectionAlreadyReleased");
+    }
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(votingData.status).to.deep.equal({ ended: {} });
+    expect(votingData.released).to.be.true;
+  });
+
+  /**
+   * Test Case 108: Batches and undos after the deadline
+   *
+   * This test verifies that once an election's end_time deadline has passed:
+   * - submit_batch fails with VotingExpired and counts nothing
+   * - undo_vote fails with VotingExpired, even within the undo window, and the vote stands
+   */
+  it("Refuses batches and undos after the deadline", async () => {
+    const clusterTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const deadline = (await clusterTime()) + 4;
+    const election = await createElection(
+      defaultConfig({ endTime: new anchor.BN(deadline), undoWindow: new anchor.BN(600) })
+    );
+    const voter = await createVoter(election);
+    await castVote(election, voter, 2);
+
+    // Wait until the cluster clock is past the deadline
+    while ((await clusterTime()) <= deadline) {
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    }
+
+    try {
+      await program.methods
+        .submitBatch([0, 1], new anchor.BN(0))
+        .accounts({ votingAccount: election, authority: provider.wallet.publicKey })
+        .rpc();
+      expect.fail("Expected submit_batch to fail after the deadline");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingExpired");
+    }
+
+    try {
+      await program.methods
+        .undoVote()
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+        })
+        .signers([voter])
+        .rpc();
+      expect.fail("Expected undo_vote to fail after the deadline");
+    } catch (error) {
+      expect(error.toString()).to.include("VotingExpired");
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([0, 0, 1]);
+    expect(votingData.totalVotes.toNumber()).to.equal(1);
+    const userData = await program.account.userAccount.fetch(
+      userAccountFor(election, voter.publicKey)
+    );
+    expect(userData.hasVoted).to.be.true;
+  });
+
+  /**
+   * Test Case 109: Undoing a vote for a candidate taken off the ballot
+   *
+   * This test verifies that within the undo window undo_vote still refuses a vote whose
+   * candidate has since been disqualified (CandidateDisqualified) or withdrawn
+   * (CandidateDisabled), and the vote stands
+   */
+  it("Refuses to undo votes for disqualified or withdrawn candidates", async () => {
+    const election = await createElection(
+      defaultConfig({ undoWindow: new anchor.BN(600) })
+    );
+    const adminAccounts = { votingAccount: election, authority: provider.wallet.publicKey };
+    const undoVote = (voter: Keypair) =>
+      program.methods
+        .undoVote()
+        .accounts({
+          votingAccount: election,
+          userAccount: userAccountFor(election, voter.publicKey),
+          user: voter.publicKey,
+        })
+        .signers([voter])
+        .rpc();
+
+    const disqualifiedVoter = await createVoter(election);
+    await castVote(election, disqualifiedVoter, 0);
+    const withdrawnVoter = await createVoter(election);
+    await castVote(election, withdrawnVoter, 1);
+
+    await program.methods.disqualifyCandidate(0).accounts(adminAccounts).rpc();
+    await program.methods.withdrawCandidate(1).accounts(adminAccounts).rpc();
+
+    try {
+      await undoVote(disqualifiedVoter);
+      expect.fail("Expected undo_vote to fail for a disqualified candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateDisqualified");
+    }
+    try {
+      await undoVote(withdrawnVoter);
+      expect.fail("Expected undo_vote to fail for a withdrawn candidate");
+    } catch (error) {
+      expect(error.toString()).to.include("CandidateDisabled");
+    }
+
+    const votingData = await program.account.votingAccount.fetch(election);
+    expect(voteCounts(votingData)).to.deep.equal([1, 0, 0]);
+    expect(votingData.voterCount.toNumber()).to.equal(2);
+    for (const voter of [disqualifiedVoter, withdrawnVoter]) {
+      const userData = await program.account.userAccount.fetch(
+        userAccountFor(election, voter.publicKey)
+      );
+      expect(userData.hasVoted).to.be.true;
+    }
   });
 });